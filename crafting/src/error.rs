@@ -1,4 +1,4 @@
-#[derive(thiserror::Error, Debug)]
+#[derive(thiserror::Error, strum::IntoStaticStr, Debug)]
 pub enum Error {
     #[error("Invalid Gear Set: {0}")]
     InvalidGearSet(String),
@@ -12,4 +12,16 @@ pub enum Error {
     Postgres(#[from] sqlx::Error),
     #[error(transparent)]
     JsonError(#[from] serde_json::Error)
-}
\ No newline at end of file
+}
+
+impl Error {
+    /// Explanation for the member whose request failed, `None` for internal errors
+    pub fn user_message_es(&self) -> Option<String> {
+        match self {
+            Error::InvalidGearSet(set) => Some(format!("El set `{set}` no existe, eligelo de la lista")),
+            Error::Timeout => Some("Se ha agotado el tiempo para responder, vuelve a empezar".to_string()),
+            Error::InvalidTrait => Some("El rasgo elegido no es valido para esa pieza".to_string()),
+            _ => None
+        }
+    }
+}
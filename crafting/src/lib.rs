@@ -4,6 +4,8 @@ mod error;
 mod prelude;
 mod store;
 
+pub use error::Error;
+
 use serenity::all::{ButtonStyle, CommandInteraction, CommandOptionType, CommandType, Context, CreateAutocompleteResponse, CreateCommandOption, CreateInteractionResponse, CreateInteractionResponseMessage, GuildId};
 use serenity::builder::{CreateButton, CreateCommand};
 use prelude::*;
@@ -14,8 +16,8 @@ use crate::sets::jewelry::jewelry_set_request;
 use crate::sets::request::GearRequest;
 use crate::sets::weapons::weapon_set_request;
 
-pub async fn gear_set_autocomplete(command: CommandInteraction, ctx: &Context) -> serenity::Result<()> {
-    let option = command.data.options.first().unwrap();
+pub async fn gear_set_autocomplete(command: CommandInteraction, ctx: &Context) -> Result<()> {
+    let option = command.data.options.first().ok_or(Error::InvalidGearSet("None".to_string()))?;
     let value = option.value.as_str().unwrap_or("");
     let choices = sets::gear_sets()
         .into_iter()
@@ -29,11 +31,11 @@ pub async fn gear_set_autocomplete(command: CommandInteraction, ctx: &Context) -
             .set_choices(choices)
     );
 
-    command.create_response(&ctx.http, response).await
+    Ok(command.create_response(&ctx.http, response).await?)
 }
 
 pub async fn gear_set_request(command: &CommandInteraction, ctx: &Context) -> Result<()> {
-    let option = command.data.options.first().unwrap();
+    let option = command.data.options.first().ok_or(Error::InvalidGearSet("None".to_string()))?;
     let value = option.value.as_str().ok_or(Error::InvalidGearSet("None".to_string()))?;
     let mut request = GearRequest::new(GearSet::try_from(value.to_string())?);
 
@@ -47,7 +49,9 @@ pub async fn gear_set_request(command: &CommandInteraction, ctx: &Context) -> Re
     let response = command.get_response(&ctx.http).await?;
 
     let interaction = response.await_component_interaction(&ctx).await.ok_or(Error::Timeout)?;
-    request.with_quality(get_selected_gear::<GearQuality>(&interaction).pop().unwrap());
+    if let Some(quality) = get_selected_gear::<GearQuality>(&interaction).pop() {
+        request.with_quality(quality);
+    }
     interaction.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(
         sets::request_menu()
             .embed(request.to_embed_preview())
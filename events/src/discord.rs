@@ -0,0 +1,111 @@
+//! Discord side effects used by the event flows, behind a trait so they can be faked in tests
+use std::time::Duration;
+use serenity::all::{ChannelId, ComponentInteraction, ComponentInteractionCollector, Context, CreateInteractionResponse, CreateInteractionResponseFollowup, CreateMessage, CreateScheduledEvent, EditMessage, EditScheduledEvent, EditThread, GetMessages, GuildId, InteractionId, Member, Message, MessageId, ModalInteraction, ModalInteractionCollector, ScheduledEvent, ScheduledEventId, UserId};
+use serenity::async_trait;
+use serenity::builder::Builder;
+use crate::prelude::*;
+
+#[async_trait]
+pub trait Discord: Send + Sync {
+    async fn send_message(&self, channel: ChannelId, message: CreateMessage) -> Result<Message>;
+    async fn edit_message(&self, channel: ChannelId, message: MessageId, edit: EditMessage) -> Result<Message>;
+    async fn channel_messages(&self, channel: ChannelId) -> Result<Vec<Message>>;
+    async fn delete_messages(&self, channel: ChannelId, messages: Vec<MessageId>) -> Result<()>;
+    async fn create_response(&self, interaction: InteractionId, token: &str, response: CreateInteractionResponse) -> Result<()>;
+    async fn get_response(&self, token: &str) -> Result<Message>;
+    /// Extra message for an interaction that already got its response
+    async fn create_followup(&self, token: &str, followup: CreateInteractionResponseFollowup) -> Result<Message>;
+    async fn member(&self, guild: GuildId, user: UserId) -> Result<Member>;
+    async fn dm(&self, user: UserId, message: CreateMessage) -> Result<Message>;
+    async fn scheduled_event(&self, guild: GuildId, event: ScheduledEventId) -> Result<ScheduledEvent>;
+    async fn scheduled_events(&self, guild: GuildId) -> Result<Vec<ScheduledEvent>>;
+    async fn create_scheduled_event(&self, guild: GuildId, event: CreateScheduledEvent<'_>) -> Result<ScheduledEvent>;
+    async fn edit_scheduled_event(&self, guild: GuildId, event: ScheduledEventId, edit: EditScheduledEvent<'_>) -> Result<ScheduledEvent>;
+    async fn delete_scheduled_event(&self, guild: GuildId, event: ScheduledEventId) -> Result<()>;
+    async fn archive_thread(&self, thread: ChannelId) -> Result<()>;
+    /// Next component interaction on `message`, `None` when the wait times out
+    async fn await_component(&self, message: MessageId, timeout: Option<Duration>) -> Option<ComponentInteraction>;
+    /// Next modal submitted from `message`, `None` when the wait times out
+    async fn await_modal(&self, message: MessageId, timeout: Option<Duration>) -> Option<ModalInteraction>;
+}
+
+#[async_trait]
+impl Discord for Context {
+    async fn send_message(&self, channel: ChannelId, message: CreateMessage) -> Result<Message> {
+        Ok(channel.send_message(&self.http, message).await?)
+    }
+
+    async fn edit_message(&self, channel: ChannelId, message: MessageId, edit: EditMessage) -> Result<Message> {
+        Ok(channel.edit_message(&self.http, message, edit).await?)
+    }
+
+    async fn channel_messages(&self, channel: ChannelId) -> Result<Vec<Message>> {
+        Ok(channel.messages(&self.http, GetMessages::new()).await?)
+    }
+
+    async fn delete_messages(&self, channel: ChannelId, messages: Vec<MessageId>) -> Result<()> {
+        Ok(channel.delete_messages(&self.http, messages).await?)
+    }
+
+    async fn create_response(&self, interaction: InteractionId, token: &str, response: CreateInteractionResponse) -> Result<()> {
+        Ok(response.execute(&self.http, (interaction, token)).await?)
+    }
+
+    async fn get_response(&self, token: &str) -> Result<Message> {
+        Ok(self.http.get_original_interaction_response(token).await?)
+    }
+
+    async fn create_followup(&self, token: &str, followup: CreateInteractionResponseFollowup) -> Result<Message> {
+        Ok(followup.execute(&self.http, (None, token)).await?)
+    }
+
+    async fn member(&self, guild: GuildId, user: UserId) -> Result<Member> {
+        Ok(guild.member(&self.http, user).await?)
+    }
+
+    async fn dm(&self, user: UserId, message: CreateMessage) -> Result<Message> {
+        let dm = user.create_dm_channel(&self.http).await?;
+        Ok(dm.send_message(&self.http, message).await?)
+    }
+
+    async fn scheduled_event(&self, guild: GuildId, event: ScheduledEventId) -> Result<ScheduledEvent> {
+        Ok(guild.scheduled_event(&self.http, event, false).await?)
+    }
+
+    async fn scheduled_events(&self, guild: GuildId) -> Result<Vec<ScheduledEvent>> {
+        Ok(guild.scheduled_events(&self.http, false).await?)
+    }
+
+    async fn create_scheduled_event(&self, guild: GuildId, event: CreateScheduledEvent<'_>) -> Result<ScheduledEvent> {
+        Ok(guild.create_scheduled_event(&self.http, event).await?)
+    }
+
+    async fn edit_scheduled_event(&self, guild: GuildId, event: ScheduledEventId, edit: EditScheduledEvent<'_>) -> Result<ScheduledEvent> {
+        Ok(guild.edit_scheduled_event(&self.http, event, edit).await?)
+    }
+
+    async fn delete_scheduled_event(&self, guild: GuildId, event: ScheduledEventId) -> Result<()> {
+        Ok(guild.delete_scheduled_event(&self.http, event).await?)
+    }
+
+    async fn archive_thread(&self, thread: ChannelId) -> Result<()> {
+        thread.edit_thread(&self.http, EditThread::new().archived(true)).await?;
+        Ok(())
+    }
+
+    async fn await_component(&self, message: MessageId, timeout: Option<Duration>) -> Option<ComponentInteraction> {
+        let collector = ComponentInteractionCollector::new(&self.shard).message_id(message);
+        match timeout {
+            Some(timeout) => collector.timeout(timeout).await,
+            None => collector.await
+        }
+    }
+
+    async fn await_modal(&self, message: MessageId, timeout: Option<Duration>) -> Option<ModalInteraction> {
+        let collector = ModalInteractionCollector::new(&self.shard).message_id(message);
+        match timeout {
+            Some(timeout) => collector.timeout(timeout).await,
+            None => collector.await
+        }
+    }
+}
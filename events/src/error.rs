@@ -1,9 +1,11 @@
-#[derive(thiserror::Error, Debug)]
+/// Variants are labels of the error metrics, see [`crate::metrics::record_error`]
+#[derive(thiserror::Error, strum::IntoStaticStr, Debug)]
 pub enum Error {
     #[error("{0}")]
     DurationParse(String),
+    /// Boxed, it's by far the largest variant and would bloat every `Result` of the crate
     #[error(transparent)]
-    Serenity(#[from] serenity::prelude::SerenityError),
+    Serenity(Box<serenity::prelude::SerenityError>),
     #[error(transparent)]
     Base64(#[from] base64::DecodeError),
     #[error(transparent)]
@@ -30,6 +32,45 @@ pub enum Error {
     ParseEvent(String),
     #[error("Interaction Timeout")]
     Timeout,
+    #[error("Eligibility rule not valid: `{0}`")]
+    ParseRule(String),
+    #[error("Class quota not valid: `{0}`")]
+    ParseQuota(String),
+    #[error("ESO account not valid: `{0}`")]
+    InvalidEsoAccount(String),
+    #[error("Image not found: `{0}`")]
+    UnknownImage(String),
     #[error("Not a day channel: `{0}`")]
     NotDay(String),
-}
\ No newline at end of file
+    #[error("Date not valid: `{0}`")]
+    InvalidDate(String),
+    #[error("Gold amount not valid: `{0}`")]
+    InvalidAmount(String),
+}
+
+impl From<serenity::prelude::SerenityError> for Error {
+    fn from(error: serenity::prelude::SerenityError) -> Self {
+        Error::Serenity(Box::new(error))
+    }
+}
+
+impl Error {
+    /// Explanation for the member whose interaction failed, `None` for internal errors the admins have to look at
+    pub fn user_message_es(&self) -> Option<String> {
+        match self {
+            Error::DurationParse(duration) => Some(format!("La duracion `{duration}` no es valida, usa por ejemplo `2h` o `1h30m`")),
+            Error::RoleFull(role) => Some(format!("El rol {role} esta lleno")),
+            Error::UnknownClass(class) => Some(format!("La clase `{class}` no existe")),
+            Error::ParseEvent(event) => Some(format!("No se ha podido leer el evento: {event}")),
+            Error::Timeout => Some("Se ha agotado el tiempo para responder, vuelve a empezar".to_string()),
+            Error::ParseRule(rule) => Some(format!("La regla `{rule}` no es valida")),
+            Error::ParseQuota(quota) => Some(format!("El cupo `{quota}` no es valido")),
+            Error::InvalidEsoAccount(account) => Some(format!("La cuenta de ESO `{account}` no es valida, usa el formato `@Cuenta`")),
+            Error::UnknownImage(image) => Some(format!("No se encuentra la imagen `{image}`")),
+            Error::NotDay(channel) => Some(format!("El canal `{channel}` no es un canal de dia de eventos")),
+            Error::InvalidDate(date) => Some(format!("La fecha `{date}` no es valida, usa `dd/mm` o `dd/mm/aaaa`")),
+            Error::InvalidAmount(amount) => Some(format!("La cantidad `{amount}` no es valida, usa por ejemplo `3000` o `3k`")),
+            _ => None
+        }
+    }
+}
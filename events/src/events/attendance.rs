@@ -0,0 +1,61 @@
+use chrono::{DateTime, Duration, Utc};
+use serenity::all::{ChannelId, UserId};
+use super::{Event, Player};
+
+/// Minutes before the start when being in the voice channel already counts
+pub const VOICE_EARLY_MINUTES: i64 = 30;
+/// Minutes after the start when the players that join are late
+pub const VOICE_LATE_MINUTES: i64 = 10;
+
+/// Time a member spent in the voice channel of an event, `left` is empty while still connected
+#[derive(Debug, Clone)]
+pub struct VoiceSession {
+    pub user: UserId,
+    pub channel: ChannelId,
+    pub joined: DateTime<Utc>,
+    pub left: Option<DateTime<Utc>>
+}
+
+/// Attendance of the starters guessed from the voice channel, for the leader to confirm
+#[derive(Debug, Default)]
+pub struct AttendanceProposal {
+    pub present: Vec<Player>,
+    /// Late players with the minutes they took to join
+    pub late: Vec<(Player, i64)>,
+    pub missing: Vec<Player>
+}
+
+impl Event {
+    /// Time the voice channel is watched for the event
+    pub fn voice_window(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        Some((self.datetime? - Duration::try_minutes(VOICE_EARLY_MINUTES)?, self.end()?))
+    }
+}
+
+impl AttendanceProposal {
+    pub fn new(event: &Event, sessions: &[VoiceSession]) -> Self {
+        let mut proposal = AttendanceProposal::default();
+        let (Some(start), Some((opens, closes))) = (event.datetime, event.voice_window()) else { return proposal };
+
+        let starters = event.roles.iter()
+            .filter(|pr| !pr.role.is_backup_role())
+            .flat_map(|pr| pr.players.iter());
+        for player in starters {
+            let arrival = sessions.iter()
+                .filter(|s| s.user == player.id && s.joined < closes && s.left.is_none_or(|left| left > opens))
+                .map(|s| s.joined.max(opens))
+                .min();
+            match arrival {
+                None => proposal.missing.push(player.clone()),
+                Some(arrival) if arrival > start + Duration::try_minutes(VOICE_LATE_MINUTES).unwrap_or_default() =>
+                    proposal.late.push((player.clone(), (arrival - start).num_minutes())),
+                Some(_) => proposal.present.push(player.clone())
+            }
+        }
+        proposal
+    }
+
+    pub fn summary_es(&self) -> String {
+        format!("{} presentes, {} tarde, {} sin presencia", self.present.len(), self.late.len(), self.missing.len())
+    }
+}
@@ -0,0 +1,99 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serenity::all::UserId;
+use super::{parse_eso_account, parse_gold};
+
+/// Unix timestamps between 2001 and 2286, smaller numbers are gold
+const MIN_TIMESTAMP: i64 = 1_000_000_000;
+const MAX_TIMESTAMP: i64 = 9_999_999_999;
+/// Words that mark a line of the history as a withdrawal
+const WITHDRAWAL_WORDS: [&str; 4] = ["withdraw", "withdrawal", "withdrew", "retirada"];
+
+/// Gold deposited to the guild bank by an ESO account
+#[derive(Debug, Clone, PartialEq)]
+pub struct BankDeposit {
+    /// Normalized `@handle`
+    pub account: String,
+    pub amount: u32,
+    pub at: DateTime<Utc>
+}
+
+/// Deposits found in an export and the lines that couldn't be read
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BankExport {
+    pub deposits: Vec<BankDeposit>,
+    /// Line numbers, starting at 1
+    pub invalid: Vec<usize>
+}
+
+/// Outcome of importing an export, deposits already imported are only counted
+#[derive(Debug, Clone, Default)]
+pub struct Reconciliation {
+    /// Deposits that went entirely to the fines of the member
+    pub matched: Vec<(BankDeposit, UserId)>,
+    /// Deposits over what the member owed, with the gold that went to the fines
+    pub overpaid: Vec<(BankDeposit, UserId, u32)>,
+    /// Deposits of accounts nobody registered
+    pub unmatched: Vec<BankDeposit>,
+    pub duplicated: usize,
+    /// Line numbers that couldn't be read
+    pub invalid: Vec<usize>
+}
+
+impl BankDeposit {
+    /// Same deposit in every export of the history, re-imports skip it
+    pub fn key(&self) -> String {
+        format!("{}|{}|{}", self.account.to_lowercase(), self.amount, self.at.timestamp())
+    }
+}
+
+/// Parses a guild bank history export, one deposit per line with the account, the gold and the time:
+///
+/// ```text
+/// @Account,10000,2026-10-19 20:30:00
+/// @Other;3.000;1760900000
+/// ```
+///
+/// Columns go in any order separated by `,`, `;` or tabs, so the exports of the guild history addons work
+/// as they are. The time is a unix timestamp, RFC 3339 or `YYYY-MM-DD HH:MM[:SS]` in UTC. Empty lines,
+/// `#` comments, headers and withdrawals are skipped, other lines without the three values are invalid
+pub fn parse_bank_export(text: &str) -> BankExport {
+    let mut export = BankExport::default();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split([',', ';', '\t'])
+            .map(|f| f.trim().trim_matches('"').trim())
+            .filter(|f| !f.is_empty())
+            .collect();
+        if fields.iter().any(|f| WITHDRAWAL_WORDS.contains(&f.to_lowercase().as_str())) {
+            continue;
+        }
+
+        let account = fields.iter().find(|f| f.starts_with('@')).and_then(|f| parse_eso_account(f).ok());
+        let at = fields.iter().find_map(|f| parse_time(f));
+        let amount = fields.iter()
+            .filter(|f| !f.starts_with('@') && parse_time(f).is_none())
+            .find_map(|f| parse_gold(f).ok());
+        match (account, amount, at) {
+            (Some(account), Some(amount), Some(at)) => export.deposits.push(BankDeposit { account, amount, at }),
+            // Header of the export
+            _ if number == 0 && !line.contains('@') => {}
+            _ => export.invalid.push(number + 1)
+        }
+    }
+    export
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(timestamp) = value.parse::<i64>() {
+        return Some(timestamp)
+            .filter(|t| (MIN_TIMESTAMP..=MAX_TIMESTAMP).contains(t))
+            .and_then(|t| DateTime::<Utc>::from_timestamp(t, 0));
+    }
+    DateTime::parse_from_rfc3339(value).map(|dt| dt.to_utc()).ok()
+        .or_else(|| ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"].iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+            .map(|dt| dt.and_utc()))
+}
@@ -0,0 +1,10 @@
+use chrono::{DateTime, Utc};
+use serenity::all::UserId;
+
+/// Entry of the change feed of an event, `actor` is `None` for changes made from Discord or by the bot
+#[derive(Debug, Clone)]
+pub struct EventChange {
+    pub actor: Option<UserId>,
+    pub description: String,
+    pub created_at: DateTime<Utc>
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use strum::EnumIter;
+
+/// Step of the event creation wizard a draft is waiting on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, EnumIter, sqlx::Type)]
+#[sqlx(type_name = "events.draft_step", rename_all = "lowercase")]
+pub enum DraftStep {
+    Info, Image, Composition, Role, Scope, Date
+}
+
+impl DraftStep {
+    pub fn next(&self) -> Option<Self> {
+        match self {
+            DraftStep::Info => Some(DraftStep::Image),
+            DraftStep::Image => Some(DraftStep::Composition),
+            DraftStep::Composition => Some(DraftStep::Role),
+            DraftStep::Role => Some(DraftStep::Scope),
+            DraftStep::Scope => Some(DraftStep::Date),
+            DraftStep::Date => None
+        }
+    }
+
+    pub fn label_es(&self) -> String {
+        match self {
+            DraftStep::Info => "Informacion",
+            DraftStep::Image => "Imagen",
+            DraftStep::Composition => "Composicion",
+            DraftStep::Role => "Rol de roster",
+            DraftStep::Scope => "Visibilidad",
+            DraftStep::Date => "Fecha y hora",
+        }.to_string()
+    }
+
+    pub fn to_id(&self) -> String {
+        format!("{self:?}").to_lowercase()
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        use strum::IntoEnumIterator;
+        Self::iter().find(|s| s.to_id() == id)
+    }
+}
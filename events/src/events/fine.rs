@@ -0,0 +1,170 @@
+use chrono::{DateTime, Datelike, Duration, DurationRound, Utc};
+use serenity::all::{MessageId, RoleId, UserId};
+use super::vacation::SPAIN_OFFSET_HOURS;
+use crate::prelude::*;
+
+/// Hours before the start when an absence of a starter is fined
+pub const LATE_ABSENCE_HOURS: i64 = 24;
+/// Spanish hour of the monday summary of the outstanding fines
+const SUMMARY_HOUR: i64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "events.fine_reason", rename_all = "snake_case")]
+pub enum FineReason {
+    /// Starter that didn't show up in the voice channel
+    NoShow,
+    /// Starter that signed absent right before the event
+    LateAbsence
+}
+
+/// Gold charged for each reason to the members with `role`, `None` is everyone without a rule of their own
+#[derive(Debug, Clone, PartialEq)]
+pub struct PenaltyRule {
+    pub role: Option<RoleId>,
+    pub no_show: u32,
+    pub late_absence: u32
+}
+
+#[derive(Debug, Clone)]
+pub struct Fine {
+    pub id: i32,
+    pub user: UserId,
+    pub name: String,
+    pub message: Option<MessageId>,
+    pub event_title: String,
+    pub reason: FineReason,
+    pub amount: u32,
+    pub created: DateTime<Utc>,
+    pub waived: bool
+}
+
+#[derive(Debug, Clone)]
+pub struct FinePayment {
+    pub id: i32,
+    pub user: UserId,
+    pub amount: u32,
+    pub paid: DateTime<Utc>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FineStatus {
+    /// Gold still owed
+    Outstanding(u32),
+    Paid,
+    Waived
+}
+
+/// Fines of a member settled with their payments, the oldest fines are paid first
+#[derive(Debug, Clone)]
+pub struct Ledger {
+    pub user: UserId,
+    pub fines: Vec<(Fine, FineStatus)>,
+    pub fined: u32,
+    pub paid: u32
+}
+
+impl FineReason {
+    pub fn label_es(&self) -> &'static str {
+        match self {
+            FineReason::NoShow => "No presentado",
+            FineReason::LateAbsence => "Ausencia de ultima hora"
+        }
+    }
+}
+
+impl PenaltyRule {
+    pub fn amount(&self, reason: FineReason) -> u32 {
+        match reason {
+            FineReason::NoShow => self.no_show,
+            FineReason::LateAbsence => self.late_absence
+        }
+    }
+}
+
+/// Fine of a member with `roles`, the cheapest rule of their roles or the default one, `None` without rules
+pub fn penalty_amount(rules: &[PenaltyRule], roles: &[RoleId], reason: FineReason) -> Option<u32> {
+    rules.iter()
+        .filter(|r| r.role.is_some_and(|role| roles.contains(&role)))
+        .map(|r| r.amount(reason))
+        .min()
+        .or_else(|| rules.iter().find(|r| r.role.is_none()).map(|r| r.amount(reason)))
+}
+
+impl Ledger {
+    pub fn new(user: UserId, fines: &[Fine], payments: &[FinePayment]) -> Self {
+        let mut fines: Vec<Fine> = fines.iter().filter(|f| f.user == user).cloned().collect();
+        fines.sort_by_key(|f| (f.created, f.id));
+        let paid: u32 = payments.iter().filter(|p| p.user == user).map(|p| p.amount).sum();
+
+        let mut left = paid;
+        let fines: Vec<(Fine, FineStatus)> = fines.into_iter()
+            .map(|fine| {
+                let status = if fine.waived { FineStatus::Waived } else {
+                    let covered = left.min(fine.amount);
+                    left -= covered;
+                    if covered == fine.amount { FineStatus::Paid } else { FineStatus::Outstanding(fine.amount - covered) }
+                };
+                (fine, status)
+            })
+            .collect();
+        let fined = fines.iter().filter(|(f, _)| !f.waived).map(|(f, _)| f.amount).sum();
+        Ledger { user, fines, fined, paid }
+    }
+
+    /// Every member with fines or payments
+    pub fn all(fines: &[Fine], payments: &[FinePayment]) -> Vec<Ledger> {
+        let mut users: Vec<UserId> = fines.iter().map(|f| f.user).chain(payments.iter().map(|p| p.user)).collect();
+        users.sort();
+        users.dedup();
+        users.into_iter().map(|user| Ledger::new(user, fines, payments)).collect()
+    }
+
+    /// Gold still owed
+    pub fn outstanding(&self) -> u32 {
+        self.fined.saturating_sub(self.paid)
+    }
+
+    /// Gold paid over the fines
+    pub fn credit(&self) -> u32 {
+        self.paid.saturating_sub(self.fined)
+    }
+
+    /// Name of the member in their last fine
+    pub fn name(&self) -> Option<&str> {
+        self.fines.last().map(|(f, _)| f.name.as_str())
+    }
+}
+
+/// Gold as written in game, `10.000 oro`
+pub fn format_gold(amount: u32) -> String {
+    let digits = amount.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push('.');
+        }
+        grouped.push(digit);
+    }
+    format!("{grouped} oro")
+}
+
+/// Parses `3000`, `3.000` or `3k`
+pub fn parse_gold(value: &str) -> Result<u32> {
+    let value = value.trim().to_lowercase().replace(' ', "");
+    let parsed = match value.strip_suffix('k') {
+        Some(thousands) => thousands.replace(',', ".").parse::<f64>().ok()
+            .filter(|k| k.is_finite() && *k > 0.0 && *k < u32::MAX as f64 / 1000.0)
+            .map(|k| (k * 1000.0).round() as u32),
+        None => value.replace('.', "").parse::<u32>().ok()
+    };
+    parsed.filter(|a| *a > 0).ok_or(Error::InvalidAmount(value))
+}
+
+/// Next monday at the summary hour in Spain
+pub fn next_fine_summary(now: DateTime<Utc>) -> DateTime<Utc> {
+    let day = Duration::try_days(1).unwrap_or_default();
+    let offset = Duration::try_hours(SUMMARY_HOUR - SPAIN_OFFSET_HOURS).unwrap_or_default();
+    let today = now.duration_trunc(day).unwrap_or(now);
+    let monday = today - Duration::try_days(today.weekday().num_days_from_monday() as i64).unwrap_or_default() + offset;
+    if monday > now { monday } else { monday + Duration::try_weeks(1).unwrap_or_default() }
+}
@@ -0,0 +1,86 @@
+use rand::prelude::SliceRandom;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use serenity::all::{CreateEmbed, UserId};
+use super::{Event, EventRole, Player};
+
+/// Biggest group the game allows
+pub const MAX_GROUP_SIZE: usize = 12;
+
+/// How the starters are split in groups, `size: None` keeps a single list
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct GroupSettings {
+    pub size: Option<usize>,
+    /// Order used to spread the players, `0` keeps the signup order
+    pub seed: u64,
+    /// Players pinned by the leader to a group, by group index
+    pub locks: Vec<(UserId, usize)>
+}
+
+/// Starters of a group with the role they signed as
+pub type Group = Vec<(EventRole, Player)>;
+
+impl GroupSettings {
+    pub fn locked_group(&self, user: UserId) -> Option<usize> {
+        self.locks.iter().find(|(u, _)| *u == user).map(|(_, group)| *group)
+    }
+}
+
+impl Event {
+    /// Splits the starters in groups spreading every role evenly, locked players stay in their group
+    pub fn groups(&self) -> Vec<Group> {
+        let Some(size) = self.group_settings.size.filter(|s| *s > 0) else { return vec![] };
+        let starters: Vec<(EventRole, Player)> = self.kind.roles().into_iter()
+            .filter(|role| !role.is_backup_role())
+            .flat_map(|role| self.roles.iter()
+                .filter(move |pr| pr.role == role)
+                .flat_map(|pr| pr.players.iter().map(|p| (pr.role, p.clone()))))
+            .collect();
+
+        let count = starters.len().div_ceil(size).max(1);
+        let mut groups: Vec<Group> = vec![vec![]; count];
+        let mut free = vec![];
+        for (role, player) in starters {
+            match self.group_settings.locked_group(player.id).filter(|g| *g < count) {
+                Some(group) => groups[group].push((role, player)),
+                None => free.push((role, player))
+            }
+        }
+
+        if self.group_settings.seed != 0 {
+            free.shuffle(&mut StdRng::seed_from_u64(self.group_settings.seed));
+        }
+
+        for role in self.kind.roles() {
+            for (_, player) in free.iter().filter(|(r, _)| *r == role) {
+                let group = groups.iter()
+                    .enumerate()
+                    .min_by_key(|(_, g)| (g.len() >= size, g.iter().filter(|(r, _)| *r == role).count(), g.len()))
+                    .map(|(i, _)| i)
+                    .unwrap_or_default();
+                groups[group].push((role, player.clone()));
+            }
+        }
+
+        groups
+    }
+
+    /// Group assignment shown under the event, `None` when the event isn't split
+    pub fn groups_embed(&self) -> Option<CreateEmbed> {
+        let size = self.group_settings.size?;
+        Some(CreateEmbed::new()
+            .title(format!("👥 Grupos de {size}"))
+            .fields(self.groups().iter().enumerate().map(|(i, group)| (
+                format!("Grupo {} ({}/{size})", i + 1, group.len()),
+                group.iter()
+                    .map(|(role, player)| {
+                        let lock = if self.group_settings.locked_group(player.id).is_some() { " 🔒" } else { "" };
+                        format!("└ {} {}{lock}", role.emoji(), player.name)
+                    })
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+                true
+            ))))
+    }
+}
@@ -0,0 +1,129 @@
+use std::path::PathBuf;
+use rand::prelude::IteratorRandom;
+use serde::{Deserialize, Serialize};
+use serenity::all::{CreateAttachment, GuildId};
+use super::{EventKind, Trial};
+use crate::prelude::*;
+
+/// PvP events that don't repeat the image of the previous ones
+const RECENT_PVP_IMAGES: i64 = 10;
+
+/// Image shown in the event message, either bundled with the bot or uploaded to the guild library
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EventImage {
+    /// `None` for bundled images
+    pub id: Option<i32>,
+    pub kind: EventKind,
+    pub file_name: String,
+    /// Trial short name, e.g. `so`
+    pub key: Option<String>,
+    pub aliases: Vec<String>
+}
+
+impl EventImage {
+    /// Value stored in the event, `library:<id>:<file>` or `asset:<kind>/<file>`
+    pub fn reference(&self) -> String {
+        match self.id {
+            Some(id) => format!("library:{id}:{}", self.file_name),
+            None => format!("asset:{}/{}", self.kind, self.file_name)
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match &self.key {
+            Some(key) => format!("{} ({key})", self.file_name),
+            None => self.file_name.clone()
+        }
+    }
+
+    /// Whether the key or any alias appears as whole words in `title`
+    pub fn matches(&self, title: &str) -> bool {
+        let title = words(title);
+        self.key.iter().chain(self.aliases.iter())
+            .map(|alias| words(alias))
+            .filter(|alias| !alias.is_empty())
+            .any(|alias| title.windows(alias.len()).any(|w| w == alias.as_slice()))
+    }
+}
+
+/// File name of an image reference, used as `attachment://<file>` in the embeds
+pub fn image_file_name(reference: &str) -> Option<&str> {
+    match reference.split_once(':')? {
+        ("library", rest) => rest.split_once(':').map(|(_, file)| file),
+        ("asset", path) => path.rsplit('/').next(),
+        _ => None
+    }
+}
+
+/// Images shipped in `assets/<kind>`, trials come with their keys and aliases
+pub fn bundled_images(kind: EventKind) -> Result<Vec<EventImage>> {
+    let mut path = PathBuf::from("assets");
+    path.push(kind.to_string());
+
+    let mut images: Vec<EventImage> = path.read_dir()?
+        .filter_map(|f| f.ok())
+        .map(|f| f.file_name().to_string_lossy().to_string())
+        .map(|file_name| {
+            let trial = Trial::with_image(&file_name);
+            EventImage {
+                id: None,
+                kind,
+                key: trial.map(|t| t.key.to_string()),
+                aliases: trial.map(|t| t.aliases.iter().map(|a| a.to_string()).collect()).unwrap_or_default(),
+                file_name
+            }
+        })
+        .collect();
+    images.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+    Ok(images)
+}
+
+/// Trials take the image matching the title or the generic one, PvP takes a random one not in `recent`
+pub fn choose_image<'a>(images: &'a [EventImage], title: &str, kind: EventKind, recent: &[String]) -> Option<&'a EventImage> {
+    match kind {
+        EventKind::Trial => images.iter().find(|i| i.matches(title))
+            .or_else(|| images.iter().find(|i| i.file_name.starts_with("generic")))
+            .or_else(|| images.first()),
+        EventKind::PvP => images.iter()
+            .filter(|i| !recent.contains(&i.reference()))
+            .choose(&mut rand::thread_rng())
+            .or_else(|| images.iter().choose(&mut rand::thread_rng()))
+    }
+}
+
+/// Guild library images followed by the bundled ones
+pub async fn available_images(store: &Store, guild: GuildId, kind: EventKind) -> Result<Vec<EventImage>> {
+    let mut images = store.get_images(guild, kind).await?;
+    images.extend(bundled_images(kind)?);
+    Ok(images)
+}
+
+/// Image reference picked automatically for an event without an explicit one
+pub async fn auto_image(store: &Store, guild: GuildId, kind: EventKind, title: &str) -> Result<Option<String>> {
+    let images = available_images(store, guild, kind).await?;
+    let recent = store.recent_images(kind, RECENT_PVP_IMAGES).await?;
+    Ok(choose_image(&images, title, kind, &recent).map(|i| i.reference()))
+}
+
+/// Loads the image file so it can be attached to the event message and scheduled event
+pub async fn image_attachment(store: &Store, reference: &str) -> Result<CreateAttachment> {
+    match reference.split_once(':') {
+        Some(("library", rest)) => {
+            let id = rest.split_once(':').map(|(id, _)| id).unwrap_or(rest).parse::<i32>()?;
+            let (file_name, data) = store.get_image_data(id).await?;
+            Ok(CreateAttachment::bytes(data, file_name))
+        }
+        Some(("asset", path)) => Ok(CreateAttachment::path(PathBuf::from("assets").join(path)).await?),
+        _ => Err(Error::UnknownImage(reference.to_string()))
+    }
+}
+
+/// Lowercase words without accents, so `Hel Ra` and `hel-ra` match `hel ra` but `SO` doesn't match `Sombras`
+fn words(text: &str) -> Vec<String> {
+    unidecode::unidecode(text)
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_string())
+        .collect()
+}
@@ -1,17 +1,50 @@
 use std::fmt::{Display, Formatter};
-use std::path::PathBuf;
 use chrono::{DateTime, Utc};
 use duration_string::DurationString;
-use rand::prelude::IteratorRandom;
-use serenity::all::{Colour, CreateAttachment, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, Mention, RoleId, ScheduledEventId, Timestamp, UserId};
+use serenity::all::{ChannelId, Colour, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, GuildId, Mention, RoleId, ScheduledEventId, ScheduledEventStatus, Timestamp, UserId};
 
 pub(crate) mod event_role;
 pub(crate) mod player;
+pub(crate) mod draft;
+pub(crate) mod template;
+pub(crate) mod swap;
+pub(crate) mod rules;
+pub(crate) mod quota;
+pub(crate) mod groups;
+pub(crate) mod image;
+pub(crate) mod change;
+pub(crate) mod overlap;
+pub(crate) mod vacation;
+pub(crate) mod notification;
+pub(crate) mod trial;
+pub(crate) mod attendance;
+pub(crate) mod fine;
+pub(crate) mod bank;
+pub(crate) mod pvp;
 
-pub(crate) use event_role::*;
-pub(crate) use player::*;
+pub use event_role::*;
+pub use player::*;
+pub use draft::*;
+pub use template::*;
+pub use swap::*;
+pub use rules::*;
+pub use quota::*;
+pub use groups::*;
+pub use image::*;
+pub use change::*;
+pub use overlap::*;
+pub use vacation::*;
+pub use notification::*;
+pub use trial::*;
+pub use attendance::*;
+pub use fine::*;
+pub use bank::*;
+pub use pvp::*;
 use serde::{Deserialize, Serialize};
 
+/// Minutes before the start when the check-in closes
+const CHECKIN_CLOSE_MINUTES: i64 = 60;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Event {
     pub title: String,
@@ -23,7 +56,36 @@ pub struct Event {
     pub leader: UserId,
     pub roles: Vec<PlayersInRole>,
     pub scheduled_event: Option<ScheduledEventId>,
-    pub notification_role: Option<RoleId>
+    pub notification_role: Option<RoleId>,
+    #[serde(default)]
+    pub rules: Vec<EligibilityRule>,
+    #[serde(default)]
+    pub class_quotas: Vec<ClassQuota>,
+    #[serde(default)]
+    pub group_settings: GroupSettings,
+    /// Image reference, see [`EventImage::reference`], picked when the event is published if empty
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub status: EventStatus,
+    /// Members interested in the scheduled event in Discord
+    #[serde(default)]
+    pub interested: Vec<Player>,
+    /// Thread on the event message where the changes are posted
+    #[serde(default)]
+    pub thread: Option<ChannelId>,
+    /// Channel of the event message
+    #[serde(default)]
+    pub channel: Option<ChannelId>,
+    /// Hours before the start when the starters have to confirm their attendance
+    #[serde(default)]
+    pub checkin: Option<u32>,
+    /// Players that confirmed their attendance
+    #[serde(default)]
+    pub confirmed: Vec<UserId>,
+    /// Campaign, alliance and style of PvP events
+    #[serde(default)]
+    pub pvp: Option<PvpDetails>
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -45,6 +107,13 @@ pub enum EventScopes {
     Public, Private, #[sqlx(rename = "semi-public")] SemiPublic
 }
 
+/// Status of the scheduled event in Discord
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "events.status", rename_all = "lowercase")]
+pub enum EventStatus {
+    #[default] Scheduled, Active, Completed, Canceled
+}
+
 impl Event {
     pub fn new(title: String, duration: DurationString, description: String, leader: UserId, kind: EventKind) -> Self {
         Event {
@@ -53,6 +122,17 @@ impl Event {
             datetime: None,
             scheduled_event: None,
             notification_role: None,
+            rules: vec![],
+            class_quotas: vec![],
+            group_settings: GroupSettings::default(),
+            image: None,
+            status: EventStatus::Scheduled,
+            interested: vec![],
+            thread: None,
+            channel: None,
+            checkin: None,
+            confirmed: vec![],
+            pvp: (kind == EventKind::PvP).then(PvpDetails::default),
             roles: kind.roles()
                 .into_iter()
                 .map(|role| PlayersInRole {role, players: vec![], max: kind.default_role_max(role) })
@@ -76,14 +156,18 @@ impl Event {
         }
     }
 
-    pub fn add_player(&mut self, role: EventRole, player: Player) -> EventRole {
-        let mut add_to_reserve = false;
+    pub fn add_player(&mut self, role: EventRole, mut player: Player) -> EventRole {
+        // The absence reason only makes sense while absent
+        if role != EventRole::Absent {
+            player.absence = None;
+        }
+        let mut add_to_reserve = !role.is_backup_role() && self.class_quota_full(&player).is_some();
         for pr in self.roles.iter_mut() {
             if let Some(position) = pr.players.iter().position(|p| p.id == player.id) {
                 pr.players.remove(position);
             }
 
-            if pr.role == role {
+            if pr.role == role && !add_to_reserve {
                 if pr.max.is_some_and(|max| max <= pr.players.len()) {
                     add_to_reserve = true;
                 } else {
@@ -103,8 +187,126 @@ impl Event {
         }
     }
 
+    /// Quota whose max would be broken if `player` joined the starters
+    pub fn class_quota_full(&self, player: &Player) -> Option<&ClassQuota> {
+        let class = player.class?;
+        self.class_quotas.iter()
+            .find(|q| q.class == class && q.max.is_some_and(|max| self.starters_of_class(class, Some(player.id)) >= max))
+    }
+
+    /// Classes below their quota min and how many starters each one lacks
+    pub fn missing_classes(&self) -> Vec<(PlayerClass, usize)> {
+        self.class_quotas.iter()
+            .filter_map(|q| q.min
+                .map(|min| min.saturating_sub(self.starters_of_class(q.class, None)))
+                .filter(|missing| *missing > 0)
+                .map(|missing| (q.class, missing)))
+            .collect()
+    }
+
+    fn starters_of_class(&self, class: PlayerClass, except: Option<UserId>) -> usize {
+        self.roles.iter()
+            .filter(|pr| !pr.role.is_backup_role())
+            .flat_map(|pr| pr.players.iter())
+            .filter(|p| p.class == Some(class) && Some(p.id) != except)
+            .count()
+    }
+
+    /// Own rules of the event, or the notification role / initiation defaults without them
+    pub fn effective_rules(&self) -> Vec<EligibilityRule> {
+        if self.rules.is_empty() {
+            default_rules(self.kind, self.notification_role)
+        } else {
+            self.rules.clone()
+        }
+    }
+
+    pub fn role_of(&self, user: UserId) -> Option<EventRole> {
+        self.roles.iter()
+            .find(|pr| pr.players.iter().any(|p| p.id == user))
+            .map(|pr| pr.role)
+    }
+
+    /// Signed player with their role
+    pub fn player(&self, user: UserId) -> Option<(EventRole, &Player)> {
+        self.roles.iter()
+            .find_map(|pr| pr.players.iter().find(|p| p.id == user).map(|p| (pr.role, p)))
+    }
+
+    /// Capped starter roles below their max, with the number of missing players
+    pub fn open_slots(&self) -> Vec<(EventRole, usize)> {
+        self.roles.iter()
+            .filter(|pr| !pr.role.is_backup_role())
+            .filter_map(|pr| pr.max.map(|max| (pr.role, max.saturating_sub(pr.players.len()))))
+            .filter(|(_, missing)| *missing > 0)
+            .collect()
+    }
+
+    /// Reserves that are flexible to `role`
+    pub fn flex_reserves(&self, role: EventRole) -> Vec<&Player> {
+        self.roles.iter()
+            .filter(|pr| pr.role == EventRole::Reserve)
+            .flat_map(|pr| pr.players.iter())
+            .filter(|p| p.flex.contains(&role))
+            .collect()
+    }
+
+    /// Opening of the check-in window, `None` if the event has no check-in or no date
+    pub fn checkin_opens(&self) -> Option<DateTime<Utc>> {
+        let hours = self.checkin?;
+        Some(self.datetime? - chrono::Duration::try_hours(hours as i64)?)
+    }
+
+    /// Deadline of the check-in, the starters that haven't confirmed by then are moved to reserve
+    pub fn checkin_closes(&self) -> Option<DateTime<Utc>> {
+        self.checkin?;
+        Some(self.datetime? - chrono::Duration::try_minutes(CHECKIN_CLOSE_MINUTES)?)
+    }
+
+    /// Whether the starters are being asked to confirm their attendance
+    pub fn checkin_open(&self) -> bool {
+        self.checkin_opens().is_some_and(|opens| opens <= Utc::now())
+    }
+
+    /// Starters, leader excluded, that haven't confirmed their attendance yet
+    pub fn unconfirmed(&self) -> Vec<(EventRole, &Player)> {
+        self.roles.iter()
+            .filter(|pr| !pr.role.is_backup_role())
+            .flat_map(|pr| pr.players.iter().map(|p| (pr.role, p)))
+            .filter(|(_, p)| p.id != self.leader && !self.confirmed.contains(&p.id))
+            .collect()
+    }
+
+    /// Takes the player out of the event, returning the role they had
+    pub fn remove_player(&mut self, user: UserId) -> Option<(EventRole, Player)> {
+        self.roles.iter_mut()
+            .find_map(|pr| pr.players.iter().position(|p| p.id == user).map(|position| (pr.role, pr.players.remove(position))))
+    }
+
+    /// Trades the roles of two signed players keeping their place in the list,
+    /// returns both players with their new role or `None` if they can't be swapped
+    pub fn swap_players(&mut self, a: UserId, b: UserId) -> Option<[(EventRole, Player); 2]> {
+        let position = |user: UserId| self.roles.iter().enumerate()
+            .find_map(|(r, pr)| pr.players.iter().position(|p| p.id == user).map(|p| (r, p)));
+        let (a_role, a_pos) = position(a)?;
+        let (b_role, b_pos) = position(b)?;
+        if a_role == b_role || self.roles[a_role].role == EventRole::Absent || self.roles[b_role].role == EventRole::Absent {
+            return None;
+        }
+
+        let a_player = swapped_player(&self.roles[a_role].players[a_pos], self.roles[a_role].role, self.roles[b_role].role);
+        let b_player = swapped_player(&self.roles[b_role].players[b_pos], self.roles[b_role].role, self.roles[a_role].role);
+        self.roles[b_role].players[b_pos] = a_player.clone();
+        self.roles[a_role].players[a_pos] = b_player.clone();
+
+        Some([(self.roles[b_role].role, a_player), (self.roles[a_role].role, b_player)])
+    }
+
     pub fn embed(&self) -> CreateEmbed {
-        CreateEmbed::new()
+        // The leader doesn't have to confirm
+        let confirmed = self.checkin_open()
+            .then(|| self.confirmed.iter().copied().chain([self.leader]).collect::<Vec<UserId>>());
+        let embed = CreateEmbed::new()
             .title(&self.title)
             .description(&self.description)
             .field(":date: Fecha y Hora:", if let Some(datetime) = &self.datetime {
@@ -112,6 +314,7 @@ impl Event {
             } else {"".to_string()}, true)
             .field(":hourglass_flowing_sand: Duración", self.duration.to_string(), true)
             .field(":crown: Lider", Mention::User(self.leader).to_string(), true)
+            .fields(self.pvp.as_ref().map(|pvp| (":crossed_swords: PvP", pvp.summary_es(), false)))
             .fields(self.kind.roles().iter()
                 .map(|role| {
                     let pr = self.roles.iter().find(|pr| pr.role == *role).unwrap();
@@ -131,31 +334,81 @@ impl Event {
                         "".to_string()
                     };
 
+                    let confirmed = confirmed.as_deref().filter(|_| !role.is_backup_role());
                     (format!("{} {} ({max_label}) {reserves_label}", pr.role.emoji().to_string(), pr.role),
-                     format_players_embed(&pr.players), false)
+                     format_players_embed(&pr.players, confirmed), false)
                 })
             )
+            .fields(self.missing_classes_field())
+            .fields(self.interested_field())
+            .fields(self.status.label_es().map(|status| (":information_source: Estado", status, false)))
             .field("", "\u{200b}", false)
-            .thumbnail(match self.kind {
-                EventKind::Trial => "https://images.uesp.net/2/26/ON-mapicon-SoloTrial.png",
-                EventKind::PvP => "https://images.uesp.net/9/9e/ON-icon-alliance-Ebonheart.png"
-            })
+            .thumbnail(self.thumbnail())
             .timestamp(Timestamp::now())
             .footer(CreateEmbedFooter::new("Ultima modificacion"))
-            .color(Colour::from_rgb(0, 255, 0))
-            .image(format!("https://github.com/mangelarilla/leki/blob/main/assets/{}/{}?raw=true",
-                           self.kind,
-                           random_image(&self.title, self.kind).unwrap().file_name().unwrap().to_string_lossy()))
+            .color(self.status.colour());
+
+        // The image is attached to the event message when published, events from before the
+        // image library keep linking a bundled image like they always did
+        match self.image.as_deref().and_then(image_file_name) {
+            Some(file) => embed.image(format!("attachment://{file}")),
+            None => match self.legacy_image() {
+                Some(url) => embed.image(url),
+                None => embed
+            }
+        }
     }
-    pub fn embed_preview(&self) -> CreateEmbed {
-        self.embed()
-            .author(CreateEmbedAuthor::new("Previsualizacion"))
+
+    /// Bundled image of the repository, the one events without `image` showed before the library
+    fn legacy_image(&self) -> Option<String> {
+        let images = bundled_images(self.kind).ok()?;
+        let image = choose_image(&images, &self.title, self.kind, &[])?;
+        Some(format!("https://github.com/mangelarilla/leki/blob/main/assets/{}/{}?raw=true", self.kind, image.file_name))
+    }
+
+    /// Trial icon, or the icon of the alliance for PvP
+    pub fn thumbnail(&self) -> &'static str {
+        match self.kind {
+            EventKind::Trial => "https://images.uesp.net/2/26/ON-mapicon-SoloTrial.png",
+            EventKind::PvP => self.pvp.as_ref().map(|p| p.alliance).unwrap_or_default().thumbnail()
+        }
     }
 
-    pub(crate) async fn image(&self) -> crate::prelude::Result<CreateAttachment> {
-        let attachment = CreateAttachment::path(random_image(&self.title, self.kind)?).await?;
+    pub fn missing_classes_field(&self) -> Option<(String, String, bool)> {
+        let missing = self.missing_classes();
+        (!missing.is_empty()).then(|| (
+            ":warning: Falta:".to_string(),
+            missing.iter().map(|(class, count)| format!("{count} {class}")).collect::<Vec<String>>().join(", "),
+            false
+        ))
+    }
 
-        Ok(attachment)
+    /// Interested members that haven't signed up yet
+    pub fn pending_interested(&self) -> Vec<&Player> {
+        self.interested.iter()
+            .filter(|i| self.role_of(i.id).is_none())
+            .collect()
+    }
+
+    fn interested_field(&self) -> Option<(String, String, bool)> {
+        let interested = self.pending_interested();
+        (!interested.is_empty()).then(|| (
+            format!(":star: Interesados ({})", interested.len()),
+            interested.iter().map(|p| format!("└ {}", p.name)).collect::<Vec<String>>().join("\n"),
+            false
+        ))
+    }
+
+    /// Embeds of the event message, the groups go in a second one
+    pub fn embeds(&self) -> Vec<CreateEmbed> {
+        let mut embeds = vec![self.embed()];
+        embeds.extend(self.groups_embed());
+        embeds
+    }
+
+    pub fn embed_preview(&self) -> CreateEmbed {
+        self.embed()
+            .author(CreateEmbedAuthor::new("Previsualizacion"))
     }
 }
 
@@ -181,6 +434,13 @@ impl EventKind {
         }
     }
 
+    /// Voice channel of the scheduled events of this kind
+    pub fn voice_channel(&self, guild: GuildId) -> ChannelId {
+        ChannelId::new(if guild == 1134046249293717514 {1157232748604444683} else {
+            if *self == EventKind::PvP {1144350647848812564} else {1144350408769286274}
+        })
+    }
+
     pub fn from_partial_id(id: &str) -> Option<Self> {
         if id.contains("trial") {
             Some(EventKind::Trial)
@@ -192,6 +452,37 @@ impl EventKind {
     }
 }
 
+impl EventStatus {
+    /// Label shown in the event message, `None` while the event is still scheduled
+    pub fn label_es(&self) -> Option<String> {
+        match self {
+            EventStatus::Scheduled => None,
+            EventStatus::Active => Some("🟢 En curso".to_string()),
+            EventStatus::Completed => Some("🏁 Finalizado".to_string()),
+            EventStatus::Canceled => Some("❌ Cancelado".to_string()),
+        }
+    }
+
+    pub fn colour(&self) -> Colour {
+        match self {
+            EventStatus::Scheduled | EventStatus::Active => Colour::from_rgb(0, 255, 0),
+            EventStatus::Completed => Colour::from_rgb(128, 128, 128),
+            EventStatus::Canceled => Colour::from_rgb(255, 0, 0),
+        }
+    }
+}
+
+impl From<ScheduledEventStatus> for EventStatus {
+    fn from(status: ScheduledEventStatus) -> Self {
+        match status {
+            ScheduledEventStatus::Active => EventStatus::Active,
+            ScheduledEventStatus::Completed => EventStatus::Completed,
+            ScheduledEventStatus::Canceled => EventStatus::Canceled,
+            _ => EventStatus::Scheduled
+        }
+    }
+}
+
 impl EventScopes {
     pub fn from_partial_id(id: &str) -> Self {
         if id.contains("semi_public") {
@@ -223,13 +514,30 @@ impl Display for EventScopes {
     }
 }
 
-fn format_players_embed(players: &Vec<Player>) -> String {
+/// Reserves keep the role they left as flex, starters drop the flex of the role they take
+fn swapped_player(player: &Player, from: EventRole, to: EventRole) -> Player {
+    let mut player = player.clone();
+    player.flex.retain(|r| *r != to);
+    if to == EventRole::Reserve && !player.flex.contains(&from) {
+        player.flex.push(from);
+    }
+    player
+}
+
+/// Players of a role, with their attendance mark when `confirmed` is given during the check-in
+fn format_players_embed(players: &[Player], confirmed: Option<&[UserId]>) -> String {
     players.iter()
         .map(|player| {
+            let absence = player.absence.as_ref().map(|reason| format!(" — _{reason}_")).unwrap_or_default();
+            let checkin = match confirmed {
+                Some(confirmed) if confirmed.contains(&player.id) => " ✅",
+                Some(_) => " ⏳",
+                None => ""
+            };
             if let Some(class) = &player.class {
-                format!("└ {} {} {}", class.emoji(), player.name, format_flex(&player.flex))
+                format!("└ {} {} {}{checkin}{absence}", class.emoji(), player.name, format_flex(&player.flex))
             } else {
-                format!("└ {} {}", player.name, format_flex(&player.flex))
+                format!("└ {} {}{checkin}{absence}", player.name, format_flex(&player.flex))
             }
         })
         .collect::<Vec<String>>()
@@ -244,51 +552,3 @@ fn format_flex(roles: &Vec<EventRole>) -> String {
         format!("({})", role_strings.join("|"))
     }
 }
-
-fn random_image(title: &str, kind: EventKind) -> crate::prelude::Result<PathBuf> {
-    let mut path = PathBuf::from("assets");
-    path.push(kind.to_string());
-
-    let image = if kind == EventKind::PvP {
-        path.read_dir()?
-            .filter_map(|f| f.ok())
-            .choose(&mut rand::thread_rng())
-            .unwrap()
-    } else {
-        path.read_dir()?
-            .filter_map(|f| f.ok())
-            .find(|t| t.file_name().to_string_lossy() == guess_image(&title))
-            .unwrap()
-    };
-
-    Ok(image.path())
-}
-
-fn guess_image(title: &str) -> String {
-    let title = unidecode::unidecode(&title);
-    if title.contains("AA") || title.contains("Aetherian") || title.contains("Aeterico") {
-        "aa.jpg".to_string()
-    } else if title.contains("AS") || title.contains("Asylum") || title.contains("Amparo") {
-        "as.jpg".to_string()
-    } else if title.contains("HRC") || title.contains("Hel Ra") || title.contains("Hel-Ra") {
-        "hrc.jpg".to_string()
-    } else if title.contains("SO") || title.contains("Ophidia") || title.contains("Sanctum") {
-        "so.png".to_string()
-    } else if title.contains("DSR") || title.contains("Dreadsail") || title.contains("Arrecife") {
-        "dsr.jpg".to_string()
-    } else if title.contains("SS") || title.contains("Sunspire") || title.contains("Sol") {
-        "ss.jpg".to_string()
-    } else if title.contains("MoL") || title.contains("Maw") || title.contains("Lorkhaj") {
-        "mol.jpg".to_string()
-    } else if title.contains("CR") || title.contains("Cloudrest") || title.contains("Nubelia") {
-        "cr.jpg".to_string()
-    } else if title.contains("SE") || title.contains("Sanity") || title.contains("Locura") {
-        "se.jpg".to_string()
-    } else if title.contains("HoF") || title.contains("Fabrication") || title.contains("Fabricacion") {
-        "hof.jpg".to_string()
-    } else if title.contains("KA") || title.contains("Kyne") || title.contains("Egida") {
-        "ka.png".to_string()
-    } else {
-        "generic.jpg".to_string()
-    }
-}
\ No newline at end of file
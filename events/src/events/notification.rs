@@ -0,0 +1,145 @@
+use chrono::{DateTime, Duration, DurationRound, Timelike, Utc};
+use serenity::all::{ChannelId, UserId};
+use strum::EnumIter;
+use super::vacation::SPAIN_OFFSET_HOURS;
+
+/// Spanish hour of the daily digest
+const DAILY_DIGEST_HOUR: i64 = 20;
+/// Discord messages can't be longer
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, sqlx::Type)]
+#[sqlx(type_name = "events.notification_kind", rename_all = "lowercase")]
+pub enum NotificationKind {
+    Signup, Absence, Reserve, Promotion
+}
+
+/// Where a kind of notification goes
+#[derive(Debug, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "events.notification_channel", rename_all = "lowercase")]
+pub enum NotificationChannel {
+    Dm, Thread, None
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, sqlx::Type)]
+#[sqlx(type_name = "events.notification_frequency", rename_all = "lowercase")]
+pub enum NotificationFrequency {
+    #[default] Instant, Hourly, Daily
+}
+
+/// Notification settings of a member, every kind goes by DM unless changed
+#[derive(Debug, Clone, Default)]
+pub struct NotificationPreferences {
+    pub frequency: NotificationFrequency,
+    pub channels: Vec<(NotificationKind, NotificationChannel)>
+}
+
+/// Queued notification, `thread` is `None` for DMs
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: i32,
+    pub user: UserId,
+    pub kind: NotificationKind,
+    pub thread: Option<ChannelId>,
+    pub content: String
+}
+
+impl NotificationKind {
+    pub fn to_id(&self) -> &'static str {
+        match self {
+            NotificationKind::Signup => "signup",
+            NotificationKind::Absence => "absence",
+            NotificationKind::Reserve => "reserve",
+            NotificationKind::Promotion => "promotion",
+        }
+    }
+
+    pub fn label_es(&self) -> &'static str {
+        match self {
+            NotificationKind::Signup => "Inscripciones",
+            NotificationKind::Absence => "Ausencias",
+            NotificationKind::Reserve => "Movidos a reserva",
+            NotificationKind::Promotion => "Subidas a titular",
+        }
+    }
+}
+
+impl NotificationChannel {
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "dm" => Some(NotificationChannel::Dm),
+            "thread" => Some(NotificationChannel::Thread),
+            "none" => Some(NotificationChannel::None),
+            _ => None
+        }
+    }
+
+    pub fn label_es(&self) -> &'static str {
+        match self {
+            NotificationChannel::Dm => "Mensaje privado",
+            NotificationChannel::Thread => "Hilo del evento",
+            NotificationChannel::None => "Desactivado",
+        }
+    }
+}
+
+impl NotificationFrequency {
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "instant" => Some(NotificationFrequency::Instant),
+            "hourly" => Some(NotificationFrequency::Hourly),
+            "daily" => Some(NotificationFrequency::Daily),
+            _ => None
+        }
+    }
+
+    pub fn label_es(&self) -> String {
+        match self {
+            NotificationFrequency::Instant => "Al momento".to_string(),
+            NotificationFrequency::Hourly => "Resumen cada hora".to_string(),
+            NotificationFrequency::Daily => format!("Resumen diario a las {DAILY_DIGEST_HOUR}:00"),
+        }
+    }
+
+    /// When a notification created at `now` has to be sent
+    pub fn due_at(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let hour = Duration::try_hours(1).unwrap_or_default();
+        match self {
+            NotificationFrequency::Instant => now,
+            NotificationFrequency::Hourly => now.duration_trunc(hour).map(|h| h + hour).unwrap_or(now),
+            NotificationFrequency::Daily => {
+                let digest_hour = ((DAILY_DIGEST_HOUR - SPAIN_OFFSET_HOURS) as u32) % 24;
+                let today = now.with_hour(digest_hour).and_then(|d| d.duration_trunc(hour).ok()).unwrap_or(now);
+                if today > now { today } else { today + Duration::try_days(1).unwrap_or_default() }
+            }
+        }
+    }
+}
+
+impl NotificationPreferences {
+    pub fn channel(&self, kind: NotificationKind) -> NotificationChannel {
+        self.channels.iter()
+            .find(|(k, _)| *k == kind)
+            .map(|(_, c)| *c)
+            .unwrap_or(NotificationChannel::Dm)
+    }
+}
+
+/// Single message with every pending notification of a member, the oldest are dropped to fit in a message
+pub fn digest(notifications: &[Notification]) -> String {
+    if let [single] = notifications {
+        return single.content.clone();
+    }
+
+    let header = format!("📬 **Resumen de avisos** ({})", notifications.len());
+    let mut lines: Vec<String> = notifications.iter().map(|n| format!("- {}", n.content)).collect();
+    let mut dropped = 0;
+    while lines.len() > 1 && header.len() + lines.iter().map(|l| l.len() + 1).sum::<usize>() + 40 > MAX_MESSAGE_LENGTH {
+        lines.remove(0);
+        dropped += 1;
+    }
+    if dropped > 0 {
+        lines.insert(0, format!("- ... y {dropped} avisos anteriores"));
+    }
+    format!("{header}\n{}", lines.join("\n"))
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serenity::all::GuildId;
+use super::Event;
+
+/// Starters two events can share before the leader is warned
+pub const MAX_SHARED_PLAYERS: usize = 2;
+
+/// Why two events at the same time are a problem
+#[derive(Debug, Clone, PartialEq)]
+pub enum Conflict {
+    SameLeader,
+    SameVoiceChannel,
+    SharedPlayers(Vec<String>)
+}
+
+impl Event {
+    pub fn end(&self) -> Option<DateTime<Utc>> {
+        let duration: std::time::Duration = self.duration.into();
+        Some(self.datetime? + duration)
+    }
+
+    pub fn overlaps(&self, other: &Event) -> bool {
+        match (self.datetime, self.end(), other.datetime, other.end()) {
+            (Some(start), Some(end), Some(other_start), Some(other_end)) => start < other_end && other_start < end,
+            _ => false
+        }
+    }
+
+    pub fn is_starter(&self, user: serenity::all::UserId) -> bool {
+        self.role_of(user).is_some_and(|role| !role.is_backup_role())
+    }
+
+    /// Conflicts with an event at the same time, empty when they can coexist
+    pub fn conflicts_with(&self, other: &Event, guild: GuildId) -> Vec<Conflict> {
+        if !self.overlaps(other) {
+            return vec![];
+        }
+
+        let mut conflicts = vec![];
+        if self.leader == other.leader {
+            conflicts.push(Conflict::SameLeader);
+        }
+        if self.kind.voice_channel(guild) == other.kind.voice_channel(guild) {
+            conflicts.push(Conflict::SameVoiceChannel);
+        }
+        let shared: Vec<String> = self.roles.iter()
+            .filter(|pr| !pr.role.is_backup_role())
+            .flat_map(|pr| pr.players.iter())
+            .filter(|p| other.is_starter(p.id))
+            .map(|p| p.name.clone())
+            .collect();
+        if shared.len() > MAX_SHARED_PLAYERS {
+            conflicts.push(Conflict::SharedPlayers(shared));
+        }
+        conflicts
+    }
+}
+
+impl Conflict {
+    pub fn label_es(&self) -> String {
+        match self {
+            Conflict::SameLeader => "mismo lider".to_string(),
+            Conflict::SameVoiceChannel => "mismo canal de voz".to_string(),
+            Conflict::SharedPlayers(players) => format!("{} titulares en comun: {}", players.len(), players.join(", ")),
+        }
+    }
+}
+
+/// Warning for the leader about an event at the same time as `event`
+pub fn conflict_warning(other: &Event, conflicts: &[Conflict]) -> String {
+    format!("⚠️ Se solapa con **{}** ({}): {}",
+            other.title,
+            other.datetime.map(|dt| format!("<t:{}:F>", dt.timestamp())).unwrap_or_default(),
+            conflicts.iter().map(|c| c.label_es()).collect::<Vec<String>>().join(", "))
+}
@@ -10,7 +10,10 @@ pub struct Player {
     pub id: UserId,
     pub name: String,
     pub class: Option<PlayerClass>,
-    pub flex: Vec<EventRole>
+    pub flex: Vec<EventRole>,
+    /// Reason given when signed as absent
+    #[serde(default)]
+    pub absence: Option<String>
 }
 
 #[derive(Clone, Copy, Debug, PartialOrd, PartialEq, Serialize, Deserialize, sqlx::Type)]
@@ -21,7 +24,21 @@ pub enum PlayerClass {
 
 impl Player {
     pub fn new(id: UserId, name: impl Into<String>) -> Self {
-        Player {id, name: name.into(), class: None, flex: vec![]}
+        Player {id, name: name.into(), class: None, flex: vec![], absence: None}
+    }
+}
+
+/// Normalizes an ESO account to `@handle`, accounts are 3 to 20 letters, digits, `.`, `_`, `-` or `'`
+pub fn parse_eso_account(handle: &str) -> Result<String> {
+    let name = handle.trim().trim_start_matches('@');
+    let valid = (3..=20).contains(&name.chars().count()) &&
+        name.chars().next().is_some_and(|c| c.is_alphabetic()) &&
+        name.chars().all(|c| c.is_alphanumeric() || ['.', '_', '-', '\''].contains(&c));
+
+    if valid {
+        Ok(format!("@{name}"))
+    } else {
+        Err(Error::InvalidEsoAccount(handle.to_string()))
     }
 }
 
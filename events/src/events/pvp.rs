@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use strum::{EnumIter, IntoEnumIterator};
+
+/// Cyrodiil campaigns offered in the wizard, others can still be stored
+pub const CAMPAIGNS: [&str; 5] = ["Grey Host", "Ravenwatch", "Blackreach", "Icereach", "Legions of the Dead"];
+/// Champion points asked by the leaders, the wizard offers these
+pub const MIN_CP_CHOICES: [u32; 6] = [300, 600, 900, 1200, 1500, 1800];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, EnumIter, sqlx::Type)]
+#[sqlx(type_name = "events.alliance", rename_all = "lowercase")]
+pub enum PvpAlliance {
+    Aldmeri,
+    Daggerfall,
+    /// Alliance of the guild
+    #[default]
+    Ebonheart
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, EnumIter, sqlx::Type)]
+#[sqlx(type_name = "events.pvp_style", rename_all = "snake_case")]
+pub enum PvpStyle {
+    OpenField, KeepSiege, Battlegrounds, SmallScale
+}
+
+/// What a PvP event is about, shown in the event message
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PvpDetails {
+    pub campaign: Option<String>,
+    pub alliance: PvpAlliance,
+    pub style: Option<PvpStyle>,
+    pub min_cp: Option<u32>
+}
+
+/// Filters of the upcoming events listing, `None` matches everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PvpFilter {
+    pub campaign: Option<String>,
+    pub alliance: Option<PvpAlliance>,
+    pub style: Option<PvpStyle>
+}
+
+impl PvpAlliance {
+    pub fn to_id(&self) -> &'static str {
+        match self {
+            PvpAlliance::Aldmeri => "ad",
+            PvpAlliance::Daggerfall => "dc",
+            PvpAlliance::Ebonheart => "ep"
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::iter().find(|a| a.to_id() == id.to_lowercase())
+    }
+
+    pub fn label_es(&self) -> &'static str {
+        match self {
+            PvpAlliance::Aldmeri => "Dominio Aldmeri",
+            PvpAlliance::Daggerfall => "Pacto de Daggerfall",
+            PvpAlliance::Ebonheart => "Pacto de Ebonheart"
+        }
+    }
+
+    /// Alliance icon used as thumbnail of the event
+    pub fn thumbnail(&self) -> &'static str {
+        match self {
+            PvpAlliance::Aldmeri => "https://images.uesp.net/9/9d/ON-icon-alliance-Aldmeri.png",
+            PvpAlliance::Daggerfall => "https://images.uesp.net/a/a3/ON-icon-alliance-Daggerfall.png",
+            PvpAlliance::Ebonheart => "https://images.uesp.net/9/9e/ON-icon-alliance-Ebonheart.png"
+        }
+    }
+}
+
+impl PvpStyle {
+    pub fn to_id(&self) -> &'static str {
+        match self {
+            PvpStyle::OpenField => "open_field",
+            PvpStyle::KeepSiege => "keep_siege",
+            PvpStyle::Battlegrounds => "battlegrounds",
+            PvpStyle::SmallScale => "small_scale"
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::iter().find(|s| s.to_id() == id)
+    }
+
+    pub fn label_es(&self) -> &'static str {
+        match self {
+            PvpStyle::OpenField => "Campo abierto",
+            PvpStyle::KeepSiege => "Asedio de fortalezas",
+            PvpStyle::Battlegrounds => "Campos de batalla",
+            PvpStyle::SmallScale => "Grupo reducido"
+        }
+    }
+}
+
+impl PvpDetails {
+    /// Lines of the event message, the alliance is already in the thumbnail but it's named anyway
+    pub fn summary_es(&self) -> String {
+        let mut lines = vec![format!("Alianza: {} ({})", self.alliance.label_es(), self.alliance.to_id().to_uppercase())];
+        if let Some(campaign) = &self.campaign {
+            lines.push(format!("Campaña: {campaign}"));
+        }
+        if let Some(style) = self.style {
+            lines.push(format!("Estilo: {}", style.label_es()));
+        }
+        if let Some(cp) = self.min_cp {
+            lines.push(format!("CP minimo: {cp}"));
+        }
+        lines.join("\n")
+    }
+
+    /// One line version for listings, e.g. `EP · Grey Host · Asedio de fortalezas · CP 1200`
+    pub fn short_es(&self) -> String {
+        let mut parts = vec![self.alliance.to_id().to_uppercase()];
+        parts.extend(self.campaign.clone());
+        parts.extend(self.style.map(|s| s.label_es().to_string()));
+        parts.extend(self.min_cp.map(|cp| format!("CP {cp}")));
+        parts.join(" · ")
+    }
+}
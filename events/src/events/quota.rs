@@ -0,0 +1,66 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use super::PlayerClass;
+use crate::prelude::*;
+
+/// Limits on how many starters of a class the event takes, written as `Warden min 1 max 2`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClassQuota {
+    pub class: PlayerClass,
+    pub min: Option<usize>,
+    pub max: Option<usize>
+}
+
+impl ClassQuota {
+    /// Parses a list of quotas separated by `;` or new lines
+    pub fn parse_list(quotas: &str) -> Result<Vec<Self>> {
+        quotas.split([';', '\n'])
+            .filter(|q| !q.trim().is_empty())
+            .map(Self::from_str)
+            .collect()
+    }
+
+    pub fn full_explanation_es(&self) -> String {
+        format!("Ya hay {} titulares de clase {}, que es el maximo para este evento", self.max.unwrap_or_default(), self.class.label_es())
+    }
+}
+
+impl Display for ClassQuota {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.class)?;
+        if let Some(min) = self.min {
+            write!(f, " min {min}")?;
+        }
+        if let Some(max) = self.max {
+            write!(f, " max {max}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ClassQuota {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || Error::ParseQuota(s.trim().to_string());
+        let mut tokens = s.split_whitespace();
+        let class = PlayerClass::from_str(tokens.next().ok_or_else(invalid)?)?;
+        let mut quota = ClassQuota { class, min: None, max: None };
+
+        while let Some(limit) = tokens.next() {
+            let value = tokens.next().and_then(|v| v.parse::<usize>().ok()).ok_or_else(invalid)?;
+            match limit {
+                "min" => quota.min = Some(value),
+                "max" => quota.max = Some(value),
+                _ => return Err(invalid())
+            }
+        }
+
+        match (quota.min, quota.max) {
+            (None, None) => Err(invalid()),
+            (Some(min), Some(max)) if min > max => Err(invalid()),
+            _ => Ok(quota)
+        }
+    }
+}
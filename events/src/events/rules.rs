@@ -0,0 +1,180 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use serenity::all::{ChannelId, Mention, RoleId};
+use super::{EventKind, PlayerClass};
+use crate::prelude::*;
+
+/// Roster roles that can't sign up as starters to initiation events
+const ADVANCED_TRIAL_ROLES: [u64; 2] = [1201977765579202590, 1201977671979122698];
+const ADVANCED_PVP_ROLES: [u64; 1] = [1201977190791512095];
+const RULES_CHANNEL: u64 = 1004447678689714197;
+
+/// Starter signup requirement, written as `condicion => resultado`, e.g. `rol <@&123> => reserva`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EligibilityRule {
+    pub condition: Condition,
+    pub outcome: RuleOutcome
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    RequiredRole(RoleId),
+    ForbiddenRole(RoleId),
+    /// Maximum starter signups in other events of the same week
+    MaxStarterSignupsPerWeek(u32),
+    RequiredClass(Vec<PlayerClass>),
+    /// Minimum percentage of past signups that weren't absences
+    MinAttendance(u8),
+    /// Not a starter in another event at the same time
+    NoOverlap
+}
+
+/// What happens to a starter signup breaking a rule, ordered from softest to strictest
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum RuleOutcome {
+    Reserve, Approval, Reject
+}
+
+/// Everything the rules are checked against for a single signup
+#[derive(Debug, Default)]
+pub struct SignupProfile {
+    pub roles: Vec<RoleId>,
+    pub class: Option<PlayerClass>,
+    pub week_starter_signups: u32,
+    /// `None` for players without past events
+    pub attendance: Option<u8>,
+    /// Titles of the events at the same time where the player is a starter
+    pub overlapping: Vec<String>
+}
+
+/// Strictest outcome among the broken rules with the explanation of each of them
+#[derive(Debug, PartialEq)]
+pub struct Verdict {
+    pub outcome: RuleOutcome,
+    pub reasons: Vec<String>
+}
+
+impl EligibilityRule {
+    pub fn new(condition: Condition, outcome: RuleOutcome) -> Self {
+        EligibilityRule { condition, outcome }
+    }
+
+    pub fn is_met(&self, profile: &SignupProfile) -> bool {
+        match &self.condition {
+            Condition::RequiredRole(role) => profile.roles.contains(role),
+            Condition::ForbiddenRole(role) => !profile.roles.contains(role),
+            Condition::MaxStarterSignupsPerWeek(max) => profile.week_starter_signups < *max,
+            Condition::RequiredClass(classes) => profile.class.is_some_and(|c| classes.contains(&c)),
+            Condition::MinAttendance(min) => profile.attendance.map(|a| a >= *min).unwrap_or(true),
+            Condition::NoOverlap => profile.overlapping.is_empty()
+        }
+    }
+
+    /// Parses a list of rules separated by `;` or new lines
+    pub fn parse_list(rules: &str) -> Result<Vec<Self>> {
+        rules.split([';', '\n'])
+            .filter(|r| !r.trim().is_empty())
+            .map(Self::from_str)
+            .collect()
+    }
+}
+
+impl Condition {
+    pub fn explanation_es(&self) -> String {
+        match self {
+            Condition::RequiredRole(role) => format!("Para poder apuntarte como titular deberas formar parte de {}, consulta los requisitos de rosters de la norma **1.5** en {}",
+                                                     Mention::Role(*role), Mention::Channel(ChannelId::new(RULES_CHANNEL))),
+            Condition::ForbiddenRole(role) => format!("Los miembros de {} no pueden apuntarse como titulares a este evento", Mention::Role(*role)),
+            Condition::MaxStarterSignupsPerWeek(max) => format!("Ya estas apuntado como titular a {max} evento(s) esta semana, que es el maximo permitido"),
+            Condition::RequiredClass(classes) => format!("Este evento solo admite titulares de clase {}",
+                                                         classes.iter().map(|c| c.label_es()).collect::<Vec<String>>().join(", ")),
+            Condition::MinAttendance(min) => format!("Tu asistencia a eventos anteriores esta por debajo del {min}% necesario"),
+            Condition::NoOverlap => "Ya estas apuntado como titular a otro evento a la misma hora".to_string(),
+        }
+    }
+}
+
+impl RuleOutcome {
+    pub fn label_es(&self) -> String {
+        match self {
+            RuleOutcome::Reserve => "reserva",
+            RuleOutcome::Approval => "aprobacion",
+            RuleOutcome::Reject => "rechazar",
+        }.to_string()
+    }
+}
+
+/// Checks a starter signup against `rules`, `None` when every rule is met
+pub fn check_rules(rules: &[EligibilityRule], profile: &SignupProfile) -> Option<Verdict> {
+    let broken: Vec<&EligibilityRule> = rules.iter().filter(|r| !r.is_met(profile)).collect();
+    broken.iter()
+        .map(|r| r.outcome)
+        .reduce(|a, b| if b > a { b } else { a })
+        .map(|outcome| Verdict {
+            outcome,
+            reasons: broken.iter().map(|r| r.condition.explanation_es()).collect()
+        })
+}
+
+/// Rules used by events without their own: the notification role is required,
+/// otherwise the event is an initiation one closed to advanced rosters
+pub fn default_rules(kind: EventKind, notification_role: Option<RoleId>) -> Vec<EligibilityRule> {
+    match notification_role {
+        Some(role) => vec![EligibilityRule::new(Condition::RequiredRole(role), RuleOutcome::Reserve)],
+        None => match kind {
+            EventKind::Trial => ADVANCED_TRIAL_ROLES.to_vec(),
+            EventKind::PvP => ADVANCED_PVP_ROLES.to_vec()
+        }.into_iter()
+            .map(|role| EligibilityRule::new(Condition::ForbiddenRole(RoleId::new(role)), RuleOutcome::Reserve))
+            .collect()
+    }
+}
+
+impl Display for EligibilityRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let condition = match &self.condition {
+            Condition::RequiredRole(role) => format!("rol {}", Mention::Role(*role)),
+            Condition::ForbiddenRole(role) => format!("sin-rol {}", Mention::Role(*role)),
+            Condition::MaxStarterSignupsPerWeek(max) => format!("max-semana {max}"),
+            Condition::RequiredClass(classes) => format!("clase {}", classes.iter().map(|c| c.to_string()).collect::<Vec<String>>().join(",")),
+            Condition::MinAttendance(min) => format!("asistencia {min}%"),
+            Condition::NoOverlap => "sin-solape".to_string(),
+        };
+        write!(f, "{condition} => {}", self.outcome.label_es())
+    }
+}
+
+impl FromStr for EligibilityRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || Error::ParseRule(s.trim().to_string());
+        let (condition, outcome) = s.split_once("=>").ok_or_else(invalid)?;
+        // Conditions without a value, like `sin-solape`, are a single word
+        let (name, value) = condition.trim().split_once(' ').unwrap_or((condition.trim(), ""));
+        let value = value.trim();
+        let role = || value.trim_start_matches("<@&").trim_end_matches('>').parse::<u64>().ok().filter(|id| *id != 0).map(RoleId::new);
+
+        let condition = match name {
+            "rol" => Condition::RequiredRole(role().ok_or_else(invalid)?),
+            "sin-rol" => Condition::ForbiddenRole(role().ok_or_else(invalid)?),
+            "max-semana" => Condition::MaxStarterSignupsPerWeek(value.parse().map_err(|_| invalid())?),
+            "clase" => Condition::RequiredClass(value.split(',')
+                .map(|c| PlayerClass::from_str(c.trim()))
+                .collect::<Result<Vec<PlayerClass>>>()?),
+            "asistencia" => Condition::MinAttendance(value.trim_end_matches('%').parse::<u8>().ok().filter(|p| *p <= 100).ok_or_else(invalid)?),
+            "sin-solape" if value.is_empty() => Condition::NoOverlap,
+            _ => return Err(invalid())
+        };
+
+        let outcome = match outcome.trim() {
+            "reserva" => RuleOutcome::Reserve,
+            "aprobacion" => RuleOutcome::Approval,
+            "rechazar" => RuleOutcome::Reject,
+            _ => return Err(invalid())
+        };
+
+        Ok(EligibilityRule { condition, outcome })
+    }
+}
@@ -0,0 +1,14 @@
+use serenity::all::{ChannelId, MessageId, UserId};
+use super::EventRole;
+
+/// A pending role swap proposed by `requester` to `target`, roles as they were when proposed
+#[derive(Debug, Clone)]
+pub struct SwapRequest {
+    pub id: i32,
+    pub message: MessageId,
+    pub channel: ChannelId,
+    pub requester: UserId,
+    pub requester_role: EventRole,
+    pub target: UserId,
+    pub target_role: EventRole
+}
@@ -0,0 +1,48 @@
+use std::io::prelude::*;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use serde::{Deserialize, Serialize};
+use serenity::all::UserId;
+use super::{ClassQuota, EligibilityRule, EventKind, PlayersInRole};
+use crate::prelude::*;
+
+/// Named event composition that can be reused when creating events
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Template {
+    pub name: String,
+    pub kind: EventKind,
+    pub roles: Vec<PlayersInRole>,
+    #[serde(default)]
+    pub rules: Vec<EligibilityRule>,
+    #[serde(default)]
+    pub class_quotas: Vec<ClassQuota>,
+    /// Member that saved it, only they and the officers can overwrite it
+    #[serde(default)]
+    pub owner: Option<UserId>
+}
+
+impl Template {
+    pub fn from_code(name: impl Into<String>, kind: EventKind, code: &str) -> Result<Self> {
+        Ok(Template { name: name.into(), kind, roles: decode_roles(code)?, rules: vec![], class_quotas: vec![], owner: None })
+    }
+}
+
+/// Roster code shown in the wizard, a base64 encoded deflate of the roles json
+pub fn encode_roles(roles: &Vec<PlayersInRole>) -> Result<String> {
+    let signups = serde_json::to_string(roles)?;
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(signups.as_bytes())?;
+    let compressed = encoder.finish()?;
+    Ok(BASE64_STANDARD.encode(compressed))
+}
+
+pub fn decode_roles(code: &str) -> Result<Vec<PlayersInRole>> {
+    let decoded = BASE64_STANDARD.decode(code.trim().as_bytes())?;
+    let mut decoder = DeflateDecoder::new(decoded.as_slice());
+    let mut json_roles = String::new();
+    decoder.read_to_string(&mut json_roles)?;
+    Ok(serde_json::from_str(&json_roles)?)
+}
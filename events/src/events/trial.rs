@@ -0,0 +1,131 @@
+use std::time::Duration;
+use duration_string::DurationString;
+use strum::{EnumIter, IntoEnumIterator};
+use super::{Event, EventRole};
+
+/// Every trial can be run in any difficulty
+const ALL_DIFFICULTIES: &[TrialDifficulty] = &[TrialDifficulty::Normal, TrialDifficulty::Veteran, TrialDifficulty::VeteranHardMode];
+
+/// Trials known by the bot, with the image bundled in `assets/trial` when there is one
+pub const TRIALS: [Trial; 14] = [
+    Trial::new("aa", "Aetherian Archive", "Archivo Aeterico", Some("aa.jpg"), &["aetherian", "aeterico"], 90, (2, 2, 8)),
+    Trial::new("hrc", "Hel Ra Citadel", "Ciudadela de Hel Ra", Some("hrc.jpg"), &["hel ra"], 90, (2, 2, 8)),
+    Trial::new("so", "Sanctum Ophidia", "Santuario de Ofidia", Some("so.png"), &["ophidia", "sanctum"], 120, (2, 2, 8)),
+    Trial::new("mol", "Maw of Lorkhaj", "Fauces de Lorkhaj", Some("mol.jpg"), &["maw", "lorkhaj"], 120, (2, 2, 8)),
+    Trial::new("hof", "Halls of Fabrication", "Salones de la Fabricacion", Some("hof.jpg"), &["fabrication", "fabricacion"], 150, (2, 2, 8)),
+    Trial::new("as", "Asylum Sanctorium", "Amparo Sanctorium", Some("as.jpg"), &["asylum", "amparo", "sanctorium"], 60, (2, 2, 8)),
+    Trial::new("cr", "Cloudrest", "Nubelia", Some("cr.jpg"), &["cloudrest", "nubelia"], 90, (2, 3, 7)),
+    Trial::new("ss", "Sunspire", "Cumbre del Sol", Some("ss.jpg"), &["sunspire", "sol"], 120, (2, 2, 8)),
+    Trial::new("ka", "Kyne's Aegis", "Egida de Kyne", Some("ka.png"), &["kyne", "egida"], 120, (2, 2, 8)),
+    Trial::new("rg", "Rockgrove", "Arboleda Rocosa", None, &["rockgrove", "arboleda"], 120, (2, 2, 8)),
+    Trial::new("dsr", "Dreadsail Reef", "Arrecife Velaterror", Some("dsr.jpg"), &["dreadsail", "arrecife"], 150, (2, 2, 8)),
+    Trial::new("se", "Sanity's Edge", "Limite de la Locura", Some("se.jpg"), &["sanity", "locura"], 150, (2, 3, 7)),
+    Trial::new("lc", "Lucent Citadel", "Ciudadela Lucida", None, &["lucent", "lucida"], 150, (2, 2, 8)),
+    Trial::new("oc", "Ossein Cage", "Jaula Osea", None, &["ossein", "osea"], 150, (2, 2, 8)),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
+pub enum TrialDifficulty {
+    Normal, Veteran, VeteranHardMode
+}
+
+/// Catalog entry of a trial, the duration and composition are the defaults of its veteran mode
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trial {
+    /// Short name used by the players, e.g. `rg`
+    pub key: &'static str,
+    pub name_en: &'static str,
+    pub name_es: &'static str,
+    /// File in `assets/trial`, without one the image is picked automatically
+    pub image: Option<&'static str>,
+    /// Words that identify the trial in a title, besides the key
+    pub aliases: &'static [&'static str],
+    pub minutes: u64,
+    /// Tanks, healers and damage dealers
+    pub composition: (usize, usize, usize),
+    pub difficulties: &'static [TrialDifficulty]
+}
+
+impl TrialDifficulty {
+    pub fn to_id(&self) -> &'static str {
+        match self {
+            TrialDifficulty::Normal => "normal",
+            TrialDifficulty::Veteran => "veteran",
+            TrialDifficulty::VeteranHardMode => "hm",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        Self::iter().find(|d| d.to_id() == id)
+    }
+
+    pub fn label_es(&self) -> &'static str {
+        match self {
+            TrialDifficulty::Normal => "Normal",
+            TrialDifficulty::Veteran => "Veterano",
+            TrialDifficulty::VeteranHardMode => "Veterano HM",
+        }
+    }
+
+    /// Name of the trial in this difficulty as written in game, e.g. `vRG HM`
+    pub fn short_name(&self, key: &str) -> String {
+        let key = key.to_uppercase();
+        match self {
+            TrialDifficulty::Normal => format!("n{key}"),
+            TrialDifficulty::Veteran => format!("v{key}"),
+            TrialDifficulty::VeteranHardMode => format!("v{key} HM"),
+        }
+    }
+
+    /// Normal runs are quicker and hard modes take an extra hour of tries
+    fn minutes(&self, veteran: u64) -> u64 {
+        match self {
+            TrialDifficulty::Normal => veteran.saturating_sub(30).max(60),
+            TrialDifficulty::Veteran => veteran,
+            TrialDifficulty::VeteranHardMode => veteran + 60,
+        }
+    }
+}
+
+impl Trial {
+    const fn new(key: &'static str, name_en: &'static str, name_es: &'static str, image: Option<&'static str>,
+                 aliases: &'static [&'static str], minutes: u64, composition: (usize, usize, usize)) -> Self {
+        Trial { key, name_en, name_es, image, aliases, minutes, composition, difficulties: ALL_DIFFICULTIES }
+    }
+
+    pub fn find(key: &str) -> Option<&'static Trial> {
+        TRIALS.iter().find(|t| t.key == key)
+    }
+
+    /// Trial with a bundled image file
+    pub fn with_image(file_name: &str) -> Option<&'static Trial> {
+        TRIALS.iter().find(|t| t.image == Some(file_name))
+    }
+
+    pub fn supports(&self, difficulty: TrialDifficulty) -> bool {
+        self.difficulties.contains(&difficulty)
+    }
+
+    /// Event title, e.g. `vRG HM - Arboleda Rocosa`
+    pub fn title(&self, difficulty: TrialDifficulty) -> String {
+        format!("{} - {}", difficulty.short_name(self.key), self.name_es)
+    }
+
+    pub fn duration(&self, difficulty: TrialDifficulty) -> DurationString {
+        Duration::from_secs(difficulty.minutes(self.minutes) * 60).into()
+    }
+
+    /// Bundled image reference, see [`super::EventImage::reference`], trials without one get the automatic image
+    pub fn image_reference(&self) -> Option<String> {
+        self.image.map(|file| format!("asset:trial/{file}"))
+    }
+
+    /// Image and role caps of the trial, the title and duration are proposed to the leader in the info modal
+    pub fn apply(&self, event: &mut Event) {
+        let (tanks, healers, dd) = self.composition;
+        event.image = self.image_reference();
+        event.set_max(EventRole::Tank, Some(tanks));
+        event.set_max(EventRole::Healer, Some(healers));
+        event.set_max(EventRole::DD, Some(dd));
+    }
+}
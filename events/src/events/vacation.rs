@@ -0,0 +1,73 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serenity::all::UserId;
+use super::Player;
+use crate::prelude::*;
+
+/// Hours to go from spanish midnight to UTC, same hack as the event dates
+pub(crate) const SPAIN_OFFSET_HOURS: i64 = 2;
+
+/// Away period of a player, every event starting inside it gets the player as absent
+#[derive(Debug, Clone)]
+pub struct Vacation {
+    pub id: i32,
+    pub user: UserId,
+    pub name: String,
+    pub start: DateTime<Utc>,
+    /// Exclusive, the midnight after the last day
+    pub end: DateTime<Utc>,
+    pub reason: String
+}
+
+impl Vacation {
+    pub fn covers(&self, datetime: DateTime<Utc>) -> bool {
+        self.start <= datetime && datetime < self.end
+    }
+
+    /// The player signed as absent with the reason of the vacation
+    pub fn absent_player(&self) -> Player {
+        let mut player = Player::new(self.user, &self.name);
+        player.absence = Some(self.reason.clone());
+        player
+    }
+
+    pub fn label_es(&self) -> String {
+        let last_day = self.end - Duration::try_days(1).unwrap_or_default();
+        format!("del {} al {}: {}", local_day(self.start).format("%d/%m/%Y"), local_day(last_day).format("%d/%m/%Y"), self.reason)
+    }
+}
+
+/// Start and exclusive end of the days from `first` to `last`, both included
+pub fn vacation_window(first: NaiveDate, last: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let midnight = |day: NaiveDate| day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc() - Duration::try_hours(SPAIN_OFFSET_HOURS).unwrap_or_default();
+    (midnight(first), midnight(last.succ_opt().unwrap_or(last)))
+}
+
+/// Parses `dd/mm` or `dd/mm/aaaa`, without year the date is the next one from `today`
+pub fn parse_day(value: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let invalid = || Error::InvalidDate(value.trim().to_string());
+    let parts: Vec<u32> = value.trim().split(['/', '-'])
+        .map(|p| p.parse::<u32>().map_err(|_| invalid()))
+        .collect::<Result<Vec<u32>>>()?;
+
+    match parts.as_slice() {
+        [day, month] => {
+            let this_year = NaiveDate::from_ymd_opt(today.year(), *month, *day).ok_or_else(invalid)?;
+            if this_year < today {
+                NaiveDate::from_ymd_opt(today.year() + 1, *month, *day).ok_or_else(invalid)
+            } else {
+                Ok(this_year)
+            }
+        }
+        [day, month, year] => NaiveDate::from_ymd_opt(*year as i32, *month, *day).ok_or_else(invalid),
+        _ => Err(invalid())
+    }
+}
+
+/// Current day in Spain
+pub fn local_today() -> NaiveDate {
+    local_day(Utc::now())
+}
+
+fn local_day(datetime: DateTime<Utc>) -> NaiveDate {
+    (datetime + Duration::try_hours(SPAIN_OFFSET_HOURS).unwrap_or_default()).date_naive()
+}
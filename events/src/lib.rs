@@ -4,4 +4,9 @@ mod prelude;
 mod store;
 mod utils;
 pub mod tasks;
-pub mod events;
\ No newline at end of file
+pub mod events;
+pub mod discord;
+pub mod metrics;
+
+pub use error::Error;
+pub use store::Store;
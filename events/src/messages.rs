@@ -1 +1,5 @@
-pub mod events;
\ No newline at end of file
+pub mod events;
+pub mod accounts;
+pub mod failures;
+pub mod notifications;
+pub mod fines;
\ No newline at end of file
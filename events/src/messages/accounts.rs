@@ -0,0 +1,42 @@
+use serenity::all::{CommandInteraction, Context, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedValue};
+use sqlx::PgPool;
+use crate::events::parse_eso_account;
+use crate::prelude::*;
+
+/// `/eso-account @handle` registers the ESO account used in the invite scripts, without handle shows the current one
+pub async fn eso_account_command(interaction: &CommandInteraction, ctx: &Context, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let handle = interaction.data.options().into_iter()
+        .find_map(|o| match (o.name, o.value) {
+            ("handle", ResolvedValue::String(handle)) => Some(handle.to_string()),
+            _ => None
+        });
+
+    let embed = match handle.map(|h| parse_eso_account(&h).map_err(|_| h)) {
+        Some(Ok(handle)) => {
+            store.set_eso_account(interaction.user.id, &handle).await?;
+            CreateEmbed::new()
+                .title("Cuenta de ESO guardada")
+                .description(format!("Las invitaciones de los eventos usaran `{handle}`"))
+        }
+        Some(Err(handle)) => CreateEmbed::new()
+            .title("Cuenta de ESO no valida")
+            .description(format!("`{handle}` no es una cuenta de ESO, debe ser como `@MiCuenta`: de 3 a 20 letras, numeros, `.`, `_`, `-` o `'`")),
+        None => match store.get_eso_account(interaction.user.id).await? {
+            Some(handle) => CreateEmbed::new()
+                .title("Tu cuenta de ESO")
+                .description(format!("`{handle}`, usa `/eso-account @handle` para cambiarla")),
+            None => CreateEmbed::new()
+                .title("Sin cuenta de ESO")
+                .description("Usa `/eso-account @handle` para que las invitaciones de los eventos te lleguen")
+        }
+    };
+
+    interaction.create_response(&ctx.http, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .embed(embed)
+    )).await?;
+
+    Ok(())
+}
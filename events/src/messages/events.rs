@@ -2,14 +2,90 @@ pub mod delete_event;
 pub mod edit;
 pub mod create;
 pub mod signup;
+pub mod template;
+pub mod swap;
+pub mod rules;
+pub mod quotas;
+pub mod images;
+pub mod scheduled;
+pub mod feed;
+pub mod overlap;
+pub mod roster;
+pub mod absence;
+pub mod vacation;
+pub mod staffing;
+pub mod checkin;
+pub mod attendance;
+pub mod list;
 
+use std::sync::Arc;
 use rand::prelude::SliceRandom;
-use serenity::all::{CreateInteractionResponse, CreateInteractionResponseMessage};
+use serenity::all::{ChannelId, CommandInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage, EditMessage, GuildId, MessageId};
+use sqlx::PgPool;
+use tracing::warn;
+use crate::discord::Discord;
+use crate::events::Event;
+use crate::prelude::*;
 
 pub use delete_event::delete_event;
 pub use edit::edit_event;
 pub use create::create_event;
-pub use signup::signup_event;
+pub use signup::{signup_event, signup_approval};
+pub use swap::swap_response;
+pub use roster::{roster_command, roster_autocomplete};
+pub use absence::absence_submit;
+pub use vacation::{vacation_command, vacation_autocomplete};
+pub use staffing::staffing_join;
+pub use checkin::checkin_confirm;
+pub use attendance::attendance_answer;
+pub use scheduled::{scheduled_event_update, scheduled_event_delete, scheduled_event_interest};
+
+/// Entrypoint for the `/event` command and its subcommands
+pub async fn event_command(interaction: &CommandInteraction, ctx: &Context, pool: PgPool, announcement_hook: &str) -> Result<()> {
+    match subcommand(interaction) {
+        Some("create") => create::create_event_command(interaction, ctx, pool, announcement_hook).await,
+        Some("template") => template::save_template(interaction, ctx, pool).await,
+        Some("rules") => rules::event_rules(interaction, ctx, pool).await,
+        Some("quotas") => quotas::event_quotas(interaction, ctx, pool).await,
+        Some("image") => images::upload_image(interaction, ctx, pool).await,
+        Some("announce") => feed::announce(interaction, ctx, pool).await,
+        Some("checkin") => checkin::event_checkin(interaction, Arc::new(ctx.clone()), pool).await,
+        Some("list") => list::list_events(interaction, ctx, pool).await,
+        _ => Err(Error::UnknownInteraction(interaction.data.name.to_string()))
+    }
+}
+
+pub async fn event_autocomplete(interaction: &CommandInteraction, ctx: &Context, pool: PgPool) -> Result<()> {
+    match subcommand(interaction) {
+        Some("create") | Some("rules") | Some("quotas") => create::create_event_autocomplete(interaction, ctx, pool).await,
+        _ => Err(Error::UnknownInteraction(interaction.data.name.to_string()))
+    }
+}
+
+fn subcommand(interaction: &CommandInteraction) -> Option<&str> {
+    interaction.data.options.first().map(|o| o.name.as_str())
+}
+
+/// Channel of the event message, events created before it was stored find it in their scheduled event
+pub(crate) async fn event_channel<D: Discord + ?Sized>(discord: &D, guild: GuildId, event: &Event) -> Option<ChannelId> {
+    match (event.channel, event.scheduled_event) {
+        (Some(channel), _) => Some(channel),
+        (None, Some(scheduled)) => discord.scheduled_event(guild, scheduled).await.ok()
+            .and_then(|s| s.description)
+            .and_then(|d| d.lines().next().and_then(quotas::parse_channel_id)),
+        (None, None) => None
+    }
+}
+
+/// Updates the event message, a message that can't be reached doesn't stop the change
+pub(crate) async fn render_event<D: Discord + ?Sized>(discord: &D, channel: Option<ChannelId>, message: MessageId, event: &Event) {
+    match channel {
+        Some(channel) => if let Err(why) = discord.edit_message(channel, message, EditMessage::new().embeds(event.embeds())).await {
+            warn!("Event message {message} not updated: {why:?}");
+        },
+        None => warn!("Event message {message} not updated, channel unknown")
+    }
+}
 
 fn not_an_event_response() -> CreateInteractionResponse {
     CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
@@ -0,0 +1,139 @@
+use chrono::{Duration, Utc};
+use serenity::all::{CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal, GuildId, Mention, MessageId, ModalInteraction, UserId};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{local_today, parse_day, vacation_window, Event, EventRole, FineReason, NotificationKind, Player, Vacation, LATE_ABSENCE_HOURS};
+use crate::tasks::fines::fine_player;
+use crate::tasks::notifications::notify;
+use crate::prelude::*;
+
+/// Reason and optional away period asked when clicking the absent button
+pub(super) fn absence_modal() -> CreateInteractionResponse {
+    CreateInteractionResponse::Modal(CreateModal::new("absence_modal", "Ausencia")
+        .components(vec![
+            components::short_input("Motivo", "absence_reason", "Trabajo, viaje, ...", true),
+            components::short_input("Ausente desde (opcional, dd/mm)", "absence_from", "Por defecto hoy", false),
+            components::short_input("Ausente hasta (opcional, dd/mm)", "absence_to", "Vacio: solo el dia de inicio, o solo este evento", false),
+        ]))
+}
+
+/// Signs the player as absent with the reason of the modal, with a start or end date the period is saved as a vacation,
+/// a start alone is a single day
+pub async fn absence_submit(modal: &ModalInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let message = modal.message.as_ref().ok_or(Error::UnknownInteraction("absence without event message".to_string()))?;
+    let guild = modal.guild_id.ok_or(Error::UnknownInteraction("absence outside guild".to_string()))?;
+    let name = modal.member.as_ref().map(|m| m.display_name().to_string()).unwrap_or(modal.user.name.clone());
+    let reason = get_input_value(&modal.data.components, 0).map(|r| r.trim().to_string()).unwrap_or_default();
+    let from = input(modal, 1);
+    let to = input(modal, 2);
+
+    let mut event = store.get_event(message.id).await?;
+    event.channel = event.channel.or(Some(message.channel_id));
+    let mut player = Player::new(modal.user.id, &name);
+    player.absence = Some(reason.clone()).filter(|r| !r.is_empty());
+
+    // Starters leaving right before the event are fined, whatever the reason
+    let late = event.is_starter(modal.user.id) && event.datetime.is_some_and(|start| {
+        let left = start - Utc::now();
+        left > Duration::zero() && left < Duration::try_hours(LATE_ABSENCE_HOURS).unwrap_or_default()
+    });
+
+    let answer = match (from, to) {
+        (None, None) => {
+            mark_absent(discord, &store, guild, message.id, &mut event, player.clone(), Some(modal.user.id)).await?;
+            "Ausencia apuntada, gracias por avisar".to_string()
+        }
+        (from, to) => {
+            let today = local_today();
+            let first = match from {
+                Some(from) => parse_day(&from, today)?,
+                None => today
+            };
+            let last = match to {
+                Some(to) => parse_day(&to, today)?,
+                None => first
+            };
+            if last < first {
+                "La fecha de fin es anterior a la de inicio".to_string()
+            } else {
+                mark_absent(discord, &store, guild, message.id, &mut event, player.clone(), Some(modal.user.id)).await?;
+                let (start, end) = vacation_window(first, last);
+                let vacation = store.add_vacation(modal.user.id, &name, start, end, &reason).await?;
+                let others = apply_vacation(discord, &store, guild, &vacation, Some(message.id)).await?;
+                vacation_summary(&vacation, &others)
+            }
+        }
+    };
+
+    if late && event.role_of(modal.user.id) == Some(EventRole::Absent) {
+        let roles = modal.member.as_ref().map(|m| m.roles.clone()).unwrap_or_default();
+        fine_player(discord, &store, &event, message.id, &player, &roles, FineReason::LateAbsence).await?;
+    }
+
+    discord.create_response(modal.id, &modal.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .content(answer)
+    )).await
+}
+
+/// Signs the player as absent to every event of the vacation, except `skip`, returning their titles
+pub(super) async fn apply_vacation<D: Discord + ?Sized>(discord: &D, store: &Store, guild: GuildId, vacation: &Vacation, skip: Option<MessageId>) -> Result<Vec<String>> {
+    let mut titles = vec![];
+    for message in store.events_between(vacation.start, vacation.end).await? {
+        if Some(message) == skip {
+            continue;
+        }
+        let mut event = store.get_event(message).await?;
+        if event.role_of(vacation.user) != Some(EventRole::Absent) {
+            mark_absent(discord, store, guild, message, &mut event, vacation.absent_player(), Some(vacation.user)).await?;
+            titles.push(event.title);
+        }
+    }
+    Ok(titles)
+}
+
+/// Players with a vacation at the time of a new event start as absent
+pub(crate) async fn add_vacation_absences(store: &Store, event: &mut Event) -> Result<()> {
+    let Some(datetime) = event.datetime else { return Ok(()) };
+    for vacation in store.get_vacations(None).await? {
+        if vacation.covers(datetime) {
+            event.add_player(EventRole::Absent, vacation.absent_player());
+        }
+    }
+    Ok(())
+}
+
+pub(super) fn vacation_summary(vacation: &Vacation, events: &[String]) -> String {
+    let signed = if events.is_empty() {
+        "Se te apuntara como ausente en los eventos que se creen en esas fechas".to_string()
+    } else {
+        format!("Tambien estas como ausente en: {}", events.iter().map(|t| format!("**{t}**")).collect::<Vec<String>>().join(", "))
+    };
+    format!("🏖️ Ausencia guardada {}\n{signed}", vacation.label_es())
+}
+
+async fn mark_absent<D: Discord + ?Sized>(discord: &D, store: &Store, guild: GuildId, message: MessageId, event: &mut Event, player: Player, actor: Option<UserId>) -> Result<()> {
+    let name = player.name.clone();
+    let reason = player.absence.as_ref().map(|r| format!(": {r}")).unwrap_or_default();
+    store.signup_player(message, EventRole::Absent, &player).await?;
+    event.add_player(EventRole::Absent, player);
+
+    let channel = super::event_channel(discord, guild, event).await;
+    super::render_event(discord, channel, message, event).await;
+    super::feed::post_change(discord, store, message, event, actor, &format!("❌ **{name}** no podra asistir{reason}")).await?;
+
+    let place = match channel {
+        Some(channel) => format!("al evento en {}", Mention::Channel(channel)),
+        None => format!("a **{}**", event.title)
+    };
+    notify(discord, store, event.leader, NotificationKind::Absence, event.thread, &format!("{name} no va a poder asistir {place}{reason}")).await?;
+    Ok(())
+}
+
+fn input(modal: &ModalInteraction, idx: usize) -> Option<String> {
+    get_input_value(&modal.data.components, idx)
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
@@ -0,0 +1,53 @@
+use serenity::all::{ComponentInteraction, CreateInteractionResponse, CreateInteractionResponseMessage, MessageId};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{AttendanceProposal, EventRole, FineReason};
+use crate::prelude::*;
+use crate::tasks::fines::{fine_player, member_roles};
+
+/// Reason of the starters marked absent from the voice channel
+const NO_VOICE_REASON: &str = "Sin presencia en el canal de voz";
+
+/// Buttons of the attendance proposal, the leader confirms it and the missing starters are marked absent
+pub async fn attendance_answer(interaction: &ComponentInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let parts: Vec<&str> = interaction.data.custom_id.split('_').collect();
+    let (action, message) = match parts.as_slice() {
+        [_, action, message] => (*action, MessageId::new(message.parse()?)),
+        _ => return Err(Error::UnknownInteraction(interaction.data.custom_id.to_string()))
+    };
+
+    let mut event = store.get_event(message).await?;
+    let answer = if event.leader != interaction.user.id {
+        return discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .ephemeral(true)
+                .content("Solo el lider del evento puede confirmar la asistencia")
+        )).await;
+    } else if action == "confirm" {
+        let proposal = AttendanceProposal::new(&event, &store.voice_presence(message).await?);
+        for mut player in proposal.missing.iter().cloned() {
+            player.absence = Some(NO_VOICE_REASON.to_string());
+            event.add_player(EventRole::Absent, player.clone());
+            store.signup_player(message, EventRole::Absent, &player).await?;
+            fine_player(discord, &store, &event, message, &player, &member_roles(discord, &player).await, FineReason::NoShow).await?;
+        }
+
+        let late = proposal.late.iter().map(|(p, minutes)| format!("{} ({minutes} min)", p.name)).collect::<Vec<String>>();
+        let mut change = format!("📋 Asistencia confirmada: {}", proposal.summary_es());
+        if !late.is_empty() {
+            change.push_str(&format!("\nTarde: {}", late.join(", ")));
+        }
+        super::feed::post_change(discord, &store, message, &event, Some(interaction.user.id), &change).await?;
+        super::render_event(discord, event.channel, message, &event).await;
+        format!("Asistencia confirmada: {}", proposal.summary_es())
+    } else {
+        "Propuesta descartada, marca las ausencias a mano con /roster".to_string()
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+            .content(answer)
+            .components(vec![])
+    )).await
+}
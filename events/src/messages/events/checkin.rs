@@ -0,0 +1,106 @@
+use std::sync::Arc;
+use chrono::Utc;
+use serenity::all::{ChannelId, CommandInteraction, ComponentInteraction, CreateInteractionResponse, CreateInteractionResponseMessage, Mention, MessageId, ResolvedValue};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{EventRole, EventStatus};
+use crate::prelude::*;
+use crate::tasks;
+use super::quotas::parse_channel_id;
+use super::rules::parse_message_id;
+
+/// Button of the check-in message, confirms the attendance of the member
+pub async fn checkin_confirm(interaction: &ComponentInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let parts: Vec<&str> = interaction.data.custom_id.split('_').collect();
+    let (channel, message) = match parts.as_slice() {
+        [_, _, channel, message] => (ChannelId::new(channel.parse()?), MessageId::new(message.parse()?)),
+        _ => return Err(Error::UnknownInteraction(interaction.data.custom_id.to_string()))
+    };
+
+    let mut event = store.get_event(message).await?;
+    let user = interaction.user.id;
+    let answer = match event.role_of(user) {
+        None | Some(EventRole::Absent) => "No estas apuntado a este evento".to_string(),
+        Some(_) if event.confirmed.contains(&user) => "Ya habias confirmado tu asistencia".to_string(),
+        Some(_) if !event.checkin_open() => "El check-in de este evento aun no esta abierto".to_string(),
+        Some(role) => {
+            store.confirm_checkin(message, user).await?;
+            event.confirmed.push(user);
+            super::render_event(discord, Some(event.channel.unwrap_or(channel)), message, &event).await;
+            if role == EventRole::Reserve {
+                "Asistencia confirmada, si un titular no confirma a tiempo entraras en su lugar".to_string()
+            } else {
+                format!("Asistencia confirmada como {role}, nos vemos en el evento!")
+            }
+        }
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .content(answer)
+    )).await
+}
+
+/// `/event checkin`, the leader sets how many hours before the start the starters have to confirm
+pub async fn event_checkin(interaction: &CommandInteraction, discord: Arc<dyn Discord>, pool: PgPool) -> Result<()> {
+    let store = Arc::new(Store::new(pool));
+    let (mut link, mut hours) = (None, None);
+    if let Some(ResolvedValue::SubCommand(options)) = interaction.data.options().first().map(|o| o.value.clone()) {
+        for option in options {
+            match (option.name, option.value) {
+                ("event", ResolvedValue::String(value)) => link = Some(value.to_string()),
+                ("hours", ResolvedValue::String(value)) => hours = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let message = link.as_deref().and_then(parse_message_id);
+    let event = match message {
+        Some(message) => store.get_event(message).await.ok(),
+        None => None
+    };
+    let hours = match hours.as_deref() {
+        Some("off") | None => Ok(None),
+        Some(hours) => hours.parse::<u32>().map(Some)
+    };
+
+    let answer = match (message, event, hours) {
+        (Some(_), Some(_), Err(_)) => "Horas no validas".to_string(),
+        (Some(message), Some(mut event), Ok(hours)) if event.leader == interaction.user.id => {
+            store.update_checkin(message, hours).await?;
+            event.checkin = hours;
+            let channel = event.channel.or(link.as_deref().and_then(parse_channel_id));
+            if let (Some(channel), Some(datetime), EventStatus::Scheduled) = (channel, event.datetime, event.status) {
+                tasks::set_reminder(datetime, discord.clone(), channel, message, store.clone());
+            }
+            super::render_event(discord.as_ref(), channel, message, &event).await;
+
+            match (hours, event.checkin_opens(), event.checkin_closes()) {
+                (None, _, _) => "Check-in desactivado".to_string(),
+                (Some(hours), None, _) => format!("Check-in activado {hours}h antes del evento, se abrira cuando tenga fecha"),
+                (Some(hours), Some(opens), _) if opens > Utc::now() =>
+                    format!("Check-in activado, se abre <t:{}:R> ({hours}h antes del evento)", opens.timestamp()),
+                // Already inside the window, the starters are asked right away
+                (Some(_), _, Some(closes)) if closes > Utc::now() => {
+                    if let Some(channel) = channel {
+                        tasks::checkin::open_checkin(discord.as_ref(), &store, channel, message).await?;
+                    }
+                    format!("Check-in abierto hasta <t:{}:t>", closes.timestamp())
+                }
+                (Some(_), _, _) => format!("Ya no hay tiempo para el check-in del evento de {}",
+                                           channel.map(|c| Mention::Channel(c).to_string()).unwrap_or_default())
+            }
+        }
+        (Some(_), Some(_), _) => "Solo el lider del evento puede cambiar el check-in".to_string(),
+        _ => "Evento no encontrado, usa el enlace del mensaje del evento".to_string()
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .content(answer)
+    )).await
+}
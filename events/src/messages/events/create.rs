@@ -1,64 +1,229 @@
+mod command;
 mod composition;
 mod info;
 mod kind;
 mod scope;
 mod date;
 mod role;
+mod image;
+mod trial;
+mod pvp;
 
 use std::sync::Arc;
-use serenity::all::{ChannelId, Colour, CommandInteraction, Context, CreateActionRow, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, CreateScheduledEvent, ExecuteWebhook, GuildId, Mention, MessageId, ScheduledEventId, ScheduledEventType, Timestamp, Webhook};
+use serenity::all::{AutoArchiveDuration, ButtonStyle, ChannelId, Colour, CommandInteraction, ComponentInteraction, Context, CreateActionRow, CreateAttachment, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, CreateScheduledEvent, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, CreateThread, ExecuteWebhook, GuildId, Interaction, Mention, Message, MessageId, ScheduledEventId, ScheduledEventType, Timestamp, Webhook};
 use sqlx::PgPool;
-use crate::events::{Event, EventKind, EventRole, EventScopes};
+use strum::IntoEnumIterator;
+use crate::events::{auto_image, image_attachment, DraftStep, Event, EventKind, EventRole, EventScopes};
 use crate::prelude::*;
 use crate::tasks;
+use tracing::warn;
+
+pub use command::{create_event_autocomplete, create_event_command};
 
 pub async fn create_event(interaction: &CommandInteraction, ctx: &Context, pool: PgPool, announcement_hook: &str) -> Result<()> {
     let store = Store::new(pool);
+    let user = interaction.user.id;
+    let guild = interaction.guild_id.ok_or(Error::UnknownInteraction("event create outside guild".to_string()))?;
 
-    // Choose new event kind
-    let (interaction, kind, message) = kind::select_event_kind(interaction, ctx).await?;
+    let (message, mut interaction, mut event, mut step) = if let Some((step, event)) = store.get_draft(user).await? {
+        // Resume or discard pending draft
+        interaction.create_response(&ctx.http, CreateInteractionResponse::Message(draft_message(&event, step))).await?;
+        let message = interaction.get_response(&ctx.http).await?;
+        let interaction = message.await_component_interaction(&ctx.shard).await.ok_or(Error::Timeout)?;
 
-    // Request basic info
-    let (modal, mut event) = info::request_info_modal(&message, &interaction, ctx, kind).await?;
+        if interaction.data.custom_id == "create_event_draft_continue" {
+            (message, Interaction::Component(interaction), event, step)
+        } else {
+            store.remove_draft(user).await?;
+            interaction.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(kind::create_event_message())).await?;
+            let (interaction, event) = new_event(&message, ctx).await?;
+            (message, interaction, event, DraftStep::Image)
+        }
+    } else {
+        interaction.create_response(&ctx.http, CreateInteractionResponse::Message(kind::create_event_message())).await?;
+        let message = interaction.get_response(&ctx.http).await?;
+        let (interaction, event) = new_event(&message, ctx).await?;
+        (message, interaction, event, DraftStep::Image)
+    };
 
-    // Event composition
-    let interaction = composition::handle_composition(&message, &modal, ctx, &mut event).await?;
+    let event_channel = loop {
+        store.save_draft(user, step, &event).await?;
 
-    // Event notification role
-    let interaction = role::select_role(&message, &interaction, ctx, &mut event).await?;
+        let mut channel = None;
+        interaction = match step {
+            DraftStep::Info => match &interaction {
+                Interaction::Component(i) => Interaction::Modal(info::edit_info_modal(&message, i, ctx, &mut event).await?),
+                _ => return Err(Error::UnknownInteraction(format!("{:?}", interaction.kind())))
+            },
+            DraftStep::Image => Interaction::Component(image::select_image(&message, &interaction, ctx, &store, guild, &mut event).await?),
+            DraftStep::Composition => composition::handle_composition(&message, &interaction, ctx, &mut event).await?,
+            DraftStep::Role => Interaction::Component(role::select_role(&message, &interaction, ctx, &mut event).await?),
+            DraftStep::Scope => Interaction::Component(scope::handle_scope(&message, &interaction, ctx, &mut event).await?),
+            DraftStep::Date => {
+                let (i, c) = date::select_date(&message, &interaction, ctx, &mut event).await?;
+                channel = c;
+                Interaction::Component(i)
+            }
+        };
 
-    // Event scope
-    let interaction = scope::handle_scope(&message, &interaction, ctx, &mut event).await?;
+        // Jump to any step out of order, otherwise keep going
+        if let Some(target) = selected_step(&interaction) {
+            step = target;
+        } else if let Some(next) = step.next() {
+            step = next;
+        } else if let Some(channel) = channel {
+            break channel;
+        } else {
+            return Err(Error::Timeout);
+        }
+    };
 
-    // Event datetime
-    let (interaction, event_channel) = date::select_date(&message, &interaction, ctx, &mut event).await?;
+    let interaction = match interaction {
+        Interaction::Component(i) => i,
+        _ => return Err(Error::UnknownInteraction(format!("{:?}", interaction.kind())))
+    };
 
     // Create event
-    let image = event.image().await?;
-    let event_message = event_channel.send_message(&ctx.http, CreateMessage::new()
-        .content(event.notification_role.map(|r| Mention::Role(r).to_string()).unwrap_or("".to_string()))
-        .embed(event.embed().attachment(image.filename))
-        .components(signup_buttons(&event))
-    ).await?;
-
-    event.scheduled_event = Some(create_discord_event(interaction.guild_id.unwrap(), ctx, &event, event_channel, event_message.id).await?);
-
-    store.create_event(event_message.id, &event).await?;
-
-    tasks::set_reminder(event.datetime.clone().unwrap(), Arc::new(ctx.clone()), event_channel, event_message.id, Arc::new(store.clone()));
-
-    send_announcement(ctx, &event, event_channel, announcement_hook).await?;
+    let message = publish_event(ctx, &store, &mut event, guild, event_channel, announcement_hook).await?;
+    store.remove_draft(user).await?;
+    let warnings = super::overlap::conflict_warnings(&store, &event, message, guild).await?;
 
     interaction.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(
         CreateInteractionResponseMessage::new()
             .ephemeral(true)
-            .embed(CreateEmbed::new().title("Nuevo evento!").description(format!("Evento creado en {}", Mention::Channel(event_channel).to_string())))
+            .embed(created_embed(event_channel, &warnings))
             .components(vec![])
     )).await?;
 
     Ok(())
 }
 
+/// Confirmation for the leader with the conflicts of the new event
+pub(super) fn created_embed(channel: ChannelId, warnings: &[String]) -> CreateEmbed {
+    let mut description = format!("Evento creado en {}", Mention::Channel(channel));
+    for warning in warnings {
+        description.push_str(&format!("\n{warning}"));
+    }
+    CreateEmbed::new().title("Nuevo evento!").description(description)
+}
+
+pub(super) async fn publish_event(ctx: &Context, store: &Store, event: &mut Event, guild: GuildId, channel: ChannelId, announcement_hook: &str) -> Result<MessageId> {
+    if event.image.is_none() {
+        event.image = auto_image(store, guild, event.kind, &event.title).await?;
+    }
+    super::absence::add_vacation_absences(store, event).await?;
+    let image = match &event.image {
+        Some(image) => Some(image_attachment(store, image).await?),
+        None => None
+    };
+
+    let mut message = CreateMessage::new()
+        .content(event.notification_role.map(|r| Mention::Role(r).to_string()).unwrap_or("".to_string()))
+        .embed(event.embed())
+        .components(signup_buttons(event));
+    if let Some(image) = &image {
+        message = message.add_file(image.clone());
+    }
+    let event_message = channel.send_message(&ctx.http, message).await?;
+    event.channel = Some(channel);
+
+    // Questions and the change feed of the event go to its own thread
+    match channel.create_thread_from_message(&ctx.http, event_message.id, CreateThread::new(super::feed::thread_name(event))
+        .auto_archive_duration(AutoArchiveDuration::OneWeek)).await {
+        Ok(thread) => event.thread = Some(thread.id),
+        Err(why) => warn!("Event thread not created: {why:?}")
+    }
+
+    event.scheduled_event = Some(create_discord_event(guild, ctx, event, channel, event_message.id, image.as_ref()).await?);
+
+    store.create_event(event_message.id, event).await?;
+
+    if let Some(datetime) = event.datetime {
+        tasks::set_reminder(datetime, Arc::new(ctx.clone()), channel, event_message.id, Arc::new(store.clone()));
+    }
+
+    send_announcement(ctx, event, channel, announcement_hook).await?;
+
+    Ok(event_message.id)
+}
+
+async fn new_event(message: &Message, ctx: &Context) -> Result<(Interaction, Event)> {
+    // Choose new event kind
+    let (interaction, kind) = kind::select_event_kind(message, ctx).await?;
+
+    // Trials can start from the catalog, PvP events say where and how they are played
+    let (interaction, trial, pvp) = match kind {
+        EventKind::Trial => {
+            let (interaction, trial) = trial::select_trial(message, interaction, ctx).await?;
+            (interaction, trial, None)
+        }
+        EventKind::PvP => {
+            let (interaction, pvp) = pvp::select_pvp(message, interaction, ctx).await?;
+            (interaction, None, Some(pvp))
+        }
+    };
+
+    // Request basic info
+    let (modal, mut event) = info::request_info_modal(message, &interaction, ctx, kind, trial).await?;
+    if pvp.is_some() {
+        event.pvp = pvp;
+    }
+
+    Ok((Interaction::Modal(modal), event))
+}
+
+pub(super) async fn update_wizard(interaction: &Interaction, ctx: &Context, response: CreateInteractionResponse) -> Result<()> {
+    match interaction {
+        Interaction::Command(i) => i.create_response(&ctx.http, response).await?,
+        Interaction::Component(i) => i.create_response(&ctx.http, response).await?,
+        Interaction::Modal(i) => i.create_response(&ctx.http, response).await?,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+pub(super) fn step_menu(current: DraftStep) -> CreateSelectMenu {
+    let kind = CreateSelectMenuKind::String {
+        options: DraftStep::iter()
+            .map(|step| CreateSelectMenuOption::new(step.label_es(), step.to_id())
+                .default_selection(step == current))
+            .collect()
+    };
+
+    CreateSelectMenu::new("create_event_goto", kind)
+        .placeholder("Ir a otro paso")
+}
+
+pub(super) fn is_step_jump(interaction: &ComponentInteraction) -> bool {
+    interaction.data.custom_id == "create_event_goto"
+}
+
+fn selected_step(interaction: &Interaction) -> Option<DraftStep> {
+    match interaction {
+        Interaction::Component(i) if is_step_jump(i) => get_selected_option(i)
+            .and_then(|step| DraftStep::from_id(&step)),
+        _ => None
+    }
+}
+
+fn draft_message(event: &Event, step: DraftStep) -> CreateInteractionResponseMessage {
+    CreateInteractionResponseMessage::new()
+        .ephemeral(true)
+        .embed(event.embed_preview())
+        .add_embed(CreateEmbed::new()
+            .title("Tienes un evento a medias")
+            .description(format!("Te quedaste en el paso: **{}**", step.label_es())))
+        .components(vec![CreateActionRow::Buttons(vec![
+            CreateButton::new("create_event_draft_continue")
+                .label("Continuar borrador")
+                .style(ButtonStyle::Success),
+            CreateButton::new("create_event_draft_discard")
+                .label("Descartar")
+                .style(ButtonStyle::Danger)
+        ])])
+}
+
 fn signup_buttons(event: &Event) -> Vec<CreateActionRow> {
     let mut components = vec![];
 
@@ -85,18 +250,18 @@ fn signup_buttons(event: &Event) -> Vec<CreateActionRow> {
     components
 }
 
-async fn create_discord_event(guild: GuildId, ctx: &Context, data: &Event, channel: ChannelId, msg: MessageId) -> Result<ScheduledEventId> {
-    let date = data.datetime.unwrap();
+async fn create_discord_event(guild: GuildId, ctx: &Context, data: &Event, channel: ChannelId, msg: MessageId, image: Option<&CreateAttachment>) -> Result<ScheduledEventId> {
+    let date = data.datetime.ok_or(Error::ParseEvent("event without date".to_string()))?;
     let duration: std::time::Duration = data.duration.into();
     let end_datetime = date + duration;
-    let event = guild.create_scheduled_event(&ctx.http, CreateScheduledEvent::new(ScheduledEventType::Voice, &data.title, Timestamp::from_unix_timestamp(date.timestamp()).unwrap())
+    let mut builder = CreateScheduledEvent::new(ScheduledEventType::Voice, &data.title, Timestamp::from_unix_timestamp(date.timestamp())?)
         .description(format!("https://discord.com/channels/{}/{}/{}\n{}", guild, channel, msg, data.description))
-        .channel_id(if guild == 1134046249293717514 {1157232748604444683} else {
-            if data.kind == EventKind::PvP {1144350647848812564} else {1144350408769286274}
-        })
-        .end_time(Timestamp::from_unix_timestamp(end_datetime.timestamp()).unwrap())
-        .image(&data.image().await?)
-    ).await?;
+        .channel_id(data.kind.voice_channel(guild))
+        .end_time(Timestamp::from_unix_timestamp(end_datetime.timestamp())?);
+    if let Some(image) = image {
+        builder = builder.image(image);
+    }
+    let event = guild.create_scheduled_event(&ctx.http, builder).await?;
     Ok(event.id)
 }
 
@@ -104,14 +269,11 @@ async fn send_announcement(ctx: &Context, event: &Event, channel: ChannelId, hoo
     let event_announcement = CreateEmbed::new()
         .title("Nuevo evento!")
         .field("Titulo", &event.title, false)
-        .field(":hourglass_flowing_sand: Cuando", format!("<t:{}:F>", event.datetime.unwrap().timestamp()), true)
+        .field(":hourglass_flowing_sand: Cuando", event.datetime.map(|dt| format!("<t:{}:F>", dt.timestamp())).unwrap_or_default(), true)
         .field(":house: Donde", Mention::Channel(channel).to_string(), true)
         .field("", &event.description, false)
         .color(Colour::from_rgb(0, 255, 0))
-        .thumbnail(match event.kind {
-            EventKind::Trial => "https://images.uesp.net/2/26/ON-mapicon-SoloTrial.png",
-            EventKind::PvP => "https://images.uesp.net/9/9e/ON-icon-alliance-Ebonheart.png"
-        });
+        .thumbnail(event.thumbnail());
 
     let builder = ExecuteWebhook::new()
         .embed(event_announcement);
@@ -0,0 +1,231 @@
+use duration_string::DurationString;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serenity::all::{AutocompleteChoice, ChannelId, ChannelType, CommandInteraction, Context, CreateAutocompleteResponse, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, EditInteractionResponse, GuildId, ResolvedOption, ResolvedValue, RoleId};
+use sqlx::PgPool;
+use crate::events::{Event, EventKind, EventScopes};
+use crate::prelude::*;
+use super::date::{get_channel_weekday, next_event_datetime};
+
+/// `/event create` options, everything but the title and date can be omitted
+struct CreateOptions {
+    kind: EventKind,
+    title: String,
+    date: String,
+    duration: Option<String>,
+    description: Option<String>,
+    template: Option<String>,
+    channel: Option<String>,
+    role: Option<RoleId>,
+    scope: EventScopes
+}
+
+pub async fn create_event_command(interaction: &CommandInteraction, ctx: &Context, pool: PgPool, announcement_hook: &str) -> Result<()> {
+    let store = Store::new(pool);
+    let guild = interaction.guild_id.ok_or(Error::UnknownInteraction("event create outside guild".to_string()))?;
+    let options = parse_options(&interaction.data.options())?;
+
+    match build_event(&options, interaction, ctx, &store, guild).await {
+        Ok((mut event, channel)) => {
+            interaction.defer_ephemeral(&ctx.http).await?;
+            let message = super::publish_event(ctx, &store, &mut event, guild, channel, announcement_hook).await?;
+            let warnings = super::super::overlap::conflict_warnings(&store, &event, message, guild).await?;
+            interaction.edit_response(&ctx.http, EditInteractionResponse::new()
+                .embed(super::created_embed(channel, &warnings))
+            ).await?;
+        }
+        Err(errors) => {
+            interaction.create_response(&ctx.http, CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .ephemeral(true)
+                    .embed(CreateEmbed::new()
+                        .title("No se ha podido crear el evento")
+                        .description(errors.iter().map(|e| format!("- {e}")).collect::<Vec<String>>().join("\n")))
+            )).await?;
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn create_event_autocomplete(interaction: &CommandInteraction, ctx: &Context, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let choices = if let Some(option) = interaction.data.autocomplete() {
+        match option.name {
+            "template" => store.search_templates(option.value).await?
+                .into_iter()
+                .map(|(name, kind)| AutocompleteChoice::new(format!("{name} ({kind})"), name))
+                .collect(),
+            "channel" => day_channels(interaction.guild_id, ctx).await?
+                .into_iter()
+                .filter(|(_, name)| name.contains(&option.value.to_lowercase()))
+                .take(25)
+                .map(|(id, name)| AutocompleteChoice::new(format!("#{name}"), id.to_string()))
+                .collect(),
+            _ => vec![]
+        }
+    } else { vec![] };
+
+    interaction.create_response(&ctx.http, CreateInteractionResponse::Autocomplete(
+        CreateAutocompleteResponse::new().set_choices(choices)
+    )).await?;
+
+    Ok(())
+}
+
+fn parse_options(options: &Vec<ResolvedOption>) -> Result<CreateOptions> {
+    let options = match options.first().map(|o| &o.value) {
+        Some(ResolvedValue::SubCommand(options)) => options,
+        _ => return Err(Error::UnknownInteraction("event create".to_string()))
+    };
+
+    let mut parsed = CreateOptions {
+        kind: EventKind::Trial,
+        title: String::new(),
+        date: String::new(),
+        duration: None,
+        description: None,
+        template: None,
+        channel: None,
+        role: None,
+        scope: EventScopes::Public
+    };
+
+    for option in options {
+        match (option.name, &option.value) {
+            ("kind", ResolvedValue::String(kind)) => parsed.kind = EventKind::from_partial_id(kind).unwrap_or(EventKind::Trial),
+            ("title", ResolvedValue::String(title)) => parsed.title = title.to_string(),
+            ("date", ResolvedValue::String(date)) => parsed.date = date.to_string(),
+            ("duration", ResolvedValue::String(duration)) => parsed.duration = Some(duration.to_string()),
+            ("description", ResolvedValue::String(description)) => parsed.description = Some(description.to_string()),
+            ("template", ResolvedValue::String(template)) => parsed.template = Some(template.to_string()),
+            ("channel", ResolvedValue::String(channel)) => parsed.channel = Some(channel.to_string()),
+            ("role", ResolvedValue::Role(role)) => parsed.role = Some(role.id),
+            ("scope", ResolvedValue::String(scope)) => parsed.scope = EventScopes::from_partial_id(scope),
+            _ => {}
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Builds the event from the command options, collecting every validation error on the way
+async fn build_event(options: &CreateOptions, interaction: &CommandInteraction, ctx: &Context, store: &Store, guild: GuildId) -> std::result::Result<(Event, ChannelId), Vec<String>> {
+    let mut errors = vec![];
+
+    let duration = match options.duration.as_deref().unwrap_or("2h").parse::<DurationString>() {
+        Ok(duration) => Some(duration),
+        Err(_) => {
+            errors.push(format!("Duracion `{}` no valida, ejemplo: `2h` o `1h30m`", options.duration.as_deref().unwrap_or_default()));
+            None
+        }
+    };
+
+    let template = match &options.template {
+        Some(name) => match store.get_template(name).await {
+            Ok(Some(template)) if template.kind == options.kind => Some(template),
+            Ok(Some(template)) => {
+                errors.push(format!("La plantilla `{name}` es de {} y el evento de {}", template.kind, options.kind));
+                None
+            }
+            Ok(None) => {
+                errors.push(format!("No existe la plantilla `{name}`"));
+                None
+            }
+            Err(why) => {
+                errors.push(why.to_string());
+                None
+            }
+        },
+        None => None
+    };
+
+    let channel = match &options.channel {
+        Some(channel) => match find_day_channel(channel, guild, ctx).await {
+            Some(channel) => Some(channel),
+            None => {
+                errors.push(format!("El canal `{channel}` no es un canal de dia (lunes, martes...)"));
+                None
+            }
+        },
+        None => {
+            errors.push("Falta el canal del evento".to_string());
+            None
+        }
+    };
+
+    let datetime = match parse_date(&options.date, channel.as_ref().map(|(_, name)| name.as_str())) {
+        Ok(datetime) => Some(datetime),
+        Err(why) => {
+            errors.push(why);
+            None
+        }
+    };
+
+    if options.title.trim().is_empty() {
+        errors.push("Falta el titulo".to_string());
+    }
+
+    match (duration, channel, datetime) {
+        (Some(duration), Some((channel, _)), Some(datetime)) if errors.is_empty() => {
+            let mut event = Event::new(options.title.clone(), duration, options.description.clone().unwrap_or_default(), interaction.user.id, options.kind);
+            if let Some(template) = template {
+                event.roles = template.roles;
+                event.rules = template.rules;
+                event.class_quotas = template.class_quotas;
+            }
+            event.datetime = Some(datetime);
+            event.notification_role = options.role;
+            event.scope = options.scope;
+            Ok((event, channel))
+        }
+        _ => Err(errors)
+    }
+}
+
+/// Parses dates like `miercoles 21:30`, the weekday can be omitted and taken from the channel
+fn parse_date(date: &str, channel_name: Option<&str>) -> std::result::Result<chrono::DateTime<chrono::Utc>, String> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"(?P<hour>\d{1,2})(?:[:.h](?P<minute>\d{2}))?").unwrap();
+    }
+
+    let date_day = get_channel_weekday(&date.to_lowercase());
+    let channel_day = channel_name.and_then(get_channel_weekday);
+    let day = match (date_day, channel_day) {
+        (Some(date_day), Some(channel_day)) if date_day != channel_day =>
+            return Err(format!("La fecha es del {date_day} pero el canal es del {channel_day}")),
+        (Some(day), _) | (None, Some(day)) => day,
+        (None, None) => return Err(format!("No se reconoce el dia de la semana en `{date}`"))
+    };
+
+    let (hour, minute) = RE.captures(date)
+        .map(|cap| (
+            cap.name("hour").and_then(|h| h.as_str().parse::<u32>().ok()),
+            cap.name("minute").map(|m| m.as_str().parse::<u32>().ok()).unwrap_or(Some(0))
+        ))
+        .unwrap_or((None, None));
+
+    match (hour, minute) {
+        (Some(hour), Some(minute)) if hour < 24 && minute < 60 => next_event_datetime(&day, hour, minute)
+            .ok_or(format!("Hora `{hour:02}:{minute:02}` no soportada")),
+        _ => Err(format!("No se reconoce la hora en `{date}`, ejemplo: `miercoles 21:30`"))
+    }
+}
+
+async fn find_day_channel(channel: &str, guild: GuildId, ctx: &Context) -> Option<(ChannelId, String)> {
+    let channel = channel.trim_start_matches('#').trim_start_matches("<#").trim_end_matches('>');
+    day_channels(Some(guild), ctx).await.ok()?
+        .into_iter()
+        .find(|(id, name)| id.to_string() == channel || name == channel)
+}
+
+async fn day_channels(guild: Option<GuildId>, ctx: &Context) -> Result<Vec<(ChannelId, String)>> {
+    let Some(guild) = guild else { return Ok(vec![]) };
+    let mut channels: Vec<(ChannelId, String)> = guild.channels(&ctx.http).await?
+        .into_values()
+        .filter(|c| c.kind == ChannelType::Text && get_channel_weekday(&c.name).is_some())
+        .map(|c| (c.id, c.name))
+        .collect();
+    channels.sort_by(|(_, a), (_, b)| a.cmp(b));
+    Ok(channels)
+}
@@ -1,16 +1,12 @@
 use std::time::Duration;
-use base64::Engine;
-use base64::prelude::BASE64_STANDARD;
-use flate2::read::DeflateDecoder;
-use serenity::all::{ButtonStyle, Context, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, Interaction, Message, ModalInteraction};
-use crate::events::{Event, EventRole, PlayersInRole};
+use serenity::all::{ButtonStyle, Context, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateModal, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, Interaction, Message};
+use crate::events::{decode_roles, DraftStep, Event, EventRole};
 use crate::prelude::*;
 use serenity::futures::StreamExt;
-use std::io::prelude::*;
 use crate::prelude::components::long_input;
 
-pub(super) async fn handle_composition(message: &Message, modal: &ModalInteraction, ctx: &Context, event: &mut Event) -> Result<Interaction> {
-    modal.create_response(&ctx.http, create_event_default_composition(&event)).await?;
+pub(super) async fn handle_composition(message: &Message, interaction: &Interaction, ctx: &Context, event: &mut Event) -> Result<Interaction> {
+    super::update_wizard(interaction, ctx, create_event_default_composition(event)).await?;
     if let Some(interaction) = message.await_component_interaction(&ctx.shard).await {
         // Select modify composition
         if interaction.data.custom_id.contains("modify") {
@@ -45,14 +41,8 @@ pub(super) async fn handle_composition(message: &Message, modal: &ModalInteracti
         if interaction.data.custom_id.contains("import") {
             interaction.create_response(&ctx, create_event_import_composition()).await?;
             if let Some(interaction) = message.await_modal_interaction(&ctx).await {
-                let code = get_input_value(&interaction.data.components, 0).unwrap();
-                let decoded = BASE64_STANDARD.decode(code.as_bytes())?;
-                let mut decoder = DeflateDecoder::new(decoded.as_slice());
-                let mut json_roles = String::new();
-                decoder.read_to_string(&mut json_roles)?;
-                let roles: Vec<PlayersInRole> = serde_json::from_str(&json_roles)?;
-
-                event.roles = roles;
+                let code = get_input_value(&interaction.data.components, 0).unwrap_or_default();
+                event.roles = decode_roles(&code)?;
                 return Ok(Interaction::Modal(interaction))
             }
         }
@@ -76,6 +66,7 @@ fn create_event_default_composition(event: &Event) -> CreateInteractionResponse
             .button(CreateButton::new("create_event_composition_import")
                 .label("Importar desde codigo")
                 .style(ButtonStyle::Secondary))
+            .select_menu(super::step_menu(DraftStep::Composition))
     )
 }
 
@@ -122,7 +113,8 @@ fn composition_buttons(event: &Event) -> Vec<CreateActionRow> {
             CreateButton::new("create_event_composition_modify_confirm")
                 .label("Continuar")
                 .style(ButtonStyle::Secondary)
-        ])
+        ]),
+        CreateActionRow::SelectMenu(super::step_menu(DraftStep::Composition))
     ]
 }
 
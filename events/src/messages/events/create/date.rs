@@ -1,13 +1,17 @@
 use chrono::{Datelike, DateTime, Timelike, Utc, Weekday};
-use serenity::all::{ChannelId, ChannelType, ComponentInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, Message};
-use crate::events::Event;
+use serenity::all::{ChannelId, ChannelType, ComponentInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, Interaction, Message};
+use crate::events::{DraftStep, Event};
 use crate::prelude::*;
 
-pub(super) async fn select_date(message: &Message, interaction: &ComponentInteraction, ctx: &Context, event: &mut Event) -> Result<(ComponentInteraction, ChannelId)> {
+pub(super) async fn select_date(message: &Message, interaction: &Interaction, ctx: &Context, event: &mut Event) -> Result<(ComponentInteraction, Option<ChannelId>)> {
 
     // Select day
-    interaction.create_response(&ctx.http, select_day_channel()).await?;
+    super::update_wizard(interaction, ctx, select_day_channel()).await?;
     if let Some(interaction) = message.await_component_interaction(&ctx.shard).await {
+        if super::is_step_jump(&interaction) {
+            return Ok((interaction, None));
+        }
+
         if let Some(channel) = get_selected_channel(&interaction) {
             let name = channel.name(&ctx.http).await?;
             let day = get_channel_weekday(&name).ok_or(Error::NotDay(name.to_string()))?;
@@ -15,16 +19,17 @@ pub(super) async fn select_date(message: &Message, interaction: &ComponentIntera
             // Select time
             interaction.create_response(&ctx.http, select_time(&day)).await?;
             if let Some(interaction) = message.await_component_interaction(&ctx.shard).await {
+                if super::is_step_jump(&interaction) {
+                    return Ok((interaction, None));
+                }
+
                 if let Some(time) = get_selected_option(&interaction) {
-                    let hour = (&time[..2]).parse::<u32>()? - 2; // hack for spanish timezone
-                    let minute = (&time[3..]).parse::<u32>()?;
+                    let hour = time[..2].parse::<u32>()?;
+                    let minute = time[3..].parse::<u32>()?;
 
-                    event.datetime = calculate_next_date(&day, hour, minute)
-                        .with_hour(hour)
-                        .map(|dt| dt.with_minute(minute))
-                        .flatten();
+                    event.datetime = next_event_datetime(&day, hour, minute);
 
-                    return Ok((interaction, channel));
+                    return Ok((interaction, Some(channel)));
                 }
             }
         }
@@ -43,6 +48,7 @@ fn select_day_channel() -> CreateInteractionResponse {
         CreateInteractionResponseMessage::new()
             .select_menu(CreateSelectMenu::new("create_event_day_select", channel_selector)
                 .placeholder("Canales del evento"))
+            .select_menu(super::step_menu(DraftStep::Date))
     )
 }
 
@@ -65,10 +71,18 @@ fn select_time(day: &str) -> CreateInteractionResponse {
         CreateInteractionResponseMessage::new()
             .select_menu(CreateSelectMenu::new("create_event_time_select", time_options)
                 .placeholder(format!("Selecciona hora para el {}", day)))
+            .select_menu(super::step_menu(DraftStep::Date))
     )
 }
 
-fn get_channel_weekday(channel_name: &str) -> Option<String> {
+pub(super) fn next_event_datetime(day: &str, hour: u32, minute: u32) -> Option<DateTime<Utc>> {
+    let hour = hour.checked_sub(2)?; // hack for spanish timezone
+    calculate_next_date(day, hour, minute)
+        .with_hour(hour)
+        .and_then(|dt| dt.with_minute(minute))
+}
+
+pub(super) fn get_channel_weekday(channel_name: &str) -> Option<String> {
     let weekdays = vec!["lunes", "martes", "miercoles", "jueves", "viernes", "sabado", "domingo"];
     for weekday in weekdays {
         let channel_no_accents = unidecode::unidecode(channel_name);
@@ -0,0 +1,44 @@
+use serenity::all::{ComponentInteraction, Context, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, GuildId, Interaction, Message};
+use crate::events::{available_images, DraftStep, Event, EventImage};
+use crate::prelude::*;
+
+const AUTOMATIC: &str = "auto";
+
+pub(super) async fn select_image(message: &Message, interaction: &Interaction, ctx: &Context, store: &Store, guild: GuildId, event: &mut Event) -> Result<ComponentInteraction> {
+    let mut images = available_images(store, guild, event.kind).await?;
+    // Images matching the title go first
+    images.sort_by_key(|i| !i.matches(&event.title));
+
+    super::update_wizard(interaction, ctx, interaction_response(event, &images)).await?;
+
+    if let Some(interaction) = message.await_component_interaction(&ctx.shard).await {
+        if interaction.data.custom_id == "create_event_image_select" {
+            event.image = get_selected_option(&interaction).filter(|i| i != AUTOMATIC);
+        }
+        Ok(interaction)
+    } else {
+        Err(Error::Timeout)
+    }
+}
+
+fn interaction_response(event: &Event, images: &[EventImage]) -> CreateInteractionResponse {
+    let options = std::iter::once(CreateSelectMenuOption::new("Automatica", AUTOMATIC)
+            .description("Segun el titulo, o sin repetir las ultimas en PvP")
+            .default_selection(event.image.is_none()))
+        .chain(images.iter().take(24).map(|i| {
+            let reference = i.reference();
+            CreateSelectMenuOption::new(i.label(), &reference)
+                .description(if i.id.is_some() { "Biblioteca del servidor" } else { "Incluida en el bot" })
+                .default_selection(event.image.as_ref() == Some(&reference))
+        }))
+        .collect();
+
+    CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+            .embed(event.embed_preview())
+            .select_menu(CreateSelectMenu::new("create_event_image_select", CreateSelectMenuKind::String { options })
+                .placeholder("Imagen del evento"))
+            .button(CreateButton::new("create_event_image_confirm").label("Continuar"))
+            .select_menu(super::step_menu(DraftStep::Image))
+    )
+}
@@ -1,38 +1,86 @@
 use anyhow::Context as AnyhowContext;
 use duration_string::DurationString;
 use serenity::all::{ComponentInteraction, Context, CreateInteractionResponse, CreateModal, Message, ModalInteraction};
-use crate::events::{Event, EventKind};
+use crate::events::{Event, EventKind, Trial, TrialDifficulty};
 use crate::prelude::*;
 
-pub(super) async fn request_info_modal(message: &Message, interaction: &ComponentInteraction, ctx: &Context, kind: EventKind) -> Result<(ModalInteraction, Event)> {
-    interaction.create_response(&ctx.http, create_event_info_modal()).await?;
+pub(super) async fn request_info_modal(message: &Message, interaction: &ComponentInteraction, ctx: &Context, kind: EventKind, trial: Option<(&Trial, TrialDifficulty)>) -> Result<(ModalInteraction, Event)> {
+    interaction.create_response(&ctx.http, create_event_info_modal(trial)).await?;
     if let Some(modal) = message.await_modal_interaction(&ctx.shard).await {
-        let (title, duration, description) = parse_info_modal(&modal)?;
-        let event = Event::new(title, duration, description, interaction.user.id, kind);
+        let (title, duration, description) = parse_info_modal(&modal, trial)?;
+        let mut event = Event::new(title, duration, description, interaction.user.id, kind);
+        if let Some((trial, _)) = trial {
+            trial.apply(&mut event);
+        }
         Ok((modal, event))
     } else {
         Err(Error::Timeout)
     }
 }
 
-fn create_event_info_modal() -> CreateInteractionResponse {
+pub(super) async fn edit_info_modal(message: &Message, interaction: &ComponentInteraction, ctx: &Context, event: &mut Event) -> Result<ModalInteraction> {
+    interaction.create_response(&ctx.http, edit_event_info_modal(event)).await?;
+    if let Some(modal) = message.await_modal_interaction(&ctx.shard).await {
+        if let Some(title) = non_empty_input(&modal, 0) {
+            event.title = title;
+        }
+        if let Some(duration) = non_empty_input(&modal, 1) {
+            event.duration = duration.parse::<DurationString>().map_err(Error::DurationParse)?;
+        }
+        if let Some(description) = non_empty_input(&modal, 2) {
+            event.description = description;
+        }
+        Ok(modal)
+    } else {
+        Err(Error::Timeout)
+    }
+}
+
+/// With a trial from the catalog the title and duration can be left empty to take its defaults
+fn create_event_info_modal(trial: Option<(&Trial, TrialDifficulty)>) -> CreateInteractionResponse {
+    let (title, duration) = match trial {
+        Some((trial, difficulty)) => (trial.title(difficulty), trial.duration(difficulty).to_string()),
+        None => ("Trial nivel avanzado - vRG".to_string(), "2h".to_string())
+    };
     CreateInteractionResponse::Modal(
         CreateModal::new("create_event_info", "Informacion del Evento")
             .components(vec![
-                components::short_input("Titulo", "event_title", "Trial nivel avanzado - vRG", true),
-                components::short_input("Duracion", "event_duration", "2h", true),
+                components::short_input("Titulo", "event_title", &title, trial.is_none()),
+                components::short_input("Duracion", "event_duration", &duration, trial.is_none()),
                 components::long_input("Descripción", "event_description", "Se empezara a montar 10 minutos antes\nbla bla bla", true),
             ])
     )
 }
 
-fn parse_info_modal(modal: &ModalInteraction) -> Result<(String, DurationString, String)> {
-    let title = get_input_value(&modal.data.components, 0).context("title")?;
-    let duration = get_input_value(&modal.data.components, 1)
-        .context("duration")?
-        .parse::<DurationString>().map_err(|e| Error::DurationParse(e))?;
+fn edit_event_info_modal(event: &Event) -> CreateInteractionResponse {
+    CreateInteractionResponse::Modal(
+        CreateModal::new("create_event_info", "Informacion del Evento")
+            .components(vec![
+                components::short_input("Titulo", "event_title", &event.title, false),
+                components::short_input("Duracion", "event_duration", &event.duration.to_string(), false),
+                components::long_input("Descripción", "event_description", &event.description, false),
+            ])
+    )
+}
+
+fn parse_info_modal(modal: &ModalInteraction, trial: Option<(&Trial, TrialDifficulty)>) -> Result<(String, DurationString, String)> {
+    let (title, duration) = match (trial, non_empty_input(modal, 0), non_empty_input(modal, 1)) {
+        (Some((trial, difficulty)), title, duration) => (
+            title.unwrap_or(trial.title(difficulty)),
+            duration.map(|d| d.parse::<DurationString>()).transpose().map_err(Error::DurationParse)?
+                .unwrap_or(trial.duration(difficulty))
+        ),
+        (None, title, duration) => (
+            title.context("title")?,
+            duration.context("duration")?.parse::<DurationString>().map_err(Error::DurationParse)?
+        )
+    };
     let description = get_input_value(&modal.data.components, 2)
         .context("description")?;
 
     Ok((title, duration, description))
-}
\ No newline at end of file
+}
+
+fn non_empty_input(modal: &ModalInteraction, idx: usize) -> Option<String> {
+    get_input_value(&modal.data.components, idx).filter(|value| !value.is_empty())
+}
@@ -0,0 +1,65 @@
+use serenity::all::{ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, Message};
+use strum::IntoEnumIterator;
+use crate::events::{PvpAlliance, PvpDetails, PvpStyle, CAMPAIGNS, MIN_CP_CHOICES};
+use crate::prelude::*;
+
+const NONE: &str = "none";
+
+/// Campaign, alliance, style and minimum CP of a new PvP event.
+/// Returns the interaction that has to answer with the info modal
+pub(super) async fn select_pvp(message: &Message, interaction: ComponentInteraction, ctx: &Context) -> Result<(ComponentInteraction, PvpDetails)> {
+    let mut details = PvpDetails::default();
+    let mut interaction = interaction;
+
+    loop {
+        interaction.create_response(&ctx.http, pvp_message(&details)).await?;
+        interaction = message.await_component_interaction(&ctx.shard).await.ok_or(Error::Timeout)?;
+        let selected = get_selected_option(&interaction).filter(|o| o != NONE);
+        match interaction.data.custom_id.as_str() {
+            "create_event_pvp_campaign" => details.campaign = selected,
+            "create_event_pvp_alliance" => details.alliance = selected.and_then(|id| PvpAlliance::from_id(&id)).unwrap_or_default(),
+            "create_event_pvp_style" => details.style = selected.and_then(|id| PvpStyle::from_id(&id)),
+            "create_event_pvp_cp" => details.min_cp = selected.and_then(|cp| cp.parse().ok()),
+            _ => return Ok((interaction, details))
+        }
+    }
+}
+
+fn pvp_message(details: &PvpDetails) -> CreateInteractionResponse {
+    let campaigns = std::iter::once(CreateSelectMenuOption::new("Sin campaña fija", NONE))
+        .chain(CAMPAIGNS.iter().map(|c| CreateSelectMenuOption::new(*c, *c)
+            .default_selection(details.campaign.as_deref() == Some(*c))))
+        .collect();
+    let alliances = PvpAlliance::iter()
+        .map(|a| CreateSelectMenuOption::new(a.label_es(), a.to_id()).default_selection(a == details.alliance))
+        .collect();
+    let styles = std::iter::once(CreateSelectMenuOption::new("Sin estilo concreto", NONE))
+        .chain(PvpStyle::iter().map(|s| CreateSelectMenuOption::new(s.label_es(), s.to_id())
+            .default_selection(details.style == Some(s))))
+        .collect();
+    let cps = std::iter::once(CreateSelectMenuOption::new("Sin CP minimo", NONE))
+        .chain(MIN_CP_CHOICES.iter().map(|cp| CreateSelectMenuOption::new(format!("CP {cp}"), cp.to_string())
+            .default_selection(details.min_cp == Some(*cp))))
+        .collect();
+
+    CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+        .embed(CreateEmbed::new()
+            .title("Nuevo PvP")
+            .description(details.summary_es())
+            .thumbnail(details.alliance.thumbnail()))
+        .components(vec![
+            CreateActionRow::SelectMenu(CreateSelectMenu::new("create_event_pvp_campaign", CreateSelectMenuKind::String { options: campaigns })
+                .placeholder("Campaña")),
+            CreateActionRow::SelectMenu(CreateSelectMenu::new("create_event_pvp_alliance", CreateSelectMenuKind::String { options: alliances })
+                .placeholder("Alianza")),
+            CreateActionRow::SelectMenu(CreateSelectMenu::new("create_event_pvp_style", CreateSelectMenuKind::String { options: styles })
+                .placeholder("Estilo")),
+            CreateActionRow::SelectMenu(CreateSelectMenu::new("create_event_pvp_cp", CreateSelectMenuKind::String { options: cps })
+                .placeholder("CP minimo")),
+            CreateActionRow::Buttons(vec![
+                CreateButton::new("create_event_pvp_confirm")
+                    .label("Continuar")
+                    .style(ButtonStyle::Success)
+            ])
+        ]))
+}
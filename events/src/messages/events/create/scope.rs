@@ -1,24 +1,24 @@
-use std::io::Write;
 use std::time::Duration;
-use base64::Engine;
-use flate2::Compression;
-use flate2::write::DeflateEncoder;
-use serenity::all::{ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, Message};
+use serenity::all::{ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, Interaction, Message};
 use serenity::builder::CreateEmbed;
-use crate::events::{Event, EventRole, EventScopes, Player};
+use crate::events::{encode_roles, DraftStep, Event, EventRole, EventScopes, Player};
 use crate::prelude::*;
 use serenity::futures::StreamExt;
 
-pub(super) async fn handle_scope(message: &Message, interaction: &ComponentInteraction, ctx: &Context, event: &mut Event) -> Result<ComponentInteraction> {
+pub(super) async fn handle_scope(message: &Message, interaction: &Interaction, ctx: &Context, event: &mut Event) -> Result<ComponentInteraction> {
     // Select scope
-    interaction.create_response(&ctx.http, create_event_scope_select(&event)).await?;
+    super::update_wizard(interaction, ctx, create_event_scope_select(event)).await?;
     if let Some(interaction) = message.await_component_interaction(&ctx.shard).await {
+        if super::is_step_jump(&interaction) {
+            return Ok(interaction);
+        }
+
         event.scope = EventScopes::from_partial_id(&interaction.data.custom_id);
 
         if event.scope != EventScopes::Public {
 
             // Select role to add players
-            interaction.create_response(&ctx.http, create_event_scope_role(event)).await?;
+            interaction.create_response(&ctx.http, create_event_scope_role(event)?).await?;
 
             let mut role_add_players = message
                 .await_component_interaction(&ctx.shard)
@@ -34,14 +34,14 @@ pub(super) async fn handle_scope(message: &Message, interaction: &ComponentInter
                     } else {
 
                         // Get nicknames and signup players
-                        let guild = interaction.guild_id.clone().unwrap();
+                        let guild = interaction.guild_id.ok_or(Error::UnknownInteraction("event scope outside guild".to_string()))?;
                         event.clear(role);
                         for user in get_selected_users(&interaction) {
                             let member = guild.member(&ctx.http, user).await?;
                             event.add_player(role, Player::new(user, member.display_name().to_string()));
                         }
 
-                        interaction.create_response(&ctx.http, create_event_scope_role(event)).await?;
+                        interaction.create_response(&ctx.http, create_event_scope_role(event)?).await?;
                     }
                 } else {
                     return Ok(interaction);
@@ -68,25 +68,22 @@ fn create_event_scope_select(event: &Event) -> CreateInteractionResponse {
             .button(CreateButton::new("create_event_scope_private")
                 .label("Cerrado")
                 .style(ButtonStyle::Danger))
+            .select_menu(super::step_menu(DraftStep::Scope))
     )
 }
 
-fn create_event_scope_role(event: &Event) -> CreateInteractionResponse {
-    let signups = serde_json::to_string(&event.roles).unwrap();
-    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
-    encoder.write_all(signups.as_bytes()).unwrap();
-    let compressed = encoder.finish().unwrap();
-    let encoded = base64::prelude::BASE64_STANDARD.encode(compressed);
+fn create_event_scope_role(event: &Event) -> Result<CreateInteractionResponse> {
+    let encoded = encode_roles(&event.roles)?;
 
-    CreateInteractionResponse::UpdateMessage(
+    Ok(CreateInteractionResponse::UpdateMessage(
         CreateInteractionResponseMessage::new()
             .embed(event.embed_preview())
             .add_embed(CreateEmbed::new()
                 .title("Codigo de plantilla para importar en futuros eventos")
                 .description(encoded)
             )
-            .components(vec![scope_role_buttons(event), scope_reserve_button(), scope_confirm()])
-    )
+            .components(vec![scope_role_buttons(event), scope_reserve_button(), scope_confirm(), scope_step_menu()])
+    ))
 }
 
 fn create_event_scope_role_select(role: EventRole, event: &Event) -> CreateInteractionResponse {
@@ -101,7 +98,8 @@ fn create_event_scope_role_select(role: EventRole, event: &Event) -> CreateInter
                 ),
                 scope_role_buttons(event),
                 scope_reserve_button(),
-                scope_confirm()
+                scope_confirm(),
+                scope_step_menu()
             ])
     )
 }
@@ -128,4 +126,7 @@ fn scope_confirm() -> CreateActionRow {
             .label("Continuar")
             .style(ButtonStyle::Secondary)
     ])
+}
+fn scope_step_menu() -> CreateActionRow {
+    CreateActionRow::SelectMenu(super::step_menu(DraftStep::Scope))
 }
\ No newline at end of file
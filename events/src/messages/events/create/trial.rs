@@ -0,0 +1,64 @@
+use serenity::all::{ButtonStyle, ComponentInteraction, Context, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, Message};
+use strum::IntoEnumIterator;
+use crate::events::{Trial, TrialDifficulty, TRIALS};
+use crate::prelude::*;
+
+const OTHER: &str = "other";
+
+/// Trial and difficulty from the catalog, `None` when the leader goes on with a custom trial.
+/// Returns the interaction that has to answer with the info modal
+pub(super) async fn select_trial(message: &Message, interaction: ComponentInteraction, ctx: &Context) -> Result<(ComponentInteraction, Option<(&'static Trial, TrialDifficulty)>)> {
+    let mut trial = None;
+    let mut difficulty = TrialDifficulty::Veteran;
+    let mut interaction = interaction;
+
+    loop {
+        interaction.create_response(&ctx.http, trial_message(trial, difficulty)).await?;
+        interaction = message.await_component_interaction(&ctx.shard).await.ok_or(Error::Timeout)?;
+        match interaction.data.custom_id.as_str() {
+            "create_event_trial_select" => trial = get_selected_option(&interaction).and_then(|key| Trial::find(&key)),
+            "create_event_difficulty_select" => difficulty = get_selected_option(&interaction)
+                .and_then(|id| TrialDifficulty::from_id(&id))
+                .unwrap_or(difficulty),
+            "create_event_trial_confirm" => return Ok((interaction, trial.filter(|t| t.supports(difficulty)).map(|t| (t, difficulty)))),
+            _ => return Ok((interaction, None))
+        }
+    }
+}
+
+fn trial_message(trial: Option<&Trial>, difficulty: TrialDifficulty) -> CreateInteractionResponse {
+    let trials = TRIALS.iter()
+        .map(|t| CreateSelectMenuOption::new(t.name_es, t.key)
+            .description(format!("{} ({})", t.name_en, t.key.to_uppercase()))
+            .default_selection(trial.is_some_and(|selected| selected.key == t.key)))
+        .chain(std::iter::once(CreateSelectMenuOption::new("Otro", OTHER)
+            .description("Titulo, duracion y composicion a mano")))
+        .collect();
+    let difficulties = TrialDifficulty::iter()
+        .filter(|d| trial.is_none_or(|t| t.supports(*d)))
+        .map(|d| CreateSelectMenuOption::new(d.label_es(), d.to_id()).default_selection(d == difficulty))
+        .collect();
+
+    let description = match trial {
+        Some(trial) => {
+            let (tanks, healers, dd) = trial.composition;
+            format!("**{}**\nDuracion: {}\nComposicion: {tanks} tanques, {healers} healers, {dd} dd",
+                    trial.title(difficulty), trial.duration(difficulty))
+        }
+        None => "Elige trial y dificultad para rellenar titulo, imagen y composicion, o sigue para un trial a medida".to_string()
+    };
+
+    CreateInteractionResponse::UpdateMessage(CreateInteractionResponseMessage::new()
+        .embed(CreateEmbed::new().title("Nuevo trial").description(description))
+        .components(vec![
+            CreateActionRow::SelectMenu(CreateSelectMenu::new("create_event_trial_select", CreateSelectMenuKind::String { options: trials })
+                .placeholder("Trial")),
+            CreateActionRow::SelectMenu(CreateSelectMenu::new("create_event_difficulty_select", CreateSelectMenuKind::String { options: difficulties })
+                .placeholder("Dificultad")),
+            CreateActionRow::Buttons(vec![
+                CreateButton::new("create_event_trial_confirm")
+                    .label("Continuar")
+                    .style(ButtonStyle::Success)
+            ])
+        ]))
+}
@@ -1,21 +1,26 @@
+use std::collections::HashMap;
 use std::time::Duration;
-use serenity::all::{ButtonStyle, ChannelId, CommandInteraction, Context, CreateInteractionResponse, CreateInteractionResponseMessage, GetMessages, Mention, Message, MessageId, MessageType, PartialChannel, ScheduledEventStatus};
+use serenity::all::{ButtonStyle, ChannelId, CommandInteraction, CreateInteractionResponse, CreateInteractionResponseMessage, Mention, MessageId, PartialChannel, ScheduledEventStatus};
 use serenity::builder::CreateButton;
 use sqlx::PgPool;
 use tracing::{instrument};
+use crate::discord::Discord;
+use crate::events::Event;
 use crate::prelude::*;
+use crate::tasks;
 
-#[instrument]
-pub async fn delete_event(interaction: &CommandInteraction, ctx: &Context, pool: PgPool) -> Result<()> {
+#[instrument(skip(discord))]
+pub async fn delete_event(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
     let store = Store::new(pool);
-    let message = interaction.data.resolved.messages.values().next().unwrap();
+    let message = interaction.data.resolved.messages.values().next()
+        .ok_or(Error::UnknownInteraction("delete event without message".to_string()))?;
     if let Ok(event) = store.get_event(message.id).await {
         if let Some(id) = event.scheduled_event {
-            let guild = interaction.guild_id.unwrap();
+            let guild = interaction.guild_id.ok_or(Error::UnknownInteraction("delete event outside guild".to_string()))?;
 
             // Scheduled event exists
-            if guild.scheduled_event(&ctx.http, id, false).await.is_ok_and(|se| se.status == ScheduledEventStatus::Scheduled) {
-                interaction.create_response(&ctx.http, CreateInteractionResponse::Message(
+            if discord.scheduled_event(guild, id).await.is_ok_and(|se| se.status == ScheduledEventStatus::Scheduled) {
+                discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
                     CreateInteractionResponseMessage::new()
                         .ephemeral(true)
                         .content(format!(r#"
@@ -27,50 +32,54 @@ Estas bien {}?? Quieres hablar??
                             .label("Si, borralo, puta vida").style(ButtonStyle::Danger))
                 )).await?;
 
-                let response = interaction.get_response(&ctx.http).await?;
-                if let Some(interaction) = response.await_component_interaction(&ctx.shard).timeout(Duration::from_secs(60)).await {
-                    guild.delete_scheduled_event(&ctx.http, id).await?;
-                    remove_event(&store, ctx, interaction.channel_id, message.id).await?;
+                let response = discord.get_response(&interaction.token).await?;
+                if let Some(interaction) = discord.await_component(response.id, Some(Duration::from_secs(60))).await {
+                    discord.delete_scheduled_event(guild, id).await?;
+                    remove_event(&store, discord, &event, message.channel_id, message.id).await?;
 
-                    interaction.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(purged().embeds(vec![]))).await?;
+                    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(purged().embeds(vec![]))).await?;
                 }
             } else {
-                remove_event(&store, ctx, interaction.channel_id, message.id).await?;
-                interaction.create_response(&ctx.http, CreateInteractionResponse::Message(purged())).await?;
+                remove_event(&store, discord, &event, message.channel_id, message.id).await?;
+                discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(purged())).await?;
             }
         } else {
-            remove_event(&store, ctx, interaction.channel_id, message.id).await?;
-            interaction.create_response(&ctx.http, CreateInteractionResponse::Message(purged())).await?;
+            remove_event(&store, discord, &event, message.channel_id, message.id).await?;
+            discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(purged())).await?;
         }
-    } else if message.author.id.get() == 1148032756899643412 && is_event_channel(interaction.channel.clone().unwrap()) {
-        purge_channel(ctx, interaction.channel_id).await?;
+    } else if message.author.id.get() == 1148032756899643412 && interaction.channel.clone().is_some_and(is_event_channel) {
+        // Leftover bot message, other events may share the channel so only this one goes
+        discord.delete_messages(message.channel_id, vec![message.id]).await?;
+        discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(purged())).await?;
     } else {
-        interaction.create_response(&ctx.http, super::not_an_event_response()).await?;
+        discord.create_response(interaction.id, &interaction.token, super::not_an_event_response()).await?;
     }
 
     Ok(())
 }
 
 fn is_event_channel(channel: PartialChannel) -> bool {
-    let name = channel.name.unwrap();
+    let name = channel.name.unwrap_or_default();
 
     name.ends_with("-a") || name.ends_with("-b") || name.ends_with("-c")
 }
 
-async fn remove_event(store: &Store, ctx: &Context, channel: ChannelId, message: MessageId) -> Result<()> {
-    purge_channel(ctx, channel).await?;
-    store.remove_event(message).await?;
-
-    Ok(())
-}
-
-async fn purge_channel(ctx: &Context, channel: ChannelId) -> Result<()> {
-    let channel_messages = channel.messages(&ctx.http, GetMessages::new()).await?
-        .into_iter()
-        .filter(|msg| !msg.pinned && msg.kind != MessageType::PinsAdd)
-        .collect::<Vec<Message>>();
+/// Deletes the event message and the messages the bot sent for it, leaving the other events of the channel alone
+async fn remove_event(store: &Store, discord: &impl Discord, event: &Event, channel: ChannelId, message: MessageId) -> Result<()> {
+    let mut by_channel: HashMap<ChannelId, Vec<MessageId>> = HashMap::new();
+    by_channel.entry(channel).or_default().push(message);
+    for (channel, id) in store.get_tracked_messages(message).await? {
+        by_channel.entry(channel).or_default().push(id);
+    }
 
-    channel.delete_messages(&ctx.http, channel_messages).await?;
+    for (channel, messages) in by_channel {
+        discord.delete_messages(channel, messages).await?;
+    }
+    if let Some(thread) = event.thread {
+        discord.archive_thread(thread).await?;
+    }
+    tasks::unset_reminder(&message);
+    store.remove_event(message).await?;
 
     Ok(())
 }
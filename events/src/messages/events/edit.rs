@@ -1,54 +1,57 @@
 mod roles;
 mod info;
+mod groups;
 
 use std::time::Duration;
-use serenity::all::{ButtonStyle, CommandInteraction, Context, CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, EditMessage, EmojiId, ReactionType};
-use crate::events::{Event, EventRole};
+use serenity::all::{ButtonStyle, CommandInteraction, CreateActionRow, CreateButton, CreateInteractionResponse, CreateInteractionResponseMessage, EditMessage, EmojiId, ReactionType};
+use crate::discord::Discord;
+use crate::events::{Event, EventKind, EventRole};
 use crate::prelude::*;
-use serenity::futures::StreamExt;
 use sqlx::PgPool;
 
-pub async fn edit_event(interaction: &CommandInteraction, ctx: &Context, pool: PgPool) -> Result<()> {
+pub async fn edit_event(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
     let store = Store::new(pool);
-    let mut message = interaction.data.resolved.messages.values().next().unwrap().clone();
+    let message = interaction.data.resolved.messages.values().next().cloned()
+        .ok_or(Error::UnknownInteraction("edit event without message".to_string()))?;
 
     if let Ok(mut event) = store.get_event(message.id).await {
-        interaction.create_response(&ctx.http, CreateInteractionResponse::Message(edit_event_message(&event))).await?;
-        let preview_message = interaction.get_response(&ctx.http).await?;
+        discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(edit_event_message(&event))).await?;
+        let preview_message = discord.get_response(&interaction.token).await?;
 
-        let mut modification = preview_message
-            .await_component_interaction(&ctx.shard)
-            .timeout(Duration::from_secs(60 * 5))
-            .stream();
-
-        while let Some(interaction) = modification.next().await {
+        while let Some(interaction) = discord.await_component(preview_message.id, Some(Duration::from_secs(60 * 5))).await {
             if let Some(role) = EventRole::from_partial_id(&interaction.data.custom_id) {
-                if let Ok(interaction) = roles::edit_role(&interaction, ctx, &store, role, message.id).await {
-                    interaction.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(edit_event_message(&event))).await?;
+                if let Ok(interaction) = roles::edit_role(&interaction, discord, &store, role, message.id).await {
+                    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(edit_event_message(&event))).await?;
                 }
             }
 
             if interaction.data.custom_id == "edit_leader" {
                 store.update_leader(message.id, interaction.user.id).await?;
-                interaction.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(edit_event_message(&event))).await?;
+                discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(edit_event_message(&event))).await?;
             }
 
             if interaction.data.custom_id == "edit_datetime" {
-                if let Ok(modal) =info::edit_datetime(&interaction, ctx, &store, &event, message.id).await {
-                    modal.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(edit_event_message(&event))).await?;
+                if let Ok(modal) =info::edit_datetime(&interaction, discord, &store, &event, message.id).await {
+                    discord.create_response(modal.id, &modal.token, CreateInteractionResponse::UpdateMessage(edit_event_message(&event))).await?;
+                }
+            }
+
+            if interaction.data.custom_id == "edit_groups" {
+                if let Ok(interaction) = groups::edit_groups(&interaction, discord, &store, message.id, message.channel_id).await {
+                    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(edit_event_message(&event))).await?;
                 }
             }
 
             if interaction.data.custom_id == "edit_info" {
-                let modal = info::edit_info(&interaction, ctx, &store, &event, message.id).await?;
-                modal.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(edit_event_message(&event))).await?;
+                let modal = info::edit_info(&interaction, discord, &store, &event, message.id).await?;
+                discord.create_response(modal.id, &modal.token, CreateInteractionResponse::UpdateMessage(edit_event_message(&event))).await?;
             }
 
             event = store.get_event(message.id).await?;
-            message.edit(&ctx.http, EditMessage::new().embed(event.embed())).await?;
+            discord.edit_message(message.channel_id, message.id, EditMessage::new().embeds(event.embeds())).await?;
         }
     } else {
-        interaction.create_response(&ctx.http, super::not_an_event_response()).await?;
+        discord.create_response(interaction.id, &interaction.token, super::not_an_event_response()).await?;
     }
 
     Ok(())
@@ -67,7 +70,10 @@ fn edit_event_message(event: &Event) -> CreateInteractionResponseMessage {
                 CreateButton::new("edit_leader").label("Robar evento").emoji(ReactionType::Custom { animated: false, id: EmojiId::new(1138123617482322031), name: Some("potion".to_string()) }),
                 CreateButton::new("edit_datetime").label("Cambiar Fecha y Hora").emoji(ReactionType::Unicode("📅".to_string())),
                 CreateButton::new("edit_info").label("Cambiar info (titulo, descripcion...)").emoji(ReactionType::Unicode("ℹ️".to_string()))
-            ])
+            ]
+                .into_iter()
+                .chain((event.kind == EventKind::PvP).then(|| CreateButton::new("edit_groups").label("Grupos").emoji(ReactionType::Unicode("👥".to_string()))))
+                .collect())
         ])
 }
 
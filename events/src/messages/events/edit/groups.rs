@@ -0,0 +1,67 @@
+use std::time::Duration;
+use serenity::all::{ButtonStyle, ChannelId, ComponentInteraction, CreateActionRow, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditMessage, MessageId};
+use crate::discord::Discord;
+use crate::events::{Event, MAX_GROUP_SIZE};
+use crate::prelude::*;
+
+/// Group builder panel, returns the interaction that closed it so the caller can go back to its menu
+pub(super) async fn edit_groups(interaction: &ComponentInteraction, discord: &impl Discord, store: &Store, msg_id: MessageId, channel: ChannelId) -> Result<ComponentInteraction> {
+    let mut event = store.get_event(msg_id).await?;
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(groups_message(&event))).await?;
+
+    while let Some(interaction) = discord.await_component(interaction.message.id, Some(Duration::from_secs(60 * 3))).await {
+        match interaction.data.custom_id.as_str() {
+            "edit_groups_done" => return Ok(interaction),
+            "edit_groups_size" => {
+                let size = get_selected_option(&interaction).and_then(|s| s.parse::<usize>().ok());
+                store.update_groups(msg_id, size, event.group_settings.seed).await?;
+            }
+            "edit_groups_shuffle" => {
+                store.update_groups(msg_id, event.group_settings.size, rand::random::<u32>() as u64 + 1).await?;
+            }
+            "edit_groups_lock" => {
+                let groups = event.groups();
+                for user in get_selected_users(&interaction) {
+                    if event.group_settings.locked_group(user).is_some() {
+                        store.unlock_player(msg_id, user).await?;
+                    } else if let Some(group) = groups.iter().position(|g| g.iter().any(|(_, p)| p.id == user)) {
+                        store.lock_player(msg_id, user, group).await?;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        event = store.get_event(msg_id).await?;
+        discord.edit_message(channel, msg_id, EditMessage::new().embeds(event.embeds())).await?;
+        discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(groups_message(&event))).await?;
+    }
+
+    Err(Error::Timeout)
+}
+
+fn groups_message(event: &Event) -> CreateInteractionResponseMessage {
+    let sizes = CreateSelectMenuKind::String {
+        options: std::iter::once(CreateSelectMenuOption::new("Sin grupos", "none")
+            .default_selection(event.group_settings.size.is_none()))
+            .chain((2..=MAX_GROUP_SIZE).map(|size| CreateSelectMenuOption::new(format!("Grupos de {size}"), size.to_string())
+                .default_selection(event.group_settings.size == Some(size))))
+            .collect()
+    };
+
+    CreateInteractionResponseMessage::new()
+        .ephemeral(true)
+        .embed(event.groups_embed().unwrap_or(CreateEmbed::new()
+            .title("👥 Grupos")
+            .description("Elige el tamaño de grupo para repartir a los titulares")))
+        .components(vec![
+            CreateActionRow::SelectMenu(CreateSelectMenu::new("edit_groups_size", sizes).placeholder("Tamaño de grupo")),
+            CreateActionRow::SelectMenu(CreateSelectMenu::new("edit_groups_lock", CreateSelectMenuKind::User { default_users: None })
+                .placeholder("Fijar/soltar jugadores en su grupo")
+                .max_values(25)),
+            CreateActionRow::Buttons(vec![
+                CreateButton::new("edit_groups_shuffle").label("Barajar").emoji('🔀').style(ButtonStyle::Primary),
+                CreateButton::new("edit_groups_done").label("Volver").style(ButtonStyle::Secondary)
+            ])
+        ])
+}
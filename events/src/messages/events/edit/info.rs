@@ -1,16 +1,17 @@
 use std::time::Duration;
 use chrono::{DateTime, Utc};
 use duration_string::DurationString;
-use serenity::all::{ComponentInteraction, Context, CreateInteractionResponse, CreateModal, EditScheduledEvent, MessageId, ModalInteraction, Timestamp};
+use serenity::all::{ComponentInteraction, CreateInteractionResponse, CreateMessage, CreateModal, EditScheduledEvent, MessageId, ModalInteraction, Timestamp};
+use crate::discord::Discord;
 use crate::events::Event;
 use crate::prelude::*;
 
-pub(super) async fn edit_datetime(interaction: &ComponentInteraction, ctx: &Context, store: &Store, event: &Event, msg_id: MessageId) -> Result<ModalInteraction> {
-    interaction.create_response(&ctx.http, CreateInteractionResponse::Modal(CreateModal::new("edit_datetime_modal", "Cambiar Fecha y Hora")
+pub(super) async fn edit_datetime(interaction: &ComponentInteraction, discord: &impl Discord, store: &Store, event: &Event, msg_id: MessageId) -> Result<ModalInteraction> {
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Modal(CreateModal::new("edit_datetime_modal", "Cambiar Fecha y Hora")
         .components(vec![
             components::short_input("Fecha y hora (formato discord):", "edit_datetime_field", "<t:1707929820:F>", true)
         ]))).await?;
-    if let Some(modal) = interaction.message.await_modal_interaction(&ctx.shard).await {
+    if let Some(modal) = discord.await_modal(interaction.message.id, None).await {
         let datetime = get_input_value(&modal.data.components, 0)
             .map(|dt| dt.replace("<t:", "").replace(":F>", ""))
             .map(|dt| dt.parse::<i64>().ok()).flatten()
@@ -18,11 +19,20 @@ pub(super) async fn edit_datetime(interaction: &ComponentInteraction, ctx: &Cont
 
         if let Some(datetime) = datetime {
             store.update_datetime(msg_id, datetime).await?;
+            super::super::feed::post_change(discord, store, msg_id, event, Some(modal.user.id),
+                                            &format!("🕒 Nueva fecha: <t:{}:F>", datetime.timestamp())).await?;
+            let guild = interaction.guild_id.ok_or(Error::UnknownInteraction("edit event outside guild".to_string()))?;
+            let updated = store.get_event(msg_id).await?;
+            let warnings = super::super::overlap::conflict_warnings(store, &updated, msg_id, guild).await?;
+            if !warnings.is_empty() {
+                discord.dm(updated.leader, CreateMessage::new()
+                    .content(format!("La nueva fecha de **{}** coincide con otros eventos:\n{}", updated.title, warnings.join("\n")))
+                ).await?;
+            }
             if let Some(event_id) = &event.scheduled_event {
-                let guild = interaction.guild_id.unwrap();
                 let duration: Duration = event.duration.into();
                 let end_datetime = datetime + duration;
-                guild.edit_scheduled_event(&ctx.http, event_id, EditScheduledEvent::new()
+                discord.edit_scheduled_event(guild, *event_id, EditScheduledEvent::new()
                     .start_time(Timestamp::from_unix_timestamp(datetime.timestamp())?)
                     .end_time(Timestamp::from_unix_timestamp(end_datetime.timestamp())?)
                 ).await?;
@@ -34,15 +44,15 @@ pub(super) async fn edit_datetime(interaction: &ComponentInteraction, ctx: &Cont
     }
 }
 
-pub(super) async fn edit_info(interaction: &ComponentInteraction, ctx: &Context, store: &Store, event: &Event, msg_id: MessageId) -> Result<ModalInteraction> {
-    interaction.create_response(&ctx.http, CreateInteractionResponse::Modal(CreateModal::new("edit_info_modal", "Cambiar info")
+pub(super) async fn edit_info(interaction: &ComponentInteraction, discord: &impl Discord, store: &Store, event: &Event, msg_id: MessageId) -> Result<ModalInteraction> {
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Modal(CreateModal::new("edit_info_modal", "Cambiar info")
         .components(vec![
             components::short_input("Titulo", "edit_info_title", &event.title, false),
             components::short_input("Duracion", "edit_info_duration", &event.duration.to_string(), false),
             components::long_input("Descripcion", "edit_info_description", &event.description, false),
         ]))).await?;
 
-    if let Some(modal) = interaction.message.await_modal_interaction(&ctx.shard).await {
+    if let Some(modal) = discord.await_modal(interaction.message.id, None).await {
         if let Some(title) = none_if_empty(get_input_value(&modal.data.components, 0)) {
             store.update_title(msg_id, title).await?;
         }
@@ -1,10 +1,11 @@
 use std::time::Duration;
-use serenity::all::{ComponentInteraction, Context, CreateActionRow, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, MessageId};
+use serenity::all::{ComponentInteraction, CreateActionRow, CreateInteractionResponse, CreateInteractionResponseMessage, CreateSelectMenu, CreateSelectMenuKind, MessageId};
+use crate::discord::Discord;
 use crate::events::{EventRole, Player};
 use crate::prelude::*;
 
-pub(super) async fn edit_role(interaction: &ComponentInteraction, ctx: &Context, store: &Store, role: EventRole, msg_id: MessageId) -> Result<ComponentInteraction> {
-    interaction.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(
+pub(super) async fn edit_role(interaction: &ComponentInteraction, discord: &impl Discord, store: &Store, role: EventRole, msg_id: MessageId) -> Result<ComponentInteraction> {
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(
         CreateInteractionResponseMessage::new()
             .ephemeral(true)
             .components(vec![
@@ -14,14 +15,16 @@ pub(super) async fn edit_role(interaction: &ComponentInteraction, ctx: &Context,
                 )
             ])
     )).await?;
-    let response = interaction.message.await_component_interaction(&ctx.shard)
-        .timeout(Duration::from_secs(60 * 3)).await;
+    let response = discord.await_component(interaction.message.id, Some(Duration::from_secs(60 * 3))).await;
     if let Some(interaction) = response {
         let users = get_selected_users(&interaction);
-        let guild = interaction.guild_id.clone().unwrap();
+        let guild = interaction.guild_id.ok_or(Error::UnknownInteraction("edit roles outside guild".to_string()))?;
+        let event = store.get_event(msg_id).await?;
         for user in users {
-            let member = guild.member(&ctx.http, user).await?;
+            let member = discord.member(guild, user).await?;
             store.signup_player(msg_id, role, &Player::new(user, member.display_name().to_string())).await?;
+            super::super::feed::post_change(discord, store, msg_id, &event, Some(interaction.user.id),
+                                            &format!("➡️ **{}** movido a {role} por el lider", member.display_name())).await?;
         }
         Ok(interaction)
     } else {
@@ -0,0 +1,96 @@
+use serenity::all::{CommandInteraction, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, Mention, MessageId, ResolvedValue, UserId};
+use sqlx::PgPool;
+use tracing::warn;
+use crate::discord::Discord;
+use crate::events::{Event, EventRole};
+use crate::prelude::*;
+use super::quotas::parse_channel_id;
+use super::rules::parse_message_id;
+
+/// Thread names can't be longer than this
+const MAX_THREAD_NAME: usize = 100;
+
+/// Records a change of the event and posts it to its thread, a thread that can't be reached doesn't stop the change
+pub(crate) async fn post_change<D: Discord + ?Sized>(discord: &D, store: &Store, message: MessageId, event: &Event, actor: Option<UserId>, change: &str) -> Result<()> {
+    store.log_change(message, actor, change).await?;
+    if let Some(thread) = event.thread {
+        if let Err(why) = discord.send_message(thread, CreateMessage::new().content(change)).await {
+            warn!("Change not posted to thread {thread}: {why:?}");
+        }
+    }
+    Ok(())
+}
+
+/// Posts the last change and archives the thread of a finished or canceled event
+pub(crate) async fn close_thread<D: Discord + ?Sized>(discord: &D, store: &Store, message: MessageId, event: &Event, change: &str) -> Result<()> {
+    post_change(discord, store, message, event, None, change).await?;
+    if let Some(thread) = event.thread {
+        if let Err(why) = discord.archive_thread(thread).await {
+            warn!("Thread {thread} not archived: {why:?}");
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn thread_name(event: &Event) -> String {
+    event.title.chars().take(MAX_THREAD_NAME).collect()
+}
+
+/// `/event announce`, the leader posts a message to the signed players in the thread of the event
+pub async fn announce(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let (mut link, mut text) = (None, None);
+    if let Some(ResolvedValue::SubCommand(options)) = interaction.data.options().first().map(|o| o.value.clone()) {
+        for option in options {
+            match (option.name, option.value) {
+                ("event", ResolvedValue::String(value)) => link = Some(value.to_string()),
+                ("message", ResolvedValue::String(value)) => text = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let message = link.as_deref().and_then(parse_message_id);
+    let event = match message {
+        Some(message) => store.get_event(message).await.ok(),
+        None => None
+    };
+
+    let answer = match (message, event, text.filter(|t| !t.is_empty())) {
+        (Some(message), Some(event), Some(text)) if event.leader == interaction.user.id => {
+            // Events created before threads existed get the announcement in their channel
+            match event.thread.or(link.as_deref().and_then(parse_channel_id)) {
+                Some(target) => {
+                    let sent = discord.send_message(target, announcement(&event, interaction.user.id, &text)).await?;
+                    store.track_message(message, target, sent.id).await?;
+                    store.log_change(message, Some(interaction.user.id), &format!("📢 Anuncio: {text}")).await?;
+                    format!("Anuncio enviado a {}", Mention::Channel(target))
+                }
+                None => "Usa el enlace del mensaje del evento".to_string()
+            }
+        }
+        (Some(_), Some(_), Some(_)) => "Solo el lider del evento puede enviar anuncios".to_string(),
+        (Some(_), Some(_), None) => "Falta el mensaje del anuncio".to_string(),
+        _ => "Evento no encontrado, usa el enlace del mensaje del evento".to_string()
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .content(answer)
+    )).await
+}
+
+fn announcement(event: &Event, leader: UserId, text: &str) -> CreateMessage {
+    let signed: Vec<String> = event.roles.iter()
+        .filter(|pr| pr.role != EventRole::Absent)
+        .flat_map(|pr| pr.players.iter())
+        .map(|p| Mention::User(p.id).to_string())
+        .collect();
+
+    CreateMessage::new()
+        .content(signed.join(" "))
+        .embed(CreateEmbed::new()
+            .title("📢 Anuncio del lider")
+            .description(format!("{text}\n\n— {}", Mention::User(leader))))
+}
@@ -0,0 +1,88 @@
+use serenity::all::{Attachment, CommandInteraction, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseFollowup, CreateInteractionResponseMessage, ResolvedValue};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{EventImage, EventKind};
+use crate::prelude::*;
+use super::template::manages_events;
+
+/// Biggest image accepted, Discord rejects bigger scheduled event covers anyway
+const MAX_IMAGE_SIZE: u32 = 8 * 1024 * 1024;
+const IMAGE_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/webp", "image/gif"];
+
+/// `/event image`, adds an image to the library of the guild for the given event kind, only for the officers
+pub async fn upload_image(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let guild = interaction.guild_id.ok_or(Error::UnknownInteraction("event image outside guild".to_string()))?;
+    if !manages_events(interaction) {
+        return discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .ephemeral(true)
+                .embed(CreateEmbed::new()
+                    .title("Sin permiso")
+                    .description("Solo los oficiales pueden subir imagenes a la biblioteca"))
+        )).await;
+    }
+    let (mut kind, mut file, mut key, mut aliases) = (EventKind::Trial, None, None, vec![]);
+    if let Some(ResolvedValue::SubCommand(options)) = interaction.data.options().first().map(|o| o.value.clone()) {
+        for option in options {
+            match (option.name, option.value) {
+                ("kind", ResolvedValue::String(value)) => kind = EventKind::from_partial_id(value).unwrap_or(EventKind::Trial),
+                ("file", ResolvedValue::Attachment(value)) => file = Some(value.clone()),
+                ("key", ResolvedValue::String(value)) => key = Some(value.trim().to_lowercase()).filter(|k| !k.is_empty()),
+                ("aliases", ResolvedValue::String(value)) => aliases = value.split(',')
+                    .map(|a| a.trim().to_lowercase())
+                    .filter(|a| !a.is_empty())
+                    .collect(),
+                _ => {}
+            }
+        }
+    }
+
+    let problem = match &file {
+        Some(file) => invalid_image(file),
+        None => Some("Falta el archivo de imagen".to_string())
+    };
+    let (Some(file), None) = (file, &problem) else {
+        return discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+            CreateInteractionResponseMessage::new()
+                .ephemeral(true)
+                .embed(CreateEmbed::new()
+                    .title("Imagen no valida")
+                    .description(problem.unwrap_or_default()))
+        )).await;
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Defer(
+        CreateInteractionResponseMessage::new().ephemeral(true)
+    )).await?;
+    let data = file.download().await?;
+    let id = store.add_image(guild, kind, &file.filename, &data, key.as_deref(), &aliases).await?;
+    let image = EventImage { id: Some(id), kind, file_name: file.filename.clone(), key, aliases };
+
+    discord.create_followup(&interaction.token, CreateInteractionResponseFollowup::new()
+        .ephemeral(true)
+        .embed(CreateEmbed::new()
+            .title("Imagen guardada")
+            .description(format!("`{}` se usara en los eventos de {kind}{}", image.label(), if image.aliases.is_empty() {
+                String::new()
+            } else {
+                format!(" cuyo titulo contenga: {}", image.aliases.join(", "))
+            }))
+            .image(&file.url))
+    ).await?;
+
+    Ok(())
+}
+
+/// Reason the attachment can't be used as event image
+fn invalid_image(file: &Attachment) -> Option<String> {
+    if !file.content_type.as_deref().is_some_and(|t| IMAGE_TYPES.contains(&t)) {
+        Some("Solo se admiten imagenes png, jpg, webp o gif".to_string())
+    } else if file.size > MAX_IMAGE_SIZE {
+        Some(format!("La imagen ocupa mas de {}MB", MAX_IMAGE_SIZE / 1024 / 1024))
+    } else if file.filename.len() > 80 || file.filename.contains(':') {
+        Some("El nombre del archivo es demasiado largo o contiene `:`".to_string())
+    } else {
+        None
+    }
+}
@@ -0,0 +1,50 @@
+use serenity::all::{CommandInteraction, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, Mention, ResolvedValue};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{EventKind, PvpAlliance, PvpFilter, PvpStyle};
+use crate::prelude::*;
+
+/// `/event list`, upcoming events filtered by kind and PvP details
+pub async fn list_events(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let (mut kind, mut filter) = (None, PvpFilter::default());
+    if let Some(ResolvedValue::SubCommand(options)) = interaction.data.options().first().map(|o| o.value.clone()) {
+        for option in options {
+            match (option.name, option.value) {
+                ("kind", ResolvedValue::String(value)) => kind = EventKind::from_partial_id(value),
+                ("alliance", ResolvedValue::String(value)) => filter.alliance = PvpAlliance::from_id(value),
+                ("style", ResolvedValue::String(value)) => filter.style = PvpStyle::from_id(value),
+                ("campaign", ResolvedValue::String(value)) => filter.campaign = Some(value.trim().to_string()).filter(|c| !c.is_empty()),
+                _ => {}
+            }
+        }
+    }
+    // PvP details only make sense for PvP events
+    if filter != PvpFilter::default() {
+        kind = Some(EventKind::PvP);
+    }
+
+    let events = store.list_upcoming_events(kind, &filter).await?;
+    let lines = if events.is_empty() { "No hay eventos que coincidan".to_string() } else {
+        events.iter()
+            .map(|(message, event)| {
+                let place = match (interaction.guild_id, event.channel) {
+                    (Some(guild), Some(channel)) => format!(" https://discord.com/channels/{guild}/{channel}/{message}"),
+                    (None, Some(channel)) => format!(" {}", Mention::Channel(channel)),
+                    _ => String::new()
+                };
+                let details = event.pvp.as_ref().map(|p| format!("\n  {}", p.short_es())).unwrap_or_default();
+                format!("- <t:{}:f> **{}**{place}{details}", event.datetime.map(|dt| dt.timestamp()).unwrap_or_default(), event.title)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .embed(CreateEmbed::new()
+                .title("Proximos eventos")
+                .description(lines))
+    )).await
+}
@@ -0,0 +1,36 @@
+use serenity::all::{GuildId, MessageId, UserId};
+use crate::events::{conflict_warning, Event};
+use crate::prelude::*;
+
+/// Other events at the same time as `event`
+async fn overlapping(store: &Store, event: &Event, message: MessageId) -> Result<Vec<Event>> {
+    let (Some(start), Some(end)) = (event.datetime, event.end()) else {
+        return Ok(vec![]);
+    };
+
+    let mut events = vec![];
+    for other in store.overlapping_events(start, end, message).await? {
+        events.push(store.get_event(other).await?);
+    }
+    Ok(events)
+}
+
+/// Titles of the events at the same time where `user` is already a starter
+pub(crate) async fn overlapping_signups(store: &Store, event: &Event, message: MessageId, user: UserId) -> Result<Vec<String>> {
+    Ok(overlapping(store, event, message).await?
+        .into_iter()
+        .filter(|other| other.is_starter(user))
+        .map(|other| other.title)
+        .collect())
+}
+
+/// Warnings for the leader about events at the same time sharing the leader, the voice channel or too many players
+pub(crate) async fn conflict_warnings(store: &Store, event: &Event, message: MessageId, guild: GuildId) -> Result<Vec<String>> {
+    Ok(overlapping(store, event, message).await?
+        .iter()
+        .filter_map(|other| {
+            let conflicts = event.conflicts_with(other, guild);
+            (!conflicts.is_empty()).then(|| conflict_warning(other, &conflicts))
+        })
+        .collect())
+}
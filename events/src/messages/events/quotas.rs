@@ -0,0 +1,95 @@
+use serenity::all::{ChannelId, CommandInteraction, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, EditMessage, ResolvedValue};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::ClassQuota;
+use crate::prelude::*;
+use super::rules::parse_message_id;
+use super::template::can_edit_template;
+
+/// `/event quotas`, shows the class quotas of an event or template, or replaces them when `quotas` is given
+/// by the leader of the event or the owner of the template
+pub async fn event_quotas(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let (mut link, mut template, mut quotas) = (None, None, None);
+    if let Some(ResolvedValue::SubCommand(options)) = interaction.data.options().first().map(|o| o.value.clone()) {
+        for option in options {
+            match (option.name, option.value) {
+                ("event", ResolvedValue::String(value)) => link = Some(value.to_string()),
+                ("template", ResolvedValue::String(value)) => template = Some(value.trim().to_string()),
+                ("quotas", ResolvedValue::String(value)) => quotas = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let quotas = match quotas.as_deref().map(str::trim) {
+        Some("ninguna") => Ok(Some(vec![])),
+        Some(quotas) => ClassQuota::parse_list(quotas).map(Some),
+        None => Ok(None)
+    };
+
+    let embed = match (link.as_deref().and_then(parse_message_id), template, quotas) {
+        (_, _, Err(why)) => CreateEmbed::new()
+            .title("Cupos no validos")
+            .description(format!("{why}\nEjemplo: `Necromancer max 2; Warden min 1; Templar min 1 max 2`")),
+        (Some(message), _, Ok(quotas)) => match store.get_event(message).await {
+            Ok(event) if quotas.is_some() && event.leader != interaction.user.id => CreateEmbed::new()
+                .title("Sin permiso")
+                .description("Solo el lider del evento puede cambiar sus cupos de clase"),
+            Ok(mut event) => {
+                if let Some(quotas) = quotas {
+                    store.update_class_quotas(message, &quotas).await?;
+                    event.class_quotas = quotas;
+                    // Links carry the channel so the unmet minimums can be shown right away
+                    if let Some(channel) = link.as_deref().and_then(parse_channel_id) {
+                        discord.edit_message(channel, message, EditMessage::new().embeds(event.embeds())).await?;
+                    }
+                }
+                quotas_embed(&format!("Cupos de clase de {}", event.title), &event.class_quotas)
+                    .fields(event.missing_classes_field())
+            }
+            Err(_) => CreateEmbed::new()
+                .title("Evento no encontrado")
+                .description("Usa el enlace del mensaje del evento")
+        },
+        (None, Some(name), Ok(quotas)) => match (store.get_template(&name).await?, quotas) {
+            (Some(template), Some(_)) if !can_edit_template(&template, interaction) => CreateEmbed::new()
+                .title("Sin permiso")
+                .description("Solo quien guardo la plantilla o los oficiales pueden cambiar sus cupos de clase"),
+            (Some(_), Some(quotas)) => {
+                store.update_template_quotas(&name, &quotas).await?;
+                quotas_embed(&format!("Cupos de clase de la plantilla {name}"), &quotas)
+            }
+            (Some(template), None) => quotas_embed(&format!("Cupos de clase de la plantilla {name}"), &template.class_quotas),
+            (None, _) => CreateEmbed::new()
+                .title("Plantilla no encontrada")
+                .description(format!("No existe la plantilla `{name}`"))
+        },
+        (None, None, _) => CreateEmbed::new()
+            .title("Falta el evento o la plantilla")
+            .description("Indica el enlace del evento o el nombre de la plantilla")
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .embed(embed)
+    )).await
+}
+
+fn quotas_embed(title: &str, quotas: &[ClassQuota]) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(title)
+        .description(if quotas.is_empty() {
+            "Sin cupos de clase".to_string()
+        } else {
+            quotas.iter().map(|q| format!("- `{q}`")).collect::<Vec<String>>().join("\n")
+        })
+}
+
+/// Channel segment of a message link `.../channels/guild/channel/message`
+pub(super) fn parse_channel_id(link: &str) -> Option<ChannelId> {
+    let mut segments = link.trim().rsplit('/');
+    segments.next()?;
+    segments.next()?.parse::<u64>().ok().filter(|id| *id != 0).map(ChannelId::new)
+}
@@ -0,0 +1,149 @@
+use std::str::FromStr;
+use serenity::all::{AutocompleteChoice, CommandInteraction, CreateAutocompleteResponse, CreateInteractionResponse, CreateInteractionResponseMessage, GuildId, MessageId, ResolvedValue, UserId};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{Event, EventRole, Player, PlayerClass};
+use crate::prelude::*;
+use super::feed::post_change;
+
+#[derive(Default)]
+struct RosterOptions {
+    event: Option<MessageId>,
+    player: Option<UserId>,
+    role: Option<EventRole>,
+    class: Option<PlayerClass>
+}
+
+/// Entrypoint for the officers `/roster` command, changes the players of an event without going through the signup buttons
+pub async fn roster_command(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let subcommand = super::subcommand(interaction).unwrap_or_default().to_string();
+    let options = parse_options(interaction)?;
+    let guild = interaction.guild_id.ok_or(Error::UnknownInteraction("roster outside guild".to_string()))?;
+
+    let answer = match options.event {
+        Some(message) => match store.get_event(message).await {
+            Ok(mut event) => match apply(&subcommand, &options, &mut event, discord, guild).await? {
+                Ok((user, change)) => {
+                    // The stored player is the one kept by the event, with the flex role added when it went to the reserve
+                    match event.player(user) {
+                        Some((role, player)) => store.signup_player(message, role, player).await?,
+                        None => store.remove_player(message, user).await?
+                    }
+                    let channel = super::event_channel(discord, guild, &event).await;
+                    super::render_event(discord, channel, message, &event).await;
+                    post_change(discord, &store, message, &event, Some(interaction.user.id), &change).await?;
+                    change
+                }
+                Err(answer) => answer
+            },
+            Err(_) => "Evento no encontrado, eligelo de la lista".to_string()
+        },
+        None => "Evento no encontrado, eligelo de la lista".to_string()
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .content(answer)
+    )).await
+}
+
+/// Upcoming events for the `event` option, signups of the chosen event for the `player` option
+pub async fn roster_autocomplete(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let choices = match interaction.data.autocomplete() {
+        Some(option) if option.name == "event" => store.upcoming_events(option.value).await?
+            .into_iter()
+            .map(|(message, title, datetime)| {
+                let name = format!("{title} — {}", datetime.format("%d/%m %H:%M"));
+                AutocompleteChoice::new(name.chars().take(100).collect::<String>(), message.to_string())
+            })
+            .collect(),
+        Some(option) if option.name == "player" => {
+            let search = option.value.to_lowercase();
+            match parse_options(interaction)?.event {
+                Some(message) => store.get_event(message).await.map(|event| event.roles).unwrap_or_default()
+                    .into_iter()
+                    .flat_map(|pr| pr.players.into_iter().map(move |p| (pr.role, p)))
+                    .filter(|(_, p)| p.name.to_lowercase().contains(&search))
+                    .take(25)
+                    .map(|(role, p)| AutocompleteChoice::new(format!("{} ({role})", p.name), p.id.to_string()))
+                    .collect(),
+                None => vec![]
+            }
+        }
+        _ => vec![]
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Autocomplete(
+        CreateAutocompleteResponse::new().set_choices(choices)
+    )).await
+}
+
+fn parse_options(interaction: &CommandInteraction) -> Result<RosterOptions> {
+    let options = match interaction.data.options().into_iter().next().map(|o| o.value) {
+        Some(ResolvedValue::SubCommand(options)) => options,
+        _ => return Err(Error::UnknownInteraction("roster".to_string()))
+    };
+
+    let mut parsed = RosterOptions::default();
+    for option in options {
+        match (option.name, option.value) {
+            ("event", ResolvedValue::String(value)) => parsed.event = super::rules::parse_message_id(value),
+            ("player", ResolvedValue::String(value)) => parsed.player = value.parse::<u64>().ok().filter(|id| *id != 0).map(UserId::new),
+            ("player", ResolvedValue::User(user, _)) => parsed.player = Some(user.id),
+            ("role", ResolvedValue::String(value)) => parsed.role = EventRole::from_partial_id(value),
+            ("class", ResolvedValue::String(value)) => parsed.class = Some(PlayerClass::from_str(value)?),
+            _ => {}
+        }
+    }
+    Ok(parsed)
+}
+
+/// Changes the roster of `event`, returns the changed player and the change for the audit trail, or the reason it can't be done
+async fn apply(subcommand: &str, options: &RosterOptions, event: &mut Event, discord: &impl Discord, guild: GuildId) -> Result<std::result::Result<(UserId, String), String>> {
+    let Some(user) = options.player else {
+        return Ok(Err("Jugador no encontrado, eligelo de la lista".to_string()));
+    };
+    if let Some(role) = options.role.filter(|r| !event.kind.roles().contains(r)) {
+        return Ok(Err(format!("Los eventos {} no tienen el rol {role}", event.kind)));
+    }
+
+    let signed = event.player(user).map(|(role, player)| (role, player.clone()));
+    let change = match (subcommand, signed) {
+        ("move", Some((from, player))) => {
+            let Some(role) = options.role else { return Ok(Err("Falta el rol".to_string())) };
+            let to = event.add_player(role, player.clone());
+            let name = player.name;
+            let full = if to != role { format!(" ({role} esta lleno)") } else { String::new() };
+            format!("🔀 **{name}** movido de {from} a {to}{full} por un oficial")
+        }
+        ("remove", Some((from, player))) => {
+            event.remove_player(user);
+            format!("🗑️ **{}** quitado de {from} por un oficial", player.name)
+        }
+        ("set-class", Some((role, mut player))) => {
+            let Some(class) = options.class else { return Ok(Err("Falta la clase".to_string())) };
+            player.class = Some(class);
+            // A class over its quota sends the player to the reserve like any other signup
+            let to = event.add_player(role, player.clone());
+            let moved = if to != role { format!(", pasa a {to} por el cupo de clase") } else { String::new() };
+            format!("🎭 **{}** cambia de clase a {}{moved}", player.name, class.label_es())
+        }
+        ("add", None) => {
+            let Some(role) = options.role else { return Ok(Err("Falta el rol".to_string())) };
+            let member = discord.member(guild, user).await?;
+            let mut player = Player::new(user, member.display_name().to_string());
+            player.class = options.class;
+            let to = event.add_player(role, player.clone());
+            let full = if to != role { format!(" ({role} esta lleno)") } else { String::new() };
+            format!("➕ **{}** añadido a {to}{full} por un oficial", player.name)
+        }
+        ("add", Some((role, player))) => return Ok(Err(format!("**{}** ya esta apuntado como {role}, usa `/roster move`", player.name))),
+        (_, None) => return Ok(Err("Ese jugador no esta apuntado al evento".to_string())),
+        _ => return Err(Error::UnknownInteraction(format!("roster {subcommand}")))
+    };
+
+    Ok(Ok((user, change)))
+}
@@ -0,0 +1,91 @@
+use serenity::all::{CommandInteraction, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, MessageId, ResolvedValue};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{default_rules, EligibilityRule};
+use crate::prelude::*;
+use super::template::can_edit_template;
+
+/// `/event rules`, shows the rules of an event or template, or replaces them when `rules` is given
+/// by the leader of the event or the owner of the template
+pub async fn event_rules(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let (mut event, mut template, mut rules) = (None, None, None);
+    if let Some(ResolvedValue::SubCommand(options)) = interaction.data.options().first().map(|o| o.value.clone()) {
+        for option in options {
+            match (option.name, option.value) {
+                ("event", ResolvedValue::String(value)) => event = Some(value.to_string()),
+                ("template", ResolvedValue::String(value)) => template = Some(value.trim().to_string()),
+                ("rules", ResolvedValue::String(value)) => rules = Some(value.to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let rules = match rules.as_deref().map(str::trim) {
+        Some("ninguna") => Ok(Some(vec![])),
+        Some(rules) => EligibilityRule::parse_list(rules).map(Some),
+        None => Ok(None)
+    };
+
+    let embed = match (event.as_deref().and_then(parse_message_id), template, rules) {
+        (_, _, Err(why)) => CreateEmbed::new()
+            .title("Reglas no validas")
+            .description(format!("{why}\nEjemplo: `rol @Roster => reserva; sin-rol @Avanzado => rechazar; max-semana 2 => aprobacion; clase Warden,Templar => reserva; asistencia 75% => aprobacion; sin-solape => reserva`")),
+        (Some(message), _, Ok(rules)) => match store.get_event(message).await {
+            Ok(event) if rules.is_some() && event.leader != interaction.user.id => CreateEmbed::new()
+                .title("Sin permiso")
+                .description("Solo el lider del evento puede cambiar sus reglas"),
+            Ok(event) => {
+                if let Some(rules) = &rules {
+                    store.update_rules(message, rules).await?;
+                }
+                let rules = rules.unwrap_or(event.rules);
+                let current = if rules.is_empty() { default_rules(event.kind, event.notification_role) } else { rules.clone() };
+                rules_embed(&format!("Reglas de {}", event.title), &current, rules.is_empty())
+            }
+            Err(_) => CreateEmbed::new()
+                .title("Evento no encontrado")
+                .description("Usa el enlace del mensaje del evento")
+        },
+        (None, Some(name), Ok(rules)) => match (store.get_template(&name).await?, rules) {
+            (Some(template), Some(_)) if !can_edit_template(&template, interaction) => CreateEmbed::new()
+                .title("Sin permiso")
+                .description("Solo quien guardo la plantilla o los oficiales pueden cambiar sus reglas"),
+            (Some(_), Some(rules)) => {
+                store.update_template_rules(&name, &rules).await?;
+                rules_embed(&format!("Reglas de la plantilla {name}"), &rules, false)
+            }
+            (Some(template), None) => rules_embed(&format!("Reglas de la plantilla {name}"), &template.rules, false),
+            (None, _) => CreateEmbed::new()
+                .title("Plantilla no encontrada")
+                .description(format!("No existe la plantilla `{name}`"))
+        },
+        (None, None, _) => CreateEmbed::new()
+            .title("Falta el evento o la plantilla")
+            .description("Indica el enlace del evento o el nombre de la plantilla")
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .embed(embed)
+    )).await
+}
+
+fn rules_embed(title: &str, rules: &[EligibilityRule], defaults: bool) -> CreateEmbed {
+    let description = if rules.is_empty() {
+        // Events without rules of their own fall back to `default_rules` when signing up
+        "Sin reglas propias, se aplican las de por defecto: el rol de notificacion del evento o no tener rol avanzado".to_string()
+    } else {
+        rules.iter().map(|r| format!("- `{r}`\n  {}", r.condition.explanation_es())).collect::<Vec<String>>().join("\n")
+    };
+
+    CreateEmbed::new()
+        .title(title)
+        .description(if defaults { format!("{description}\n\n*Reglas por defecto del evento*") } else { description })
+}
+
+/// Accepts a message id or a message link, whose last segment is the id
+pub(super) fn parse_message_id(value: &str) -> Option<MessageId> {
+    value.trim().rsplit('/').next()?.parse::<u64>().ok().filter(|id| *id != 0).map(MessageId::new)
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use chrono::DateTime;
+use serenity::all::{ChannelId, EditMessage, GuildId, MessageId, ScheduledEvent, ScheduledEventId, UserId};
+use sqlx::PgPool;
+use tracing::{info, instrument};
+use crate::discord::Discord;
+use crate::events::{Event, EventStatus, Player};
+use crate::prelude::*;
+use crate::tasks;
+use super::feed;
+use super::quotas::parse_channel_id;
+
+/// Brings back the changes made to a scheduled event from Discord: title, start time and status
+#[instrument(skip(discord, pool))]
+pub async fn scheduled_event_update(scheduled: &ScheduledEvent, discord: Arc<dyn Discord>, pool: PgPool) -> Result<()> {
+    let store = Arc::new(Store::new(pool));
+    let Some((message, channel)) = event_message(&store, scheduled).await? else { return Ok(()) };
+    let mut event = store.get_event(message).await?;
+
+    if scheduled.name != event.title {
+        store.update_title(message, scheduled.name.clone()).await?;
+        feed::post_change(discord.as_ref(), &store, message, &event, None, &format!("✏️ Nuevo titulo: {}", scheduled.name)).await?;
+    }
+
+    let start = DateTime::from_timestamp(scheduled.start_time.unix_timestamp(), 0);
+    if let Some(start) = start.filter(|s| Some(*s) != event.datetime) {
+        store.update_datetime(message, start).await?;
+        feed::post_change(discord.as_ref(), &store, message, &event, None, &format!("🕒 Nueva fecha: <t:{}:F>", start.timestamp())).await?;
+    }
+
+    let status = EventStatus::from(scheduled.status);
+    if status != event.status {
+        store.update_status(message, status).await?;
+        match status {
+            EventStatus::Active => feed::post_change(discord.as_ref(), &store, message, &event, None, "🟢 El evento ha empezado").await?,
+            EventStatus::Completed => feed::close_thread(discord.as_ref(), &store, message, &event, "🏁 Evento finalizado").await?,
+            EventStatus::Canceled => feed::close_thread(discord.as_ref(), &store, message, &event, "❌ Evento cancelado").await?,
+            EventStatus::Scheduled => {}
+        }
+    }
+
+    // Also catches the start time changed from the edit menu, which comes back through here
+    match (status, start.or(event.datetime)) {
+        (EventStatus::Scheduled, Some(start)) => tasks::set_reminder(start, discord.clone(), channel, message, store.clone()),
+        (EventStatus::Canceled, _) => tasks::unset_reminder(&message),
+        _ => {}
+    }
+
+    event = store.get_event(message).await?;
+    render(discord.as_ref(), channel, message, &event).await
+}
+
+/// A scheduled event deleted in Discord leaves the event canceled, unless it already took place
+#[instrument(skip(discord, pool))]
+pub async fn scheduled_event_delete(scheduled: &ScheduledEvent, discord: Arc<dyn Discord>, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let Some((message, channel)) = event_message(&store, scheduled).await? else { return Ok(()) };
+    let event = store.get_event(message).await?;
+
+    store.update_scheduled_event(message, None).await?;
+    if event.status != EventStatus::Completed && event.status != EventStatus::Canceled {
+        store.update_status(message, EventStatus::Canceled).await?;
+        tasks::unset_reminder(&message);
+        feed::close_thread(discord.as_ref(), &store, message, &event, "❌ Evento cancelado").await?;
+    }
+
+    let event = store.get_event(message).await?;
+    render(discord.as_ref(), channel, message, &event).await
+}
+
+/// Keeps the "Interesados" list of the event in sync with the members interested in the scheduled event
+#[instrument(skip(discord, pool))]
+pub async fn scheduled_event_interest(guild: GuildId, scheduled: ScheduledEventId, user: UserId, interested: bool, discord: Arc<dyn Discord>, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let scheduled = discord.scheduled_event(guild, scheduled).await?;
+    let Some((message, channel)) = event_message(&store, &scheduled).await? else { return Ok(()) };
+
+    if interested {
+        let member = discord.member(guild, user).await?;
+        store.add_interested(message, &Player::new(user, member.display_name())).await?;
+    } else {
+        store.remove_interested(message, user).await?;
+    }
+
+    let event = store.get_event(message).await?;
+    render(discord.as_ref(), channel, message, &event).await
+}
+
+/// Event message and channel of a scheduled event created by the bot, events stored before their channel
+/// was saved fall back to the link in the description, which leaders can edit away
+async fn event_message(store: &Store, scheduled: &ScheduledEvent) -> Result<Option<(MessageId, ChannelId)>> {
+    let Some(message) = store.get_scheduled_event_message(scheduled.id).await? else { return Ok(None) };
+    let channel = store.get_event(message).await?.channel
+        .or_else(|| scheduled.description.as_deref().and_then(|d| d.lines().next()).and_then(parse_channel_id));
+    match channel {
+        Some(channel) => Ok(Some((message, channel))),
+        None => {
+            info!("Scheduled event {} lost the event link", scheduled.id);
+            Ok(None)
+        }
+    }
+}
+
+async fn render(discord: &dyn Discord, channel: ChannelId, message: MessageId, event: &Event) -> Result<()> {
+    let mut edit = EditMessage::new().embeds(event.embeds());
+    if event.status == EventStatus::Canceled {
+        edit = edit.components(vec![]);
+    }
+    discord.edit_message(channel, message, edit).await?;
+    Ok(())
+}
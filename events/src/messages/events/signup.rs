@@ -1,78 +1,118 @@
 use std::str::FromStr;
 use serde::{Deserialize, Serialize};
-use serenity::all::{ChannelId, ComponentInteraction, Context, CreateEmbed, CreateInteractionResponseMessage, CreateMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditMessage, Member, Mention, RoleId, UserId};
+use serenity::all::{ButtonStyle, ChannelId, ComponentInteraction, CreateButton, CreateEmbed, CreateInteractionResponseMessage, CreateMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditMessage, Member, Mention, MessageId, RoleId, UserId};
 use serenity::builder::CreateInteractionResponse;
-use crate::events::{EventKind, EventRole, Player, PlayerClass, PlayersInRole};
+use crate::discord::Discord;
+use crate::events::{check_rules, Condition, Event, EventKind, EventRole, NotificationKind, Player, PlayerClass, PlayersInRole, RuleOutcome, SignupProfile, Verdict};
+use crate::tasks::notifications::notify;
 use crate::prelude::*;
-use serenity::futures::StreamExt;
 use sqlx::PgPool;
 use tracing::{info, instrument};
 
-pub async fn signup_event(interaction: &ComponentInteraction, ctx: &Context, pool: PgPool) -> Result<()> {
+pub async fn signup_event(interaction: &ComponentInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
     let store = Store::new(pool);
     if let Some(role) = EventRole::from_partial_id(&interaction.data.custom_id) {
-        let mut original_message = interaction.message.clone();
+        let original_message = interaction.message.clone();
         let mut event = store.get_event(interaction.message.id).await?;
-        let member = interaction.member.clone().unwrap();
+        let member = interaction.member.clone().ok_or(Error::UnknownInteraction("signup outside guild".to_string()))?;
         let mut player = Player::new(interaction.user.id, member.display_name());
 
-        let dm = event.leader.create_dm_channel(&ctx.http).await?;
         let username = member.display_name();
         let channel = Mention::Channel(interaction.channel_id).to_string();
 
         if role == EventRole::Absent {
-            store.signup_player(original_message.id, EventRole::Absent, &player).await?;
-            event.add_player(EventRole::Absent, player);
-            original_message.edit(&ctx.http, EditMessage::new().embed(event.embed())).await?;
-            interaction.create_response(&ctx.http, CreateInteractionResponse::Message(signup_msg(&member, None, event.leader, event.kind))).await?;
-
-            dm.send_message(&ctx.http, CreateMessage::new()
-                .content(format!("{username} no va a poder asistir al evento en {channel}"))
-            ).await?;
+            // Signed as absent once the reason is submitted, see `absence_submit`
+            discord.create_response(interaction.id, &interaction.token, super::absence::absence_modal()).await?;
         } else {
 
             // Select flex roles and class
-            interaction.create_response(&ctx.http, select_class_flex(event.kind)).await?;
-            let mut selects = interaction.get_response(&ctx.http).await?
-                .await_component_interaction(&ctx.shard)
-                .stream();
+            discord.create_response(interaction.id, &interaction.token, select_class_flex(event.kind)).await?;
+            let selects = discord.get_response(&interaction.token).await?;
 
-            while let Some(interaction) = selects.next().await {
-                if interaction.data.custom_id.ends_with("flex") {
+            while let Some(interaction) = discord.await_component(selects.id, None).await {
+                if interaction.data.custom_id == "swap_propose" {
+                    super::swap::select_swap_target(&interaction, &original_message, discord, &store).await?;
+                } else if interaction.data.custom_id == "swap_target" {
+                    super::swap::request_swap(&interaction, &original_message, discord, &store).await?;
+                } else if interaction.data.custom_id.ends_with("flex") {
                     let selected_flex = get_selected_options(&interaction);
-                    interaction.create_response(&ctx.http, update_flex_roles(selected_flex)).await?;
+                    discord.create_response(interaction.id, &interaction.token, update_flex_roles(selected_flex)).await?;
                 } else {
-                    player.class = PlayerClass::from_str(&get_selected_option(&interaction).unwrap()).ok();
+                    player.class = get_selected_option(&interaction).and_then(|c| PlayerClass::from_str(&c).ok());
                     player.flex = interaction.message.embeds.first().map(|e| e
-                        .description.clone().unwrap()
+                        .description.clone().unwrap_or_default()
                         .split(",")
                         .filter_map(|f| EventRole::from_str(f).ok())
                         .collect()).unwrap_or(vec![]);
                     let flex_as_string = player.flex.iter().map(|r| r.to_string()).collect::<Vec<String>>();
 
-                    if event.leader != interaction.user.id &&
-                        (event.notification_role.is_some_and(|r| !member.roles.contains(&r)) ||
-                            (event.notification_role.is_none() && initiation_check(&member, event.kind))) {
-                        if !player.flex.contains(&role) && role != EventRole::Reserve {
-                            player.flex.push(role);
+                    let overlapping = if role.is_backup_role() { vec![] } else {
+                        super::overlap::overlapping_signups(&store, &event, original_message.id, player.id).await?
+                    };
+                    let verdict = if event.leader == interaction.user.id || role.is_backup_role() {
+                        None
+                    } else {
+                        let profile = signup_profile(&store, &event, &member, &player, original_message.id, overlapping.clone()).await?;
+                        check_rules(&event.effective_rules(), &profile)
+                    };
+
+                    let quota_full = match &verdict {
+                        None if !role.is_backup_role() => event.class_quota_full(&player).map(|q| q.full_explanation_es()),
+                        _ => None
+                    };
+
+                    match &verdict {
+                        Some(Verdict { outcome: RuleOutcome::Reject, .. }) => {
+                            discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(
+                                signup_msg(&member, verdict.as_ref(), event.leader, &overlapping)
+                            )).await?;
+                            continue;
                         }
-                        event.add_player(EventRole::Reserve, player.clone());
-                        store.signup_player(original_message.id, EventRole::Reserve, &player).await?;
+                        Some(Verdict { outcome, reasons }) => {
+                            if !player.flex.contains(&role) {
+                                player.flex.push(role);
+                            }
+                            event.add_player(EventRole::Reserve, player.clone());
+                            store.signup_player(original_message.id, EventRole::Reserve, &player).await?;
 
-                        dm.send_message(&ctx.http, CreateMessage::new()
-                            .content(format!("{username} no cumple los requisitos de titular y se ha movido a reserva en el evento de {channel}, flexible a: {}", flex_as_string.join(",")))
-                        ).await?;
-                    } else {
-                        let role = event.add_player(role, player.clone());
-                        store.signup_player(original_message.id, role, &player).await?;
+                            // Approvals need the buttons of the DM, whatever the preferences of the leader
+                            if *outcome == RuleOutcome::Approval {
+                                discord.dm(event.leader, CreateMessage::new()
+                                    .content(format!("{username} quiere apuntarse como {role} en el evento de {channel} pero necesita tu aprobacion:\n{}", format_reasons(reasons)))
+                                    .button(CreateButton::new(approval_id("approve", original_message.channel_id, original_message.id, player.id, role))
+                                        .label("Aprobar").style(ButtonStyle::Success))
+                                    .button(CreateButton::new(approval_id("deny", original_message.channel_id, original_message.id, player.id, role))
+                                        .label("Dejar en reserva").style(ButtonStyle::Danger))
+                                ).await?;
+                            } else {
+                                notify(discord, &store, event.leader, NotificationKind::Reserve, event.thread,
+                                       &format!("{username} no cumple los requisitos de titular y se ha movido a reserva en el evento de {channel}, flexible a: {}\n{}", flex_as_string.join(","), format_reasons(reasons))).await?;
+                            }
+                            super::feed::post_change(discord, &store, original_message.id, &event, Some(interaction.user.id),
+                                                     &format!("🪑 **{username}** se apunta como {} (queria {role})", EventRole::Reserve)).await?;
+                        }
+                        None => {
+                            let signed_role = event.add_player(role, player.clone());
+                            if signed_role != role && !player.flex.contains(&role) {
+                                player.flex.push(role);
+                            }
+                            store.signup_player(original_message.id, signed_role, &player).await?;
 
-                        dm.send_message(&ctx.http, CreateMessage::new()
-                            .content(format!("{username} se ha apuntado al evento en {channel} como {role}, y flexible a: {}", flex_as_string.join(",")))
-                        ).await?;
+                            let overlap_note = if overlapping.is_empty() { String::new() } else {
+                                format!("\n⚠️ A la misma hora esta como titular en: {}", overlapping.join(", "))
+                            };
+                            notify(discord, &store, event.leader, NotificationKind::Signup, event.thread,
+                                   &format!("{username} se ha apuntado al evento en {channel} como {signed_role}, y flexible a: {}{overlap_note}", flex_as_string.join(","))).await?;
+                            super::feed::post_change(discord, &store, original_message.id, &event, Some(interaction.user.id),
+                                                     &format!("✅ **{username}** se apunta como {signed_role}")).await?;
+                        }
                     }
 
-                    original_message.edit(&ctx.http, EditMessage::new().embed(event.embed())).await?;
-                    interaction.create_response(&ctx.http, CreateInteractionResponse::UpdateMessage(signup_msg(&member, event.notification_role, event.leader, event.kind))).await?;
+                    let verdict = verdict.or(quota_full.map(|reason| Verdict { outcome: RuleOutcome::Reserve, reasons: vec![reason] }));
+                    discord.edit_message(original_message.channel_id, original_message.id, EditMessage::new().embeds(event.embeds())).await?;
+                    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(
+                        signup_msg(&member, verdict.as_ref(), event.leader, &overlapping).button(super::swap::swap_button())
+                    )).await?;
                 }
             }
         }
@@ -81,6 +121,89 @@ pub async fn signup_event(interaction: &ComponentInteraction, ctx: &Context, poo
     Ok(())
 }
 
+/// Handles the leader answer to a starter signup that needed approval
+pub async fn signup_approval(interaction: &ComponentInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let parts: Vec<&str> = interaction.data.custom_id.split('_').collect();
+    let (approved, channel, message, user, role) = match parts.as_slice() {
+        [_, answer, channel, message, user, role] => (
+            *answer == "approve",
+            ChannelId::new(channel.parse()?),
+            MessageId::new(message.parse()?),
+            UserId::new(user.parse()?),
+            EventRole::from_partial_id(*role).ok_or(Error::UnknownRole(role.to_string()))?
+        ),
+        _ => return Err(Error::UnknownInteraction(interaction.data.custom_id.to_string()))
+    };
+
+    let mut event = store.get_event(message).await?;
+    let pending = event.roles.iter()
+        .find(|pr| pr.role == EventRole::Reserve)
+        .and_then(|pr| pr.players.iter().find(|p| p.id == user))
+        .cloned();
+
+    let answer = match pending {
+        Some(_) if event.leader != interaction.user.id => "Solo el lider del evento puede aprobar inscripciones".to_string(),
+        None => "El jugador ya no esta en reservas".to_string(),
+        Some(player) if !approved => {
+            discord.dm(user, CreateMessage::new()
+                .content(format!("El lider no ha aprobado tu inscripcion como {role} en el evento de {}, sigues en reservas", Mention::Channel(channel)))
+            ).await?;
+            format!("{} sigue en reservas", player.name)
+        }
+        Some(mut player) => {
+            player.flex.retain(|r| *r != role);
+            let signed_role = event.add_player(role, player.clone());
+            if signed_role != role {
+                player.flex.push(role);
+            }
+            store.signup_player(message, signed_role, &player).await?;
+            discord.edit_message(channel, message, EditMessage::new().embeds(event.embeds())).await?;
+
+            if signed_role == role {
+                notify(discord, &store, user, NotificationKind::Promotion, event.thread,
+                       &format!("El lider ha aprobado tu inscripcion, ya estas como {role} en el evento de {}", Mention::Channel(channel))).await?;
+            } else {
+                discord.dm(user, CreateMessage::new()
+                    .content(format!("El lider ha aprobado tu inscripcion pero {role} esta lleno, sigues en reservas en el evento de {}", Mention::Channel(channel)))
+                ).await?;
+            }
+            super::feed::post_change(discord, &store, message, &event, Some(interaction.user.id),
+                                     &format!("✅ **{}** aprobado como {signed_role}", player.name)).await?;
+            format!("{} apuntado como {signed_role}", player.name)
+        }
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+            .content(answer)
+            .components(vec![])
+    )).await
+}
+
+fn approval_id(answer: &str, channel: ChannelId, message: MessageId, user: UserId, role: EventRole) -> String {
+    format!("eligibility_{answer}_{channel}_{message}_{user}_{}", role.to_id())
+}
+
+/// Loads only the signup history the rules of the event need
+pub(crate) async fn signup_profile(store: &Store, event: &Event, member: &Member, player: &Player, message: MessageId, overlapping: Vec<String>) -> Result<SignupProfile> {
+    let rules = event.effective_rules();
+    let week_starter_signups = match event.datetime {
+        Some(datetime) if rules.iter().any(|r| matches!(r.condition, Condition::MaxStarterSignupsPerWeek(_))) =>
+            store.week_starter_signups(player.id, datetime, message).await?,
+        _ => 0
+    };
+    let attendance = if rules.iter().any(|r| matches!(r.condition, Condition::MinAttendance(_))) {
+        store.attendance(player.id).await?
+    } else { None };
+
+    Ok(SignupProfile { roles: member.roles.clone(), class: player.class, week_starter_signups, attendance, overlapping })
+}
+
+pub(super) fn format_reasons(reasons: &[String]) -> String {
+    reasons.iter().map(|r| format!("- {r}")).collect::<Vec<String>>().join("\n")
+}
+
 #[derive(Deserialize, Serialize)]
 struct SignupTemplate {
     roles: Vec<PlayersInRole>
@@ -138,30 +261,45 @@ fn update_flex_roles(flex_roles: Vec<String>) -> CreateInteractionResponse {
 }
 
 #[instrument]
-fn signup_msg(member: &Member, notification_role: Option<RoleId>, leader: UserId, event_kind: EventKind) -> CreateInteractionResponseMessage {
+/// `overlapping` are the events at the same time where the player is already a starter
+fn signup_msg(member: &Member, verdict: Option<&Verdict>, leader: UserId, overlapping: &[String]) -> CreateInteractionResponseMessage {
     // Role Escudero
     let tax = if member.roles.contains(&RoleId::new(592733654996746253)) {"3"} else {"10"};
     info!("Member {} signed in with roles: {:?}", member.display_name(), member.roles);
 
     let rules_channel = Mention::Channel(ChannelId::new(1004447678689714197)).to_string();
     let frac = format!("Recuerda que __si no eres reserva__ y faltas de manera __injustificada__ deberas ingresar {tax}k al banco como penalización tal y como indican las {rules_channel}");
-    let embed = if notification_role.is_some_and(|r| !member.roles.contains(&r)) {
-        let notification_role = Mention::Role(notification_role.unwrap()).to_string();
-        CreateEmbed::new()
+    let leader = Mention::User(leader);
+    let embed = match verdict {
+        Some(Verdict { outcome: RuleOutcome::Reserve, reasons }) => CreateEmbed::new()
             .title("Apuntado como reserva porque faltan requisitos:")
             .description(format!(r#"
-__**Para poder apuntarte como titular deberas formar parte de {notification_role}**__, consulta los requisitos de rosters de la norma **1.5** en {rules_channel}
-Si crees que cumples los requisitos o quieres mas informacion consultar con el lider del evento {}
-
-{frac}"#, Mention::User(leader)))
-    } else if notification_role.is_none() && initiation_check(member, event_kind) {
-        CreateEmbed::new()
-            .title("Apuntado como reserva porque no eres de iniciacion")
-            .description("Al ser titular de un roster intermedio o avanzado no puedes apuntarte como titular a un evento de iniciacion")
-    } else {
-        CreateEmbed::new()
+{}
+Si crees que cumples los requisitos o quieres mas informacion consultar con el lider del evento {leader}
+
+{frac}"#, format_reasons(reasons))),
+        Some(Verdict { outcome: RuleOutcome::Approval, reasons }) => CreateEmbed::new()
+            .title("Apuntado como reserva a la espera de aprobacion:")
+            .description(format!(r#"
+{}
+El lider del evento {leader} decidira si entras como titular, te avisaremos por mensaje privado
+
+{frac}"#, format_reasons(reasons))),
+        Some(Verdict { outcome: RuleOutcome::Reject, reasons }) => CreateEmbed::new()
+            .title("No puedes apuntarte como titular:")
+            .description(format!(r#"
+{}
+Puedes apuntarte como reserva o consultar con el lider del evento {leader}"#, format_reasons(reasons))),
+        None if overlapping.is_empty() => CreateEmbed::new()
             .title("Ya estas dentro!")
-            .description(frac)
+            .description(frac),
+        None => CreateEmbed::new()
+            .title("Ya estas dentro!")
+            .description(format!(r#"
+⚠️ A la misma hora ya estas como titular en: {}
+Avisa al lider si no vas a poder asistir a alguno de ellos
+
+{frac}"#, overlapping.iter().map(|t| format!("**{t}**")).collect::<Vec<String>>().join(", ")))
     };
 
     CreateInteractionResponseMessage::new()
@@ -170,10 +308,3 @@ Si crees que cumples los requisitos o quieres mas informacion consultar con el l
         .embed(embed) // #normas
         .components(vec![])
 }
-
-fn initiation_check(member: &Member, event_kind: EventKind) -> bool {
-    match event_kind {
-        EventKind::Trial => member.roles.contains(&RoleId::new(1201977765579202590)) || member.roles.contains(&RoleId::new(1201977671979122698)),
-        EventKind::PvP => member.roles.contains(&RoleId::new(1201977190791512095))
-    }
-}
\ No newline at end of file
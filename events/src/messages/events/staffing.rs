@@ -0,0 +1,66 @@
+use serenity::all::{ChannelId, ComponentInteraction, CreateInteractionResponse, CreateInteractionResponseMessage, EditMessage, Mention, MessageId};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{check_rules, EventRole, NotificationKind, Player, Verdict};
+use crate::tasks::notifications::notify;
+use crate::tasks::staffing::join_buttons;
+use crate::prelude::*;
+
+/// Join button of the open slot alerts, signs the member straight into the missing role if they meet the rules
+pub async fn staffing_join(interaction: &ComponentInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let parts: Vec<&str> = interaction.data.custom_id.split('_').collect();
+    let (channel, message, role) = match parts.as_slice() {
+        [_, _, channel, message, role] => (
+            ChannelId::new(channel.parse()?),
+            MessageId::new(message.parse()?),
+            EventRole::from_partial_id(*role).ok_or(Error::UnknownRole(role.to_string()))?
+        ),
+        _ => return Err(Error::UnknownInteraction(interaction.data.custom_id.to_string()))
+    };
+    let member = interaction.member.clone().ok_or(Error::UnknownInteraction("staffing join outside guild".to_string()))?;
+
+    let mut event = store.get_event(message).await?;
+    let current = event.player(interaction.user.id).map(|(role, player)| (role, player.clone()));
+    let open = event.open_slots();
+    let answer = match current {
+        _ if !open.iter().any(|(r, _)| *r == role) => format!("El hueco de {role} ya esta cubierto, gracias!"),
+        Some((signed, _)) if !signed.is_backup_role() => format!("Ya estas apuntado como {signed}"),
+        current => {
+            let mut player = current.map(|(_, p)| p).unwrap_or(Player::new(interaction.user.id, member.display_name()));
+            let verdict = if event.leader == interaction.user.id { None } else {
+                let profile = super::signup::signup_profile(&store, &event, &member, &player, message, vec![]).await?;
+                check_rules(&event.effective_rules(), &profile)
+            };
+
+            match verdict {
+                Some(Verdict { reasons, .. }) => format!("No puedes entrar como {role}:\n{}\nApuntate desde el evento en {} o habla con {}",
+                                                         super::signup::format_reasons(&reasons), Mention::Channel(channel), Mention::User(event.leader)),
+                None => {
+                    player.flex.retain(|r| *r != role);
+                    let signed_role = event.add_player(role, player.clone());
+                    store.signup_player(message, signed_role, &player).await?;
+                    super::render_event(discord, Some(channel), message, &event).await;
+                    super::feed::post_change(discord, &store, message, &event, Some(interaction.user.id),
+                                             &format!("🚑 **{}** cubre el hueco de {signed_role}", player.name)).await?;
+                    notify(discord, &store, event.leader, NotificationKind::Signup, event.thread,
+                           &format!("{} cubre el hueco de {signed_role} en el evento de {}", player.name, Mention::Channel(channel))).await?;
+
+                    // The alert keeps only the buttons of the roles still open
+                    let open = event.open_slots();
+                    if !open.iter().any(|(r, _)| *r == role) {
+                        discord.edit_message(interaction.channel_id, interaction.message.id, EditMessage::new()
+                            .components(join_buttons(channel, message, &open))).await?;
+                    }
+                    format!("Ya estas dentro como {signed_role} en el evento de {}", Mention::Channel(channel))
+                }
+            }
+        }
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .content(answer)
+    )).await
+}
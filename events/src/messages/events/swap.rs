@@ -0,0 +1,185 @@
+use serenity::all::{ButtonStyle, ComponentInteraction, ComponentInteractionDataKind, CreateButton, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, CreateMessage, CreateSelectMenu, CreateSelectMenuKind, CreateSelectMenuOption, EditMessage, Mention, Message, MessageId, UserId};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{check_rules, Event, EventRole, Player};
+use crate::prelude::*;
+
+pub(super) fn swap_button() -> CreateButton {
+    CreateButton::new("swap_propose")
+        .label("Cambiar rol con otro jugador")
+        .style(ButtonStyle::Secondary)
+        .emoji('🔁')
+}
+
+/// Lists the other signed players so the requester can pick who to swap with
+pub(super) async fn select_swap_target(interaction: &ComponentInteraction, event_message: &Message, discord: &impl Discord, store: &Store) -> Result<()> {
+    let event = store.get_event(event_message.id).await?;
+    let candidates: Vec<CreateSelectMenuOption> = event.roles.iter()
+        .filter(|pr| pr.role != EventRole::Absent)
+        .flat_map(|pr| pr.players.iter()
+            .filter(|p| p.id != interaction.user.id)
+            .map(move |p| CreateSelectMenuOption::new(format!("{} ({})", p.name, pr.role), p.id.to_string())))
+        .take(25)
+        .collect();
+
+    let response = match event.role_of(interaction.user.id) {
+        Some(role) if role != EventRole::Absent && !candidates.is_empty() => CreateInteractionResponseMessage::new()
+            .embed(CreateEmbed::new()
+                .title("Cambiar rol")
+                .description(format!("Ahora mismo estas en {role}, elige con quien quieres cambiarte")))
+            .select_menu(CreateSelectMenu::new("swap_target", CreateSelectMenuKind::String { options: candidates })
+                .placeholder("Jugador")),
+        Some(role) if role != EventRole::Absent => CreateInteractionResponseMessage::new()
+            .embed(CreateEmbed::new().title("Cambiar rol").description("No hay nadie mas apuntado con quien cambiarse"))
+            .components(vec![]),
+        _ => CreateInteractionResponseMessage::new()
+            .embed(CreateEmbed::new().title("Cambiar rol").description("Tienes que estar apuntado para poder cambiar tu rol"))
+            .components(vec![])
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(response)).await
+}
+
+/// Saves the swap request and asks the target by DM
+pub(super) async fn request_swap(interaction: &ComponentInteraction, event_message: &Message, discord: &impl Discord, store: &Store) -> Result<()> {
+    let target = match &interaction.data.kind {
+        ComponentInteractionDataKind::StringSelect { values } => values.first().map(|v| v.parse::<u64>()).transpose()?.map(UserId::new),
+        _ => None
+    }.ok_or(Error::UnknownInteraction(interaction.data.custom_id.to_string()))?;
+
+    let event = store.get_event(event_message.id).await?;
+    let (Some(requester_role), Some(target_role)) = (event.role_of(interaction.user.id), event.role_of(target)) else {
+        return discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(
+            CreateInteractionResponseMessage::new()
+                .embed(CreateEmbed::new().title("Cambiar rol").description("Alguno de los dos ya no esta apuntado al evento"))
+                .components(vec![])
+        )).await;
+    };
+
+    let id = store.create_swap_request(event_message.id, event_message.channel_id, (interaction.user.id, requester_role), (target, target_role)).await?;
+    let requester = player_name(&event, interaction.user.id);
+    discord.dm(target, CreateMessage::new()
+        .embed(CreateEmbed::new()
+            .title(format!("Cambio de rol para {}", event.title))
+            .description(format!("{requester} quiere cambiar su puesto en {requester_role} por el tuyo en {target_role} en el evento de {}\n{}",
+                                 Mention::Channel(event_message.channel_id), event_message.link())))
+        .button(CreateButton::new(format!("swap_answer_accept_{id}")).label("Aceptar").style(ButtonStyle::Success))
+        .button(CreateButton::new(format!("swap_answer_decline_{id}")).label("Rechazar").style(ButtonStyle::Danger))
+    ).await?;
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+            .embed(CreateEmbed::new()
+                .title("Solicitud enviada")
+                .description(format!("Le hemos preguntado a {}, te avisaremos con su respuesta", player_name(&event, target))))
+            .components(vec![])
+    )).await
+}
+
+/// Handles the accept/decline buttons of the DM sent to the swap target
+pub async fn swap_response(interaction: &ComponentInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let custom_id = &interaction.data.custom_id;
+    let id = custom_id.rsplit('_').next().unwrap_or_default().parse::<i32>()?;
+    let accepted = custom_id.starts_with("swap_answer_accept");
+
+    let request = match store.get_swap_request(id).await? {
+        Some(request) if request.target == interaction.user.id => request,
+        _ => return answer(interaction, discord, "Esta solicitud ya no es valida").await
+    };
+    store.remove_swap_request(id).await?;
+    let mut event = store.get_event(request.message).await?;
+    let target = player_name(&event, request.target);
+    let channel = Mention::Channel(request.channel);
+
+    if !accepted {
+        discord.dm(request.requester, CreateMessage::new()
+            .content(format!("{target} ha rechazado cambiarse contigo en el evento de {channel}"))
+        ).await?;
+        return answer(interaction, discord, "Solicitud rechazada").await;
+    }
+
+    if event.role_of(request.requester) != Some(request.requester_role) || event.role_of(request.target) != Some(request.target_role) {
+        discord.dm(request.requester, CreateMessage::new()
+            .content(format!("No se ha podido hacer el cambio con {target} en el evento de {channel}, alguno de los dos ha cambiado de rol mientras tanto"))
+        ).await?;
+        return answer(interaction, discord, "Alguno de los dos ha cambiado de rol mientras tanto, el cambio ya no es posible").await;
+    }
+
+    let Some(swapped) = event.swap_players(request.requester, request.target) else {
+        return answer(interaction, discord, "El cambio ya no es posible").await;
+    };
+    let reasons = swap_blockers(discord, &store, &event, request.message, &swapped, [request.requester_role, request.target_role]).await?;
+    if !reasons.is_empty() {
+        let reasons = super::signup::format_reasons(&reasons);
+        discord.dm(request.requester, CreateMessage::new()
+            .content(format!("No se ha podido hacer el cambio con {target} en el evento de {channel}:\n{reasons}"))
+        ).await?;
+        return answer(interaction, discord, &format!("El cambio no es posible:\n{reasons}")).await;
+    }
+    store.swap_players(request.message, &swapped).await?;
+    discord.edit_message(request.channel, request.message, EditMessage::new().embeds(event.embeds())).await?;
+
+    let requester = player_name(&event, request.requester);
+    discord.dm(request.requester, CreateMessage::new()
+        .content(format!("{target} ha aceptado, ahora estas en {} en el evento de {channel}", request.target_role))
+    ).await?;
+    discord.dm(event.leader, CreateMessage::new()
+        .content(format!("{requester} ({}) y {target} ({}) se han intercambiado el rol en el evento de {channel}", request.target_role, request.requester_role))
+    ).await?;
+
+    super::feed::post_change(discord, &store, request.message, &event, Some(request.target),
+                             &format!("🔁 **{requester}** pasa a {} y **{target}** a {}", request.target_role, request.requester_role)).await?;
+
+    answer(interaction, discord, &format!("Hecho! ahora estas en {} en el evento de {channel}", request.requester_role)).await
+}
+
+/// Why the swap can't be done, whoever leaves the reserve to be a starter has to fit the class quotas
+/// and meet the rules like any signup, `event` has the players already swapped
+async fn swap_blockers(discord: &impl Discord, store: &Store, event: &Event, message: MessageId, swapped: &[(EventRole, Player); 2], previous: [EventRole; 2]) -> Result<Vec<String>> {
+    let rules = event.effective_rules();
+    let mut reasons = vec![];
+    for ((role, player), previous) in swapped.iter().zip(previous) {
+        if role.is_backup_role() || !previous.is_backup_role() {
+            continue;
+        }
+        if let Some(quota) = event.class_quota_full(player) {
+            reasons.push(quota.full_explanation_es());
+        }
+        if player.id == event.leader || rules.is_empty() {
+            continue;
+        }
+
+        let member = match crate::tasks::guild() {
+            Some(guild) => discord.member(guild, player.id).await.ok(),
+            None => None
+        };
+        match member {
+            Some(member) => {
+                let overlapping = super::overlap::overlapping_signups(store, event, message, player.id).await?;
+                let profile = super::signup::signup_profile(store, event, &member, player, message, overlapping).await?;
+                if let Some(verdict) = check_rules(&rules, &profile) {
+                    reasons.extend(verdict.reasons);
+                }
+            }
+            None => reasons.push(format!("No se han podido comprobar los requisitos de titular de {}", player.name))
+        }
+    }
+    Ok(reasons)
+}
+
+async fn answer(interaction: &ComponentInteraction, discord: &impl Discord, description: &str) -> Result<()> {
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::UpdateMessage(
+        CreateInteractionResponseMessage::new()
+            .embed(CreateEmbed::new().title("Cambio de rol").description(description))
+            .components(vec![])
+    )).await
+}
+
+fn player_name(event: &crate::events::Event, user: UserId) -> String {
+    event.roles.iter()
+        .flat_map(|pr| pr.players.iter())
+        .find(|p| p.id == user)
+        .map(|p| p.name.clone())
+        .unwrap_or(Mention::User(user).to_string())
+}
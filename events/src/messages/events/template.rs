@@ -0,0 +1,59 @@
+use serenity::all::{CommandInteraction, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedValue};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{EventKind, Template};
+use crate::prelude::*;
+
+pub async fn save_template(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let (mut name, mut kind, mut code) = (String::new(), EventKind::Trial, String::new());
+    if let Some(ResolvedValue::SubCommand(options)) = interaction.data.options().first().map(|o| o.value.clone()) {
+        for option in options {
+            match (option.name, option.value) {
+                ("name", ResolvedValue::String(value)) => name = value.trim().to_string(),
+                ("kind", ResolvedValue::String(value)) => kind = EventKind::from_partial_id(value).unwrap_or(EventKind::Trial),
+                ("code", ResolvedValue::String(value)) => code = value.to_string(),
+                _ => {}
+            }
+        }
+    }
+
+    let embed = match Template::from_code(&name, kind, &code) {
+        Ok(mut template) if template.roles.iter().all(|pr| kind.roles().contains(&pr.role)) => {
+            template.owner = Some(interaction.user.id);
+            if store.save_template(&template, manages_events(interaction)).await? {
+                CreateEmbed::new()
+                    .title("Plantilla guardada")
+                    .description(format!("Usa `template:{name}` al crear eventos de {kind}"))
+            } else {
+                CreateEmbed::new()
+                    .title("Nombre ocupado")
+                    .description(format!("Ya hay otra plantilla `{name}` guardada por otra persona, elige otro nombre"))
+            }
+        }
+        Ok(_) => CreateEmbed::new()
+            .title("Plantilla no valida")
+            .description(format!("El codigo no corresponde a un evento de {kind}")),
+        Err(_) => CreateEmbed::new()
+            .title("Plantilla no valida")
+            .description("No se ha podido leer el codigo de plantilla")
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .embed(embed)
+    )).await
+}
+
+/// Officers with `MANAGE_EVENTS`, the permission `/roster` asks for, can change the templates of anyone
+pub(super) fn manages_events(interaction: &CommandInteraction) -> bool {
+    interaction.member.as_ref()
+        .and_then(|m| m.permissions)
+        .is_some_and(|p| p.manage_events())
+}
+
+/// Templates are changed by whoever saved them or the officers
+pub(super) fn can_edit_template(template: &Template, interaction: &CommandInteraction) -> bool {
+    template.owner == Some(interaction.user.id) || manages_events(interaction)
+}
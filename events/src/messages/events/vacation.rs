@@ -0,0 +1,80 @@
+use serenity::all::{AutocompleteChoice, CommandInteraction, CreateAutocompleteResponse, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedValue};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{local_today, parse_day, vacation_window};
+use crate::prelude::*;
+use super::absence::{apply_vacation, vacation_summary};
+
+/// `/vacation add|list|cancel`, away periods where the player is signed as absent to every event
+pub async fn vacation_command(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let guild = interaction.guild_id.ok_or(Error::UnknownInteraction("vacation outside guild".to_string()))?;
+    let (mut from, mut to, mut reason, mut period) = (None, None, None, None);
+    if let Some(ResolvedValue::SubCommand(options)) = interaction.data.options().first().map(|o| o.value.clone()) {
+        for option in options {
+            match (option.name, option.value) {
+                ("from", ResolvedValue::String(value)) => from = Some(value.to_string()),
+                ("to", ResolvedValue::String(value)) => to = Some(value.to_string()),
+                ("reason", ResolvedValue::String(value)) => reason = Some(value.trim().to_string()),
+                ("period", ResolvedValue::String(value)) => period = value.parse::<i32>().ok(),
+                _ => {}
+            }
+        }
+    }
+
+    let embed = match super::subcommand(interaction) {
+        Some("add") => {
+            let today = local_today();
+            let first = parse_day(from.as_deref().unwrap_or_default(), today)?;
+            let last = parse_day(to.as_deref().unwrap_or_default(), today)?;
+            if last < first {
+                CreateEmbed::new().title("Fechas no validas").description("La fecha de fin es anterior a la de inicio")
+            } else {
+                let name = interaction.member.as_ref().map(|m| m.display_name().to_string()).unwrap_or(interaction.user.name.clone());
+                let (start, end) = vacation_window(first, last);
+                let vacation = store.add_vacation(interaction.user.id, &name, start, end, reason.as_deref().unwrap_or_default()).await?;
+                let events = apply_vacation(discord, &store, guild, &vacation, None).await?;
+                CreateEmbed::new().title("Vacaciones guardadas").description(vacation_summary(&vacation, &events))
+            }
+        }
+        Some("list") => {
+            let vacations = store.get_vacations(Some(interaction.user.id)).await?;
+            CreateEmbed::new()
+                .title("Tus vacaciones")
+                .description(if vacations.is_empty() {
+                    "No tienes vacaciones programadas, usa `/vacation add`".to_string()
+                } else {
+                    vacations.iter().map(|v| format!("- {}", v.label_es())).collect::<Vec<String>>().join("\n")
+                })
+        }
+        Some("cancel") => match period {
+            Some(id) if store.remove_vacation(id, interaction.user.id).await? => CreateEmbed::new()
+                .title("Vacaciones canceladas")
+                .description("Las ausencias ya apuntadas se mantienen, apuntate de nuevo a los eventos a los que si vayas a ir"),
+            _ => CreateEmbed::new()
+                .title("Vacaciones no encontradas")
+                .description("Elige las vacaciones de la lista")
+        },
+        _ => return Err(Error::UnknownInteraction(interaction.data.name.to_string()))
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .embed(embed)
+    )).await
+}
+
+/// Own vacations for `/vacation cancel`
+pub async fn vacation_autocomplete(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let choices = store.get_vacations(Some(interaction.user.id)).await?
+        .into_iter()
+        .take(25)
+        .map(|v| AutocompleteChoice::new(v.label_es().chars().take(100).collect::<String>(), v.id.to_string()))
+        .collect();
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Autocomplete(
+        CreateAutocompleteResponse::new().set_choices(choices)
+    )).await
+}
@@ -0,0 +1,90 @@
+use serenity::all::{ChannelId, CreateEmbed, CreateInteractionResponse, CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage, Interaction, Mention, UserId};
+use tracing::{error, warn};
+use crate::discord::Discord;
+use crate::metrics;
+use crate::prelude::*;
+
+/// Embed descriptions can't be longer than this
+const MAX_DETAIL: usize = 3900;
+
+/// A failed handler, what the member is told and what the admins get to see
+#[derive(Debug)]
+pub struct Failure {
+    pub variant: &'static str,
+    /// Explanation for the member, `None` for internal errors
+    pub message_es: Option<String>,
+    pub detail: String
+}
+
+impl Failure {
+    pub fn new(variant: &'static str, message_es: Option<String>, detail: String) -> Self {
+        Failure { variant, message_es, detail }
+    }
+}
+
+impl From<&Error> for Failure {
+    fn from(error: &Error) -> Self {
+        Failure::new(error.into(), error.user_message_es(), format!("{error:#?}"))
+    }
+}
+
+/// Logs a failed handler under a new correlation id, tells the member what happened and
+/// forwards internal errors to `admin_log`. Returns the correlation id
+pub async fn report_failure<D: Discord + ?Sized>(discord: &D, interaction: Option<&Interaction>, handler: &str, failure: Failure, admin_log: Option<ChannelId>) -> String {
+    let id = format!("{:08x}", rand::random::<u32>());
+    error!("[{id}] {handler}: {}", failure.detail);
+    metrics::record_failure(failure.variant);
+
+    let user = interaction.and_then(interaction_user);
+    if let Some(interaction) = interaction {
+        answer(discord, interaction, &id, &failure).await;
+    }
+
+    if let (None, Some(channel)) = (&failure.message_es, admin_log) {
+        let mut embed = CreateEmbed::new()
+            .title(format!("⚠️ Error interno `{id}`"))
+            .description(format!("```\n{}\n```", failure.detail.chars().take(MAX_DETAIL).collect::<String>()))
+            .field("Manejador", handler, true)
+            .field("Tipo", failure.variant, true);
+        if let Some(user) = user {
+            embed = embed.field("Usuario", Mention::User(user).to_string(), true);
+        }
+        if let Err(why) = discord.send_message(channel, CreateMessage::new().embed(embed)).await {
+            warn!("[{id}] Error not forwarded to {channel}: {why:?}");
+        }
+    }
+
+    id
+}
+
+/// Ephemeral answer with the correlation id, as a followup when the interaction was already answered
+async fn answer<D: Discord + ?Sized>(discord: &D, interaction: &Interaction, id: &str, failure: &Failure) {
+    let (interaction_id, token) = match interaction {
+        Interaction::Command(c) => (c.id, &c.token),
+        Interaction::Component(c) => (c.id, &c.token),
+        Interaction::Modal(m) => (m.id, &m.token),
+        // Autocomplete can only answer with choices
+        _ => return
+    };
+
+    let content = format!("{}\nCodigo de error: `{id}`", failure.message_es.as_deref()
+        .unwrap_or("Ha ocurrido un error inesperado, ya se ha avisado a los administradores"));
+    let response = CreateInteractionResponse::Message(CreateInteractionResponseMessage::new()
+        .ephemeral(true)
+        .content(&content));
+    if discord.create_response(interaction_id, token, response).await.is_err() {
+        let followup = CreateInteractionResponseFollowup::new().ephemeral(true).content(content);
+        if let Err(why) = discord.create_followup(token, followup).await {
+            warn!("[{id}] Error not reported to the member: {why:?}");
+        }
+    }
+}
+
+fn interaction_user(interaction: &Interaction) -> Option<UserId> {
+    match interaction {
+        Interaction::Command(c) | Interaction::Autocomplete(c) => Some(c.user.id),
+        Interaction::Component(c) => Some(c.user.id),
+        Interaction::Modal(m) => Some(m.user.id),
+        _ => None
+    }
+}
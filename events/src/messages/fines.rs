@@ -0,0 +1,250 @@
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use serenity::all::{Attachment, CommandInteraction, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, Mention, ResolvedValue, RoleId, UserId};
+use sqlx::PgPool;
+use crate::discord::Discord;
+use crate::events::{format_gold, parse_bank_export, parse_gold, FineStatus, Ledger, PenaltyRule, Reconciliation};
+use crate::prelude::*;
+use crate::tasks::fines::import_bank_export;
+
+/// Fines listed in a ledger, the embed can't be longer
+const MAX_LEDGER_LINES: usize = 25;
+/// Biggest guild bank export accepted, months of history take a few hundred KB
+const MAX_EXPORT_SIZE: u32 = 2 * 1024 * 1024;
+/// Characters of an embed field value
+const MAX_FIELD_LENGTH: usize = 1024;
+
+lazy_static! {
+    static ref TREASURER_ROLE: Mutex<Option<RoleId>> = Mutex::new(None);
+}
+
+/// Role of the members that manage the fines besides the server managers
+pub fn set_treasurer_role(role: Option<RoleId>) {
+    *TREASURER_ROLE.lock().unwrap() = role;
+}
+
+#[derive(Default)]
+struct FinesOptions {
+    player: Option<UserId>,
+    amount: Option<String>,
+    fine: Option<i32>,
+    role: Option<RoleId>,
+    no_show: Option<String>,
+    late_absence: Option<String>,
+    file: Option<Attachment>
+}
+
+/// Entrypoint for `/fines`, the treasurers list, record payments or import them from the guild bank history,
+/// waive fines and set the penalties, everyone can check their own fines
+pub async fn fines_command(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    let subcommand = interaction.data.options.first().map(|o| o.name.clone()).unwrap_or_default();
+    let options = parse_options(interaction);
+
+    let embed = match subcommand.as_str() {
+        "me" => ledger_embed(&store, interaction.user.id, "Tus multas").await?,
+        _ if !is_treasurer(interaction) => CreateEmbed::new()
+            .title("Solo para tesoreros")
+            .description("Usa `/fines me` para ver tus multas"),
+        "list" => match options.player {
+            Some(player) => ledger_embed(&store, player, "Multas").await?,
+            None => outstanding_embed(&store).await?
+        },
+        "pay" => match options.player {
+            Some(player) => {
+                let ledger = Ledger::new(player, &store.get_fines(Some(player)).await?, &store.get_fine_payments(Some(player)).await?);
+                let amount = match options.amount.as_deref() {
+                    Some(amount) => parse_gold(amount)?,
+                    None => ledger.outstanding()
+                };
+                if amount == 0 {
+                    CreateEmbed::new()
+                        .title("Sin multas pendientes")
+                        .description(format!("{} no debe nada", Mention::User(player)))
+                } else {
+                    store.add_fine_payment(player, amount, Some(interaction.user.id)).await?;
+                    ledger_embed(&store, player, "Pago registrado").await?
+                        .description(format!("{} ingresados por {}", format_gold(amount), Mention::User(player)))
+                }
+            }
+            None => CreateEmbed::new().title("Falta el jugador")
+        },
+        "waive" => match options.fine {
+            Some(id) => match store.waive_fine(id, interaction.user.id).await? {
+                Some(fine) => ledger_embed(&store, fine.user, "Multa perdonada").await?
+                    .description(format!("#{id} de {} ({}) en **{}**", Mention::User(fine.user), format_gold(fine.amount), fine.event_title)),
+                None => CreateEmbed::new()
+                    .title("Multa no encontrada")
+                    .description(format!("No hay ninguna multa #{id} sin perdonar"))
+            },
+            None => CreateEmbed::new().title("Falta el numero de la multa")
+        },
+        "rule" => {
+            let rules = store.penalty_rules().await?;
+            let current = rules.iter().find(|r| r.role == options.role).cloned()
+                .unwrap_or(PenaltyRule { role: options.role, no_show: 0, late_absence: 0 });
+            let rule = PenaltyRule {
+                role: options.role,
+                no_show: options.no_show.as_deref().map(parse_gold).transpose()?.unwrap_or(current.no_show),
+                late_absence: options.late_absence.as_deref().map(parse_gold).transpose()?.unwrap_or(current.late_absence)
+            };
+            store.set_penalty_rule(&rule).await?;
+            rules_embed(&store.penalty_rules().await?)
+        }
+        "import" => match options.file {
+            Some(file) if file.size <= MAX_EXPORT_SIZE => {
+                let text = String::from_utf8_lossy(&file.download().await?).to_string();
+                let report = import_bank_export(&store, &parse_bank_export(&text), Some(interaction.user.id)).await?;
+                reconciliation_embed(&report)
+            }
+            Some(_) => CreateEmbed::new()
+                .title("Archivo no valido")
+                .description(format!("El historial ocupa mas de {}MB, exporta menos dias", MAX_EXPORT_SIZE / 1024 / 1024)),
+            None => CreateEmbed::new().title("Falta el historial del banco")
+        },
+        _ => return Err(Error::UnknownInteraction(interaction.data.name.to_string()))
+    };
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .embed(embed)
+    )).await
+}
+
+fn is_treasurer(interaction: &CommandInteraction) -> bool {
+    let role = *TREASURER_ROLE.lock().unwrap();
+    interaction.member.as_ref().is_some_and(|member| {
+        member.permissions.is_some_and(|p| p.manage_guild()) || role.is_some_and(|role| member.roles.contains(&role))
+    })
+}
+
+fn parse_options(interaction: &CommandInteraction) -> FinesOptions {
+    let mut parsed = FinesOptions::default();
+    let Some(ResolvedValue::SubCommand(options)) = interaction.data.options().into_iter().next().map(|o| o.value) else { return parsed };
+    for option in options {
+        match (option.name, option.value) {
+            ("player", ResolvedValue::User(user, _)) => parsed.player = Some(user.id),
+            ("player", ResolvedValue::String(value)) => parsed.player = value.parse::<u64>().ok().filter(|id| *id != 0).map(UserId::new),
+            ("amount", ResolvedValue::String(value)) => parsed.amount = Some(value.to_string()),
+            ("fine", ResolvedValue::Integer(value)) => parsed.fine = i32::try_from(value).ok(),
+            ("fine", ResolvedValue::String(value)) => parsed.fine = value.trim_start_matches('#').parse().ok(),
+            ("role", ResolvedValue::Role(role)) => parsed.role = Some(role.id),
+            ("no_show", ResolvedValue::String(value)) => parsed.no_show = Some(value.to_string()),
+            ("late_absence", ResolvedValue::String(value)) => parsed.late_absence = Some(value.to_string()),
+            ("file", ResolvedValue::Attachment(file)) => parsed.file = Some(file.clone()),
+            _ => {}
+        }
+    }
+    parsed
+}
+
+async fn ledger_embed(store: &Store, user: UserId, title: &str) -> Result<CreateEmbed> {
+    let ledger = Ledger::new(user, &store.get_fines(Some(user)).await?, &store.get_fine_payments(Some(user)).await?);
+    let lines = if ledger.fines.is_empty() { "Sin multas".to_string() } else {
+        ledger.fines.iter().rev()
+            .take(MAX_LEDGER_LINES)
+            .map(|(fine, status)| {
+                let status = match status {
+                    FineStatus::Outstanding(left) => format!("pendiente {}", format_gold(*left)),
+                    FineStatus::Paid => "pagada".to_string(),
+                    FineStatus::Waived => "perdonada".to_string()
+                };
+                format!("`#{}` <t:{}:d> **{}** — {}: {} ({status})", fine.id, fine.created.timestamp(), fine.event_title,
+                        fine.reason.label_es(), format_gold(fine.amount))
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+
+    let mut embed = CreateEmbed::new()
+        .title(title)
+        .field("Jugador", Mention::User(user).to_string(), true)
+        .field("Pendiente", format_gold(ledger.outstanding()), true)
+        .field("Pagado", format_gold(ledger.paid), true)
+        .field("Detalle", lines, false);
+    if ledger.credit() > 0 {
+        embed = embed.footer(CreateEmbedFooter::new(format!("A favor: {}", format_gold(ledger.credit()))));
+    }
+    Ok(embed)
+}
+
+async fn outstanding_embed(store: &Store) -> Result<CreateEmbed> {
+    let ledgers = Ledger::all(&store.get_fines(None).await?, &store.get_fine_payments(None).await?);
+    let mut debtors: Vec<&Ledger> = ledgers.iter().filter(|l| l.outstanding() > 0).collect();
+    debtors.sort_by_key(|l| std::cmp::Reverse(l.outstanding()));
+    let total: u32 = debtors.iter().map(|l| l.outstanding()).sum();
+
+    let lines = if debtors.is_empty() { "Nadie debe nada 🎉".to_string() } else {
+        debtors.iter()
+            .take(MAX_LEDGER_LINES)
+            .map(|l| format!("- {} ({}): {}", l.name().unwrap_or_default(), Mention::User(l.user), format_gold(l.outstanding())))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    Ok(CreateEmbed::new()
+        .title("Multas pendientes")
+        .description(format!("{lines}\n\n**Total:** {}", format_gold(total))))
+}
+
+fn rules_embed(rules: &[PenaltyRule]) -> CreateEmbed {
+    let lines = rules.iter()
+        .map(|r| format!("- {}: no presentado {}, ausencia de ultima hora {}",
+                         r.role.map(|role| Mention::Role(role).to_string()).unwrap_or("Resto".to_string()),
+                         format_gold(r.no_show), format_gold(r.late_absence)))
+        .collect::<Vec<String>>()
+        .join("\n");
+    CreateEmbed::new()
+        .title("Multas por rol")
+        .description(format!("{lines}\nSe aplica la mas baja de los roles del jugador"))
+}
+
+fn reconciliation_embed(report: &Reconciliation) -> CreateEmbed {
+    let matched = report.matched.iter()
+        .map(|(d, user)| format!("- {} ({}): {}", d.account, Mention::User(*user), format_gold(d.amount)))
+        .collect();
+    let overpaid = report.overpaid.iter()
+        .map(|(d, user, applied)| format!("- {} ({}): {} de {}, sobran {}", d.account, Mention::User(*user),
+                                          format_gold(*applied), format_gold(d.amount), format_gold(d.amount - applied)))
+        .collect();
+    let unmatched = report.unmatched.iter()
+        .map(|d| format!("- {}: {} <t:{}:d>", d.account, format_gold(d.amount), d.at.timestamp()))
+        .collect();
+
+    let mut embed = CreateEmbed::new()
+        .title("Historial del banco importado")
+        .field("Pagos de multas", report_lines(matched), false)
+        .field("Por encima de lo que debian", report_lines(overpaid), false)
+        .field("Cuentas sin registrar", report_lines(unmatched), false);
+    let mut notes = vec![];
+    if report.duplicated > 0 {
+        notes.push(format!("{} ingresos ya importados", report.duplicated));
+    }
+    if !report.invalid.is_empty() {
+        notes.push(format!("Lineas no reconocidas: {}", report.invalid.iter().take(20).map(|l| l.to_string()).collect::<Vec<String>>().join(", ")));
+    }
+    if !notes.is_empty() {
+        embed = embed.footer(CreateEmbedFooter::new(notes.join(" · ")));
+    }
+    embed
+}
+
+/// Lines of a report field cut to the embed limits
+fn report_lines(lines: Vec<String>) -> String {
+    if lines.is_empty() {
+        return "Ninguno".to_string();
+    }
+    let mut value = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let more = format!("\n... y {} mas", lines.len() - i);
+        if value.len() + line.len() + more.len() + 1 > MAX_FIELD_LENGTH {
+            value.push_str(&more);
+            break;
+        }
+        if !value.is_empty() {
+            value.push('\n');
+        }
+        value.push_str(line);
+    }
+    value
+}
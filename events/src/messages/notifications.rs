@@ -0,0 +1,36 @@
+use serenity::all::{CommandInteraction, CreateEmbed, CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseMessage, ResolvedValue};
+use sqlx::PgPool;
+use strum::IntoEnumIterator;
+use crate::discord::Discord;
+use crate::events::{NotificationChannel, NotificationFrequency, NotificationKind};
+use crate::prelude::*;
+
+/// `/notifications` changes how often and where the event notices arrive, without options shows the current ones
+pub async fn notifications_command(interaction: &CommandInteraction, discord: &impl Discord, pool: PgPool) -> Result<()> {
+    let store = Store::new(pool);
+    for option in interaction.data.options() {
+        let ResolvedValue::String(value) = option.value else { continue };
+        if option.name == "frequency" {
+            if let Some(frequency) = NotificationFrequency::from_id(value) {
+                store.set_notification_frequency(interaction.user.id, frequency).await?;
+            }
+        } else if let (Some(kind), Some(channel)) = (NotificationKind::iter().find(|k| k.to_id() == option.name), NotificationChannel::from_id(value)) {
+            store.set_notification_channel(interaction.user.id, kind, channel).await?;
+        }
+    }
+
+    let preferences = store.get_notification_preferences(interaction.user.id).await?;
+    let channels = NotificationKind::iter()
+        .map(|kind| format!("- {}: {}", kind.label_es(), preferences.channel(kind).label_es()))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    discord.create_response(interaction.id, &interaction.token, CreateInteractionResponse::Message(
+        CreateInteractionResponseMessage::new()
+            .ephemeral(true)
+            .embed(CreateEmbed::new()
+                .title("Tus avisos")
+                .description(format!("**Frecuencia:** {}\n{channels}", preferences.frequency.label_es()))
+                .footer(CreateEmbedFooter::new("Las aprobaciones de inscripcion siempre llegan por mensaje privado")))
+    )).await
+}
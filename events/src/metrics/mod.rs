@@ -0,0 +1,166 @@
+//! Prometheus metrics, kept in memory and rendered in the text format by the `/metrics` route
+mod server;
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use lazy_static::lazy_static;
+use serenity::all::Interaction;
+use crate::prelude::*;
+use crate::tasks;
+
+pub use server::{serve, spawn_server};
+
+/// Upper bounds in seconds of the histogram buckets
+const BUCKETS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+const INTERACTIONS: &str = "leki_interactions_total";
+const HANDLER_DURATION: &str = "leki_handler_duration_seconds";
+const ERRORS: &str = "leki_errors_total";
+const QUERY_DURATION: &str = "leki_db_query_duration_seconds";
+const REMINDER_TASKS: &str = "leki_reminder_tasks";
+const EVENT_SIGNUPS: &str = "leki_event_signups";
+
+/// Name, type and help of every metric, in the order they are rendered
+const METRICS: [(&str, &str, &str); 6] = [
+    (INTERACTIONS, "counter", "Interactions received by type and custom id prefix"),
+    (HANDLER_DURATION, "histogram", "Time spent handling an interaction"),
+    (ERRORS, "counter", "Handler errors by variant"),
+    (QUERY_DURATION, "histogram", "Time spent in each store query"),
+    (REMINDER_TASKS, "gauge", "Reminder tasks waiting to be sent"),
+    (EVENT_SIGNUPS, "gauge", "Players signed up to upcoming events"),
+];
+
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64
+}
+
+#[derive(Default)]
+struct Registry {
+    counters: BTreeMap<(&'static str, Labels), u64>,
+    histograms: BTreeMap<(&'static str, Labels), Histogram>
+}
+
+lazy_static! {
+    static ref REGISTRY: Mutex<Registry> = Mutex::new(Registry::default());
+}
+
+fn increment(name: &'static str, labels: Labels) {
+    *REGISTRY.lock().unwrap().counters.entry((name, labels)).or_default() += 1;
+}
+
+fn observe(name: &'static str, labels: Labels, duration: Duration) {
+    let seconds = duration.as_secs_f64();
+    let mut registry = REGISTRY.lock().unwrap();
+    let histogram = registry.histograms.entry((name, labels)).or_default();
+    for (bucket, bound) in histogram.buckets.iter_mut().zip(BUCKETS) {
+        if seconds <= bound {
+            *bucket += 1;
+        }
+    }
+    histogram.sum += seconds;
+    histogram.count += 1;
+}
+
+/// Counts an interaction when created and records how long it took to handle when dropped
+pub struct InteractionTimer {
+    kind: &'static str,
+    start: Instant
+}
+
+impl InteractionTimer {
+    pub fn start(interaction: &Interaction) -> Self {
+        let (kind, id) = match interaction {
+            Interaction::Command(c) => ("command", c.data.name.clone()),
+            Interaction::Autocomplete(a) => ("autocomplete", a.data.name.clone()),
+            Interaction::Component(c) => ("component", id_prefix(&c.data.custom_id)),
+            Interaction::Modal(m) => ("modal", id_prefix(&m.data.custom_id)),
+            _ => ("other", String::new())
+        };
+        increment(INTERACTIONS, vec![("type", kind.to_string()), ("id", id)]);
+        InteractionTimer { kind, start: Instant::now() }
+    }
+}
+
+impl Drop for InteractionTimer {
+    fn drop(&mut self) {
+        observe(HANDLER_DURATION, vec![("type", self.kind.to_string())], self.start.elapsed());
+    }
+}
+
+/// Records the time spent in a store query when dropped
+pub(crate) struct QueryTimer {
+    query: &'static str,
+    start: Instant
+}
+
+impl QueryTimer {
+    pub(crate) fn new(query: &'static str) -> Self {
+        QueryTimer { query, start: Instant::now() }
+    }
+}
+
+impl Drop for QueryTimer {
+    fn drop(&mut self) {
+        observe(QUERY_DURATION, vec![("query", self.query.to_string())], self.start.elapsed());
+    }
+}
+
+pub fn record_error(error: &Error) {
+    record_failure(error.into());
+}
+
+/// Counts a failed handler by the variant of its error, also for errors of other crates
+pub fn record_failure(variant: &'static str) {
+    increment(ERRORS, vec![("variant", variant.to_string())]);
+}
+
+/// Custom ids carry the ids of messages and users after the first `_`, e.g. `signup_tanks`
+fn id_prefix(custom_id: &str) -> String {
+    custom_id.split('_').next().unwrap_or_default().to_string()
+}
+
+/// Every metric in the Prometheus text format, the gauges are read when rendered
+pub async fn render(store: &Store) -> Result<String> {
+    let mut gauges: Vec<(&'static str, Labels, f64)> = vec![(REMINDER_TASKS, vec![], tasks::active_reminders() as f64)];
+    for (message, title, signups) in store.upcoming_signups().await? {
+        gauges.push((EVENT_SIGNUPS, vec![("event", message.to_string()), ("title", title)], signups as f64));
+    }
+
+    let registry = REGISTRY.lock().unwrap();
+    let mut text = String::new();
+    for (name, kind, help) in METRICS {
+        let _ = writeln!(text, "# HELP {name} {help}\n# TYPE {name} {kind}");
+        for ((_, labels), value) in registry.counters.iter().filter(|((n, _), _)| *n == name) {
+            let _ = writeln!(text, "{name}{} {value}", format_labels(labels, None));
+        }
+        for ((_, labels), histogram) in registry.histograms.iter().filter(|((n, _), _)| *n == name) {
+            for (count, bound) in histogram.buckets.iter().zip(BUCKETS) {
+                let _ = writeln!(text, "{name}_bucket{} {count}", format_labels(labels, Some(&bound.to_string())));
+            }
+            let _ = writeln!(text, "{name}_bucket{} {}", format_labels(labels, Some("+Inf")), histogram.count);
+            let _ = writeln!(text, "{name}_sum{} {}", format_labels(labels, None), histogram.sum);
+            let _ = writeln!(text, "{name}_count{} {}", format_labels(labels, None), histogram.count);
+        }
+        for (_, labels, value) in gauges.iter().filter(|(n, _, _)| *n == name) {
+            let _ = writeln!(text, "{name}{} {value}", format_labels(labels, None));
+        }
+    }
+    Ok(text)
+}
+
+fn format_labels(labels: &Labels, le: Option<&str>) -> String {
+    let mut pairs: Vec<String> = labels.iter()
+        .map(|(name, value)| format!("{name}=\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")))
+        .collect();
+    if let Some(le) = le {
+        pairs.push(format!("le=\"{le}\""));
+    }
+    if pairs.is_empty() { String::new() } else { format!("{{{}}}", pairs.join(",")) }
+}
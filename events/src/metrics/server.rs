@@ -0,0 +1,95 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use serenity::gateway::{ConnectionStage, ShardManager};
+use sqlx::PgPool;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{error, warn};
+use crate::prelude::*;
+
+/// Requests are a single line and a few headers, anything longer is cut
+const MAX_REQUEST: usize = 8 * 1024;
+
+/// Serves `/metrics` and `/healthz` on `addr` next to the bot
+pub fn spawn_server(addr: SocketAddr, pool: PgPool, shards: Arc<ShardManager>) {
+    tokio::spawn(async move {
+        match TcpListener::bind(addr).await {
+            Ok(listener) => serve(listener, Store::new(pool), Some(shards)).await,
+            Err(why) => error!("Metrics server not started on {addr}: {why:?}")
+        }
+    });
+}
+
+/// Answers every connection of `listener`, `shards` is `None` until the gateway client exists
+pub async fn serve(listener: TcpListener, store: Store, shards: Option<Arc<ShardManager>>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                let store = store.clone();
+                let shards = shards.clone();
+                tokio::spawn(async move {
+                    if let Err(why) = respond(stream, &store, shards.as_deref()).await {
+                        warn!("Metrics request failed: {why:?}");
+                    }
+                });
+            }
+            Err(why) => warn!("Metrics connection not accepted: {why:?}")
+        }
+    }
+}
+
+async fn respond(mut stream: TcpStream, store: &Store, shards: Option<&ShardManager>) -> Result<()> {
+    let mut request = vec![0; MAX_REQUEST];
+    let mut read = 0;
+    while read < MAX_REQUEST && !request[..read].windows(4).any(|w| w == b"\r\n\r\n") {
+        match stream.read(&mut request[read..]).await? {
+            0 => break,
+            n => read += n
+        }
+    }
+
+    let request = String::from_utf8_lossy(&request[..read]);
+    let mut line = request.lines().next().unwrap_or_default().split(' ');
+    let (method, path) = (line.next().unwrap_or_default(), line.next().unwrap_or_default());
+    let path = path.split('?').next().unwrap_or_default();
+
+    let (status, body) = match (method, path) {
+        ("GET", "/metrics") => match super::render(store).await {
+            Ok(metrics) => ("200 OK", metrics),
+            Err(why) => ("500 Internal Server Error", format!("{why}\n"))
+        },
+        ("GET", "/healthz") => health(store, shards).await,
+        (_, "/metrics" | "/healthz") => ("405 Method Not Allowed", String::new()),
+        _ => ("404 Not Found", String::new())
+    };
+
+    let response = format!("HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}", body.len());
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Healthy when Postgres answers and every shard is connected to the gateway
+async fn health(store: &Store, shards: Option<&ShardManager>) -> (&'static str, String) {
+    let postgres = store.ping().await;
+    let gateway = match shards {
+        Some(shards) => shards.runners.lock().await.iter()
+            .map(|(id, runner)| (id.0, runner.stage, runner.latency))
+            .collect(),
+        None => vec![]
+    };
+
+    let mut body = match &postgres {
+        Ok(()) => "postgres: ok\n".to_string(),
+        Err(why) => format!("postgres: {why}\n")
+    };
+    if gateway.is_empty() {
+        body.push_str("gateway: no shards\n");
+    }
+    for (shard, stage, latency) in &gateway {
+        body.push_str(&format!("gateway shard {shard}: {stage:?}{}\n", latency.map(|l| format!(" ({}ms)", l.as_millis())).unwrap_or_default()));
+    }
+
+    let healthy = postgres.is_ok() && !gateway.is_empty() && gateway.iter().all(|(_, stage, _)| *stage == ConnectionStage::Connected);
+    (if healthy { "200 OK" } else { "503 Service Unavailable" }, body)
+}
@@ -1,11 +1,13 @@
-use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
 use duration_string::DurationString;
-use serenity::all::{MessageId, ScheduledEventId, UserId};
+use serenity::all::{ChannelId, GuildId, MessageId, ScheduledEventId, UserId};
 use serenity::model::id::RoleId;
 use sqlx::PgPool;
 use sqlx::types::time::OffsetDateTime;
 use tracing::{info, instrument};
-use crate::events::{Event, EventKind, EventRole, EventScopes, Player, PlayerClass, PlayersInRole};
+use crate::events::{DraftStep, Event, EventKind, EventRole, EventScopes, Player, PlayerClass, PlayersInRole, EligibilityRule, ClassQuota, GroupSettings, SwapRequest, Template, EventImage, EventStatus, EventChange, Vacation, Notification, NotificationChannel, NotificationFrequency, NotificationKind, NotificationPreferences, VoiceSession, Fine, FinePayment, FineReason, PenaltyRule, BankDeposit, PvpAlliance, PvpDetails, PvpFilter, PvpStyle};
+use crate::metrics::QueryTimer;
 use crate::prelude::*;
 
 #[derive(Clone, Debug)]
@@ -20,13 +22,16 @@ impl Store {
 
     #[instrument]
     pub async fn get_event(&self, message_id: MessageId) -> Result<Event> {
+        let _timer = QueryTimer::new("get_event");
         info!("get event {}", message_id.get());
         let mut event: Event = sqlx::query_as!(DbEvent, r#"
         select
             title,
             kind as "kind!: EventKind",
             scope as "scope!: EventScopes",
-            description, datetime, duration, leader, scheduled_event, notification_role
+            description, datetime, duration, leader, scheduled_event, notification_role, serialized_rules, group_size, group_seed, image,
+            status as "status!: EventStatus", thread_id, channel_id, checkin_hours,
+            pvp_alliance as "pvp_alliance: PvpAlliance", pvp_campaign, pvp_style as "pvp_style: PvpStyle", pvp_min_cp
         from events.events
         where message_id = $1"#, message_id.get() as i64)
             .fetch_one(&self.pool).await?.into();
@@ -43,7 +48,8 @@ impl Store {
         select
             role as "role!: EventRole",
             user_id, name,
-            class as "class!: Option<PlayerClass>"
+            class as "class!: Option<PlayerClass>",
+            absence
         from events.players
         where message_id = $1"#, message_id.get() as i64)
             .fetch_all(&self.pool).await?;
@@ -56,6 +62,47 @@ impl Store {
         where message_id = $1"#, message_id.get() as i64)
             .fetch_all(&self.pool).await?;
 
+        event.class_quotas = sqlx::query!(r#"
+        select
+            class as "class!: PlayerClass",
+            min, max
+        from events.class_quotas
+        where message_id = $1
+        order by class"#, message_id.get() as i64)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|q| ClassQuota { class: q.class, min: q.min.map(|m| m as usize), max: q.max.map(|m| m as usize) })
+            .collect();
+
+        event.group_settings.locks = sqlx::query!(r#"
+        select user_id, group_number
+        from events.group_locks
+        where message_id = $1"#, message_id.get() as i64)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|l| (UserId::new(l.user_id as u64), l.group_number as usize))
+            .collect();
+
+        event.interested = sqlx::query!(r#"
+        select user_id, name
+        from events.interested
+        where message_id = $1
+        order by name"#, message_id.get() as i64)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|i| Player::new(UserId::new(i.user_id as u64), &i.name))
+            .collect();
+
+        event.confirmed = sqlx::query!(r#"
+        select user_id
+        from events.checkins
+        where message_id = $1
+        order by confirmed_at"#, message_id.get() as i64)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|c| UserId::new(c.user_id as u64))
+            .collect();
+
         event.roles = player_roles.into_iter()
             .map(|pr| {
                 let mut pr: PlayersInRole = pr.into();
@@ -80,10 +127,12 @@ impl Store {
 
     #[instrument]
     pub async fn create_event(&self, message_id: MessageId, event: &Event) -> Result<()> {
+        let _timer = QueryTimer::new("create_event");
         info!("create event {}", message_id.get());
         sqlx::query!(r#"
-        insert into events.events(message_id,kind,scope,title,description,duration,leader,datetime,scheduled_event,notification_role)
-        values($1,$2,$3,$4,$5,$6,$7,$8,$9,$10)
+        insert into events.events(message_id,kind,scope,title,description,duration,leader,datetime,scheduled_event,notification_role,serialized_rules,group_size,image,thread_id,channel_id,checkin_hours,
+            pvp_alliance,pvp_campaign,pvp_style,pvp_min_cp)
+        values($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15,$16,$17,$18,$19,$20)
         "#, message_id.get() as i64,
             event.kind as EventKind,
             event.scope as EventScopes,
@@ -93,7 +142,17 @@ impl Store {
             event.leader.get() as i64,
             event.datetime.map(|dt| OffsetDateTime::from_unix_timestamp(dt.timestamp()).ok()).flatten(),
             event.scheduled_event.map(|e| e.get() as i64),
-            event.notification_role.map(|e| e.get() as i64))
+            event.notification_role.map(|e| e.get() as i64),
+            serde_json::to_string(&event.rules)?,
+            event.group_settings.size.map(|s| s as i16),
+            event.image,
+            event.thread.map(|t| t.get() as i64),
+            event.channel.map(|c| c.get() as i64),
+            event.checkin.map(|h| h as i16),
+            event.pvp.as_ref().map(|p| p.alliance) as Option<PvpAlliance>,
+            event.pvp.as_ref().and_then(|p| p.campaign.clone()),
+            event.pvp.as_ref().and_then(|p| p.style) as Option<PvpStyle>,
+            event.pvp.as_ref().and_then(|p| p.min_cp).map(|cp| cp as i32))
             .execute(&self.pool).await?;
 
         for pr in &event.roles {
@@ -104,15 +163,18 @@ impl Store {
                 .execute(&self.pool).await?;
 
             for player in &pr.players {
-                self.signup_player(message_id, pr.role, &player).await?;
+                self.signup_player(message_id, pr.role, player).await?;
             }
         }
 
+        self.update_class_quotas(message_id, &event.class_quotas).await?;
+
         Ok(())
     }
 
     #[instrument]
     pub async fn update_datetime(&self, message_id: MessageId, datetime: DateTime<Utc>) -> Result<()> {
+        let _timer = QueryTimer::new("update_datetime");
         let datetime = OffsetDateTime::from_unix_timestamp(datetime.timestamp()).unwrap();
         info!("update datetime {datetime} for {}", message_id.get());
         sqlx::query!(r#"
@@ -126,6 +188,7 @@ impl Store {
 
     #[instrument]
     pub async fn update_leader(&self, message_id: MessageId, leader: UserId) -> Result<()> {
+        let _timer = QueryTimer::new("update_leader");
         info!("update leader to {leader} for {}", message_id.get());
         sqlx::query!(r#"
         update events.events
@@ -136,8 +199,162 @@ impl Store {
         Ok(())
     }
 
+    #[instrument]
+    pub async fn update_status(&self, message_id: MessageId, status: EventStatus) -> Result<()> {
+        let _timer = QueryTimer::new("update_status");
+        info!("update status to {status:?} for {}", message_id.get());
+        sqlx::query!(r#"
+        update events.events
+        set status = $1
+        where message_id = $2
+        "#, status as EventStatus, message_id.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn update_scheduled_event(&self, message_id: MessageId, scheduled_event: Option<ScheduledEventId>) -> Result<()> {
+        let _timer = QueryTimer::new("update_scheduled_event");
+        info!("update scheduled event to {scheduled_event:?} for {}", message_id.get());
+        sqlx::query!(r#"
+        update events.events
+        set scheduled_event = $1
+        where message_id = $2
+        "#, scheduled_event.map(|e| e.get() as i64), message_id.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Event message of a Discord scheduled event created by the bot
+    #[instrument]
+    pub async fn get_scheduled_event_message(&self, scheduled_event: ScheduledEventId) -> Result<Option<MessageId>> {
+        let _timer = QueryTimer::new("get_scheduled_event_message");
+        info!("get message of scheduled event {scheduled_event}");
+        let message = sqlx::query_scalar!(r#"
+        select message_id
+        from events.events
+        where scheduled_event = $1
+        "#, scheduled_event.get() as i64)
+            .fetch_optional(&self.pool).await?;
+        Ok(message.map(|m| MessageId::new(m as u64)))
+    }
+
+    #[instrument]
+    pub async fn add_interested(&self, message_id: MessageId, player: &Player) -> Result<()> {
+        let _timer = QueryTimer::new("add_interested");
+        info!("add interested {} to {}", player.name, message_id.get());
+        sqlx::query!(r#"
+        insert into events.interested(message_id,user_id,name)
+        values($1,$2,$3)
+        on conflict (message_id,user_id) do update set name = excluded.name
+        "#, message_id.get() as i64, player.id.get() as i64, player.name)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn update_checkin(&self, message_id: MessageId, hours: Option<u32>) -> Result<()> {
+        let _timer = QueryTimer::new("update_checkin");
+        info!("set check-in of {} to {hours:?} hours", message_id.get());
+        sqlx::query!(r#"
+        update events.events
+        set checkin_hours = $2
+        where message_id = $1
+        "#, message_id.get() as i64, hours.map(|h| h as i16))
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn confirm_checkin(&self, message_id: MessageId, user: UserId) -> Result<()> {
+        let _timer = QueryTimer::new("confirm_checkin");
+        info!("confirm attendance of {user} to {}", message_id.get());
+        sqlx::query!(r#"
+        insert into events.checkins(message_id,user_id)
+        values($1,$2)
+        on conflict (message_id,user_id) do nothing
+        "#, message_id.get() as i64, user.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn remove_interested(&self, message_id: MessageId, user: UserId) -> Result<()> {
+        let _timer = QueryTimer::new("remove_interested");
+        info!("remove interested {user} from {}", message_id.get());
+        sqlx::query!(r#"
+        delete from events.interested
+        where message_id = $1 and user_id = $2
+        "#, message_id.get() as i64, user.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn log_change(&self, message_id: MessageId, actor: Option<UserId>, description: &str) -> Result<()> {
+        let _timer = QueryTimer::new("log_change");
+        info!("log change of {}: {description}", message_id.get());
+        sqlx::query!(r#"
+        insert into events.changes(message_id,actor,description)
+        values($1,$2,$3)
+        "#, message_id.get() as i64, actor.map(|a| a.get() as i64), description)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Changes of an event, oldest first
+    #[instrument]
+    pub async fn get_changes(&self, message_id: MessageId) -> Result<Vec<EventChange>> {
+        let _timer = QueryTimer::new("get_changes");
+        info!("get changes of {}", message_id.get());
+        let changes = sqlx::query!(r#"
+        select actor, description, created_at
+        from events.changes
+        where message_id = $1
+        order by id"#, message_id.get() as i64)
+            .fetch_all(&self.pool).await?;
+
+        Ok(changes.into_iter()
+            .map(|c| EventChange {
+                actor: c.actor.map(|a| UserId::new(a as u64)),
+                description: c.description,
+                created_at: DateTime::<Utc>::from_timestamp(c.created_at.unix_timestamp(), 0).unwrap_or_default()
+            })
+            .collect())
+    }
+
+    /// Remembers a message sent for an event so it's deleted with it
+    #[instrument]
+    pub async fn track_message(&self, message_id: MessageId, channel: ChannelId, id: MessageId) -> Result<()> {
+        let _timer = QueryTimer::new("track_message");
+        info!("track message {id} of {}", message_id.get());
+        sqlx::query!(r#"
+        insert into events.bot_messages(id,message_id,channel_id)
+        values($1,$2,$3)
+        on conflict do nothing
+        "#, id.get() as i64, message_id.get() as i64, channel.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn get_tracked_messages(&self, message_id: MessageId) -> Result<Vec<(ChannelId, MessageId)>> {
+        let _timer = QueryTimer::new("get_tracked_messages");
+        info!("get tracked messages of {}", message_id.get());
+        let messages = sqlx::query!(r#"
+        select id, channel_id
+        from events.bot_messages
+        where message_id = $1
+        order by id"#, message_id.get() as i64)
+            .fetch_all(&self.pool).await?;
+        Ok(messages.into_iter()
+            .map(|m| (ChannelId::new(m.channel_id as u64), MessageId::new(m.id as u64)))
+            .collect())
+    }
+
     #[instrument]
     pub async fn update_title(&self, message_id: MessageId, title: String) -> Result<()> {
+        let _timer = QueryTimer::new("update_title");
         info!("update title to {title} for {}", message_id.get());
         sqlx::query!(r#"
         update events.events
@@ -150,6 +367,7 @@ impl Store {
 
     #[instrument]
     pub async fn update_description(&self, message_id: MessageId, description: String) -> Result<()> {
+        let _timer = QueryTimer::new("update_description");
         info!("update description to {description} for {}", message_id.get());
         sqlx::query!(r#"
         update events.events
@@ -162,6 +380,7 @@ impl Store {
 
     #[instrument]
     pub async fn update_duration(&self, message_id: MessageId, duration: DurationString) -> Result<()> {
+        let _timer = QueryTimer::new("update_duration");
         info!("update duration to {duration} for {}", message_id.get());
         sqlx::query!(r#"
         update events.events
@@ -172,8 +391,253 @@ impl Store {
         Ok(())
     }
 
+    #[instrument]
+    pub async fn update_rules(&self, message_id: MessageId, rules: &Vec<EligibilityRule>) -> Result<()> {
+        let _timer = QueryTimer::new("update_rules");
+        info!("update rules for {}", message_id.get());
+        sqlx::query!(r#"
+        update events.events
+        set serialized_rules = $1
+        where message_id = $2
+        "#, serde_json::to_string(rules)?, message_id.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn update_class_quotas(&self, message_id: MessageId, quotas: &Vec<ClassQuota>) -> Result<()> {
+        let _timer = QueryTimer::new("update_class_quotas");
+        info!("update class quotas for {}", message_id.get());
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(r#"
+            delete from events.class_quotas
+            where message_id = $1
+            "#, message_id.get() as i64)
+            .execute(&mut *tx).await?;
+        for quota in quotas {
+            sqlx::query!(r#"
+            insert into events.class_quotas(message_id,class,min,max)
+            values($1,$2,$3,$4)
+            "#, message_id.get() as i64, quota.class as PlayerClass, quota.min.map(|m| m as i16), quota.max.map(|m| m as i16))
+                .execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn update_groups(&self, message_id: MessageId, size: Option<usize>, seed: u64) -> Result<()> {
+        let _timer = QueryTimer::new("update_groups");
+        info!("update groups of {size:?} with seed {seed} for {}", message_id.get());
+        sqlx::query!(r#"
+        update events.events
+        set group_size = $1, group_seed = $2
+        where message_id = $3
+        "#, size.map(|s| s as i16), seed as i64, message_id.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn lock_player(&self, message_id: MessageId, user: UserId, group: usize) -> Result<()> {
+        let _timer = QueryTimer::new("lock_player");
+        info!("lock {user} in group {group} for {}", message_id.get());
+        sqlx::query!(r#"
+        insert into events.group_locks(message_id,user_id,group_number)
+        values($1,$2,$3)
+        on conflict (message_id, user_id) do update
+        set group_number = excluded.group_number
+        "#, message_id.get() as i64, user.get() as i64, group as i16)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn unlock_player(&self, message_id: MessageId, user: UserId) -> Result<()> {
+        let _timer = QueryTimer::new("unlock_player");
+        info!("unlock {user} for {}", message_id.get());
+        sqlx::query!(r#"
+        delete from events.group_locks
+        where message_id = $1 and user_id = $2
+        "#, message_id.get() as i64, user.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn set_eso_account(&self, user: UserId, handle: &str) -> Result<()> {
+        let _timer = QueryTimer::new("set_eso_account");
+        info!("Set ESO account {handle} for {user}");
+        sqlx::query!(r#"
+        insert into events.eso_accounts(user_id,handle)
+        values($1,$2)
+        on conflict (user_id) do update
+        set handle = excluded.handle, updated_at = now()
+        "#, user.get() as i64, handle)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn get_eso_account(&self, user: UserId) -> Result<Option<String>> {
+        let _timer = QueryTimer::new("get_eso_account");
+        info!("Get ESO account of {user}");
+        let account = sqlx::query!(r#"
+        select handle
+        from events.eso_accounts
+        where user_id = $1"#, user.get() as i64)
+            .fetch_optional(&self.pool).await?;
+        Ok(account.map(|a| a.handle))
+    }
+
+    /// Registered ESO accounts of `users`, users without one are left out
+    #[instrument]
+    pub async fn get_eso_accounts(&self, users: &[UserId]) -> Result<HashMap<UserId, String>> {
+        let _timer = QueryTimer::new("get_eso_accounts");
+        info!("Get ESO accounts of {} users", users.len());
+        let ids: Vec<i64> = users.iter().map(|u| u.get() as i64).collect();
+        let accounts = sqlx::query!(r#"
+        select user_id, handle
+        from events.eso_accounts
+        where user_id = any($1)"#, &ids)
+            .fetch_all(&self.pool).await?;
+        Ok(accounts.into_iter().map(|a| (UserId::new(a.user_id as u64), a.handle)).collect())
+    }
+
+    /// Members registered with the ESO `handles`, by lowercase handle
+    #[instrument]
+    pub async fn eso_account_users(&self, handles: &[String]) -> Result<HashMap<String, UserId>> {
+        let _timer = QueryTimer::new("eso_account_users");
+        info!("Members of {} ESO accounts", handles.len());
+        let handles: Vec<String> = handles.iter().map(|h| h.to_lowercase()).collect();
+        let accounts = sqlx::query!(r#"
+        select user_id, handle
+        from events.eso_accounts
+        where lower(handle) = any($1)"#, &handles)
+            .fetch_all(&self.pool).await?;
+        Ok(accounts.into_iter().map(|a| (a.handle.to_lowercase(), UserId::new(a.user_id as u64))).collect())
+    }
+
+    /// Starter signups of `user` in other events of the same week as `datetime`
+    #[instrument]
+    pub async fn week_starter_signups(&self, user: UserId, datetime: DateTime<Utc>, exclude: MessageId) -> Result<u32> {
+        let _timer = QueryTimer::new("week_starter_signups");
+        info!("Week starter signups of {user}");
+        let datetime = OffsetDateTime::from_unix_timestamp(datetime.timestamp()).unwrap();
+        let count = sqlx::query!(r#"
+        select count(*) as "count!"
+        from events.players p
+        join events.events e on e.message_id = p.message_id
+        where p.user_id = $1
+            and p.role not in ('reserve', 'absent')
+            and date_trunc('week', e.datetime) = date_trunc('week', $2::timestamptz)
+            and e.message_id <> $3"#, user.get() as i64, datetime, exclude.get() as i64)
+            .fetch_one(&self.pool).await?;
+        Ok(count.count as u32)
+    }
+
+    /// Percentage of past event signups of `user` that weren't absences, `None` without history
+    #[instrument]
+    pub async fn attendance(&self, user: UserId) -> Result<Option<u8>> {
+        let _timer = QueryTimer::new("attendance");
+        info!("Attendance of {user}");
+        let attendance = sqlx::query!(r#"
+        select
+            count(*) as "total!",
+            count(*) filter (where p.role <> 'absent') as "attended!"
+        from events.players p
+        join events.events e on e.message_id = p.message_id
+        where p.user_id = $1 and e.datetime < now()"#, user.get() as i64)
+            .fetch_one(&self.pool).await?;
+        Ok((attendance.total > 0).then(|| (attendance.attended * 100 / attendance.total) as u8))
+    }
+
+    /// Players signed up to each event still to be played, absences aside
+    #[instrument]
+    pub async fn upcoming_signups(&self) -> Result<Vec<(MessageId, String, i64)>> {
+        let _timer = QueryTimer::new("upcoming_signups");
+        info!("Upcoming signups");
+        let signups = sqlx::query!(r#"
+        select e.message_id, e.title, count(p.user_id) as "signups!"
+        from events.events e
+        left join events.players p on p.message_id = e.message_id and p.role <> 'absent'
+        where e.datetime > now() and e.status in ('scheduled', 'active')
+        group by e.message_id, e.title"#)
+            .fetch_all(&self.pool).await?;
+        Ok(signups.into_iter().map(|s| (MessageId::new(s.message_id as u64), s.title, s.signups)).collect())
+    }
+
+    /// Events still to be played whose title contains `search`, soonest first
+    #[instrument]
+    pub async fn upcoming_events(&self, search: &str) -> Result<Vec<(MessageId, String, DateTime<Utc>)>> {
+        let _timer = QueryTimer::new("upcoming_events");
+        info!("Upcoming events like {search}");
+        let events = sqlx::query!(r#"
+        select message_id, title, datetime as "datetime!"
+        from events.events
+        where datetime > now() and status in ('scheduled', 'active') and title ilike '%' || $1 || '%'
+        order by datetime
+        limit 25"#, search)
+            .fetch_all(&self.pool).await?;
+        Ok(events.into_iter()
+            .filter_map(|e| DateTime::<Utc>::from_timestamp(e.datetime.unix_timestamp(), 0).map(|dt| (MessageId::new(e.message_id as u64), e.title, dt)))
+            .collect())
+    }
+
+    /// Events still to be played of `kind` with the PvP details of `filter`, soonest first
+    #[instrument]
+    pub async fn list_upcoming_events(&self, kind: Option<EventKind>, filter: &PvpFilter) -> Result<Vec<(MessageId, Event)>> {
+        let _timer = QueryTimer::new("list_upcoming_events");
+        info!("Upcoming {kind:?} events with {filter:?}");
+        let ids = sqlx::query!(r#"
+        select message_id
+        from events.events
+        where datetime > now() and status in ('scheduled', 'active')
+            and ($1::events.kind is null or kind = $1)
+            and ($2::events.alliance is null or pvp_alliance = $2)
+            and ($3::events.pvp_style is null or pvp_style = $3)
+            and ($4::varchar is null or pvp_campaign ilike '%' || $4 || '%')
+        order by datetime
+        limit 25"#, kind as Option<EventKind>, filter.alliance as Option<PvpAlliance>, filter.style as Option<PvpStyle>, filter.campaign)
+            .fetch_all(&self.pool).await?;
+
+        let mut events = vec![];
+        for id in ids {
+            let message = MessageId::new(id.message_id as u64);
+            events.push((message, self.get_event(message).await?));
+        }
+        Ok(events)
+    }
+
+    #[instrument]
+    pub async fn ping(&self) -> Result<()> {
+        let _timer = QueryTimer::new("ping");
+        sqlx::query!("select 1 as one").fetch_one(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Events still to be played that overlap the time between `start` and `end`
+    #[instrument]
+    pub async fn overlapping_events(&self, start: DateTime<Utc>, end: DateTime<Utc>, exclude: MessageId) -> Result<Vec<MessageId>> {
+        let _timer = QueryTimer::new("overlapping_events");
+        info!("Events between {start} and {end}");
+        let start = OffsetDateTime::from_unix_timestamp(start.timestamp()).unwrap();
+        let end = OffsetDateTime::from_unix_timestamp(end.timestamp()).unwrap();
+        let events = sqlx::query!(r#"
+        select message_id
+        from events.events
+        where datetime < $2
+            and datetime + duration::interval > $1
+            and status in ('scheduled', 'active')
+            and message_id <> $3
+        order by datetime"#, start, end, exclude.get() as i64)
+            .fetch_all(&self.pool).await?;
+        Ok(events.into_iter().map(|e| MessageId::new(e.message_id as u64)).collect())
+    }
+
     #[instrument]
     pub async fn signup_player(&self, message_id: MessageId, role: EventRole, player: &Player) -> Result<()> {
+        let _timer = QueryTimer::new("signup_player");
         info!("Delete players for {}", message_id.get());
         sqlx::query!(r#"
             delete from events.players
@@ -184,16 +648,16 @@ impl Store {
         if let Some(class) = &player.class {
             info!("insert playersn in {role} with class {class} for {}", message_id.get());
             sqlx::query!(r#"
-            insert into events.players(message_id,role,user_id,name,class)
-            values($1,$2,$3,$4,$5)
-            "#, message_id.get() as i64, role as EventRole, player.id.get() as i64, player.name, *class as PlayerClass)
+            insert into events.players(message_id,role,user_id,name,class,absence)
+            values($1,$2,$3,$4,$5,$6)
+            "#, message_id.get() as i64, role as EventRole, player.id.get() as i64, player.name, *class as PlayerClass, player.absence)
                 .execute(&self.pool).await?;
         } else {
             info!("insert players in {role} for {}", message_id.get());
             sqlx::query!(r#"
-            insert into events.players(message_id,role,user_id,name)
-            values($1,$2,$3,$4)
-            "#, message_id.get() as i64, role as EventRole, player.id.get() as i64, player.name)
+            insert into events.players(message_id,role,user_id,name,absence)
+            values($1,$2,$3,$4,$5)
+            "#, message_id.get() as i64, role as EventRole, player.id.get() as i64, player.name, player.absence)
                 .execute(&self.pool).await?;
         }
 
@@ -215,8 +679,414 @@ impl Store {
         Ok(())
     }
 
+    /// Removes the player from the event, their flex roles and group lock go with them
+    #[instrument]
+    pub async fn remove_player(&self, message_id: MessageId, user: UserId) -> Result<()> {
+        let _timer = QueryTimer::new("remove_player");
+        info!("Remove {user} from {}", message_id.get());
+        sqlx::query!(r#"
+            delete from events.players
+            where message_id = $1 and user_id = $2
+            "#, message_id.get() as i64, user.get() as i64)
+            .execute(&self.pool).await?;
+        sqlx::query!(r#"
+            delete from events.flex_roles
+            where message_id = $1 and user_id = $2
+            "#, message_id.get() as i64, user.get() as i64)
+            .execute(&self.pool).await?;
+        sqlx::query!(r#"
+            delete from events.group_locks
+            where message_id = $1 and user_id = $2
+            "#, message_id.get() as i64, user.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn add_vacation(&self, user: UserId, name: &str, start: DateTime<Utc>, end: DateTime<Utc>, reason: &str) -> Result<Vacation> {
+        let _timer = QueryTimer::new("add_vacation");
+        info!("Vacation of {user} from {start} to {end}");
+        let id = sqlx::query_scalar!(r#"
+            insert into events.vacations(user_id,name,start_at,end_at,reason)
+            values($1,$2,$3,$4,$5)
+            returning id
+            "#, user.get() as i64, name, OffsetDateTime::from_unix_timestamp(start.timestamp()).ok(),
+            OffsetDateTime::from_unix_timestamp(end.timestamp()).ok(), reason)
+            .fetch_one(&self.pool).await?;
+        Ok(Vacation { id, user, name: name.to_string(), start, end, reason: reason.to_string() })
+    }
+
+    /// Vacations not finished yet, of a single user or everyone
+    #[instrument]
+    pub async fn get_vacations(&self, user: Option<UserId>) -> Result<Vec<Vacation>> {
+        let _timer = QueryTimer::new("get_vacations");
+        info!("Vacations of {user:?}");
+        let vacations = sqlx::query_as!(DbVacation, r#"
+            select id, user_id, name, start_at, end_at, reason
+            from events.vacations
+            where end_at > now() and ($1::bigint is null or user_id = $1)
+            order by start_at"#, user.map(|u| u.get() as i64))
+            .fetch_all(&self.pool).await?;
+        Ok(vacations.into_iter().map(|v| v.into()).collect())
+    }
+
+    /// Removes a vacation of `user`, `false` if they have no vacation with that id
+    #[instrument]
+    pub async fn remove_vacation(&self, id: i32, user: UserId) -> Result<bool> {
+        let _timer = QueryTimer::new("remove_vacation");
+        info!("Remove vacation {id} of {user}");
+        let removed = sqlx::query!(r#"
+            delete from events.vacations
+            where id = $1 and user_id = $2
+            "#, id, user.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(removed.rows_affected() > 0)
+    }
+
+    /// Events still to be played starting between `start` and `end`
+    #[instrument]
+    pub async fn events_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<MessageId>> {
+        let _timer = QueryTimer::new("events_between");
+        info!("Events between {start} and {end}");
+        let events = sqlx::query_scalar!(r#"
+            select message_id
+            from events.events
+            where datetime >= $1 and datetime < $2 and datetime > now() and status in ('scheduled', 'active')
+            order by datetime"#, OffsetDateTime::from_unix_timestamp(start.timestamp()).ok(), OffsetDateTime::from_unix_timestamp(end.timestamp()).ok())
+            .fetch_all(&self.pool).await?;
+        Ok(events.into_iter().map(|m| MessageId::new(m as u64)).collect())
+    }
+
+    /// Events with a date that haven't finished yet, their tasks are scheduled again on startup
+    #[instrument]
+    pub async fn unfinished_events(&self) -> Result<Vec<MessageId>> {
+        let _timer = QueryTimer::new("unfinished_events");
+        info!("Unfinished events");
+        let events = sqlx::query_scalar!(r#"
+            select message_id
+            from events.events
+            where datetime + duration::interval > now() and status in ('scheduled', 'active')
+            order by datetime"#)
+            .fetch_all(&self.pool).await?;
+        Ok(events.into_iter().map(|m| MessageId::new(m as u64)).collect())
+    }
+
+    /// Events started or starting in the next `lead` that haven't finished yet
+    #[instrument]
+    pub async fn events_in_progress(&self, lead: Duration) -> Result<Vec<MessageId>> {
+        let _timer = QueryTimer::new("events_in_progress");
+        info!("Events in progress");
+        let events = sqlx::query_scalar!(r#"
+            select message_id
+            from events.events
+            where datetime <= now() + make_interval(mins => $1) and datetime + duration::interval > now() and status in ('scheduled', 'active')
+            order by datetime"#, lead.num_minutes() as i32)
+            .fetch_all(&self.pool).await?;
+        Ok(events.into_iter().map(|m| MessageId::new(m as u64)).collect())
+    }
+
+    #[instrument]
+    pub async fn voice_joined(&self, message_id: MessageId, user: UserId, channel: ChannelId, at: DateTime<Utc>) -> Result<()> {
+        let _timer = QueryTimer::new("voice_joined");
+        info!("{user} joined {channel} for {}", message_id.get());
+        sqlx::query!(r#"
+        insert into events.voice_presence(message_id,user_id,channel_id,joined_at)
+        select $1,$2,$3,$4
+        where not exists (
+            select 1 from events.voice_presence
+            where message_id = $1 and user_id = $2 and channel_id = $3 and left_at is null
+        )"#, message_id.get() as i64, user.get() as i64, channel.get() as i64, OffsetDateTime::from_unix_timestamp(at.timestamp()).ok())
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Closes the voice presence of `user` in every channel but `channel`, the one they are now connected to
+    #[instrument]
+    pub async fn voice_left(&self, user: UserId, channel: Option<ChannelId>, at: DateTime<Utc>) -> Result<()> {
+        let _timer = QueryTimer::new("voice_left");
+        sqlx::query!(r#"
+        update events.voice_presence
+        set left_at = $3
+        where user_id = $1 and left_at is null and channel_id is distinct from $2"#,
+            user.get() as i64, channel.map(|c| c.get() as i64), OffsetDateTime::from_unix_timestamp(at.timestamp()).ok())
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn voice_presence(&self, message_id: MessageId) -> Result<Vec<VoiceSession>> {
+        let _timer = QueryTimer::new("voice_presence");
+        info!("Voice presence of {}", message_id.get());
+        let sessions = sqlx::query!(r#"
+        select user_id, channel_id, joined_at, left_at
+        from events.voice_presence
+        where message_id = $1
+        order by joined_at"#, message_id.get() as i64)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|s| VoiceSession {
+                user: UserId::new(s.user_id as u64),
+                channel: ChannelId::new(s.channel_id as u64),
+                joined: DateTime::<Utc>::from_timestamp(s.joined_at.unix_timestamp(), 0).unwrap_or_default(),
+                left: s.left_at.and_then(|l| DateTime::<Utc>::from_timestamp(l.unix_timestamp(), 0))
+            })
+            .collect();
+        Ok(sessions)
+    }
+
+    #[instrument]
+    pub async fn penalty_rules(&self) -> Result<Vec<PenaltyRule>> {
+        let _timer = QueryTimer::new("penalty_rules");
+        info!("Penalty rules");
+        let rules = sqlx::query!(r#"
+        select role_id, no_show, late_absence
+        from events.penalty_rules
+        order by role_id"#)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|r| PenaltyRule {
+                role: (r.role_id != 0).then(|| RoleId::new(r.role_id as u64)),
+                no_show: r.no_show as u32,
+                late_absence: r.late_absence as u32
+            })
+            .collect();
+        Ok(rules)
+    }
+
+    #[instrument]
+    pub async fn set_penalty_rule(&self, rule: &PenaltyRule) -> Result<()> {
+        let _timer = QueryTimer::new("set_penalty_rule");
+        info!("Penalty rule for {:?}", rule.role);
+        sqlx::query!(r#"
+        insert into events.penalty_rules(role_id,no_show,late_absence)
+        values($1,$2,$3)
+        on conflict (role_id) do update set no_show = excluded.no_show, late_absence = excluded.late_absence
+        "#, rule.role.map(|r| r.get() as i64).unwrap_or(0), rule.no_show as i32, rule.late_absence as i32)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Fines `user` for the event, `None` if they already had a fine for it
+    #[instrument]
+    pub async fn add_fine(&self, user: UserId, name: &str, message_id: MessageId, event_title: &str, reason: FineReason, amount: u32) -> Result<Option<Fine>> {
+        let _timer = QueryTimer::new("add_fine");
+        info!("Fine {user} with {amount} for {}", message_id.get());
+        let fine = sqlx::query_as!(DbFine, r#"
+        insert into events.fines(user_id,name,message_id,event_title,reason,amount)
+        values($1,$2,$3,$4,$5,$6)
+        on conflict (user_id, message_id) do nothing
+        returning id, user_id, name, message_id, event_title, reason as "reason!: FineReason", amount, created_at, waived_at
+        "#, user.get() as i64, name, message_id.get() as i64, event_title, reason as FineReason, amount as i32)
+            .fetch_optional(&self.pool).await?;
+        Ok(fine.map(|f| f.into()))
+    }
+
+    /// Fines of `user`, or of everyone, oldest first
+    #[instrument]
+    pub async fn get_fines(&self, user: Option<UserId>) -> Result<Vec<Fine>> {
+        let _timer = QueryTimer::new("get_fines");
+        info!("Fines of {user:?}");
+        let fines = sqlx::query_as!(DbFine, r#"
+        select id, user_id, name, message_id, event_title, reason as "reason!: FineReason", amount, created_at, waived_at
+        from events.fines
+        where $1::bigint is null or user_id = $1
+        order by created_at, id"#, user.map(|u| u.get() as i64))
+            .fetch_all(&self.pool).await?;
+        Ok(fines.into_iter().map(|f| f.into()).collect())
+    }
+
+    /// Waives a fine still standing, `None` if there is no such fine
+    #[instrument]
+    pub async fn waive_fine(&self, id: i32, by: UserId) -> Result<Option<Fine>> {
+        let _timer = QueryTimer::new("waive_fine");
+        info!("Waive fine {id}");
+        let fine = sqlx::query_as!(DbFine, r#"
+        update events.fines
+        set waived_by = $2, waived_at = now()
+        where id = $1 and waived_at is null
+        returning id, user_id, name, message_id, event_title, reason as "reason!: FineReason", amount, created_at, waived_at
+        "#, id, by.get() as i64)
+            .fetch_optional(&self.pool).await?;
+        Ok(fine.map(|f| f.into()))
+    }
+
+    #[instrument]
+    pub async fn add_fine_payment(&self, user: UserId, amount: u32, recorded_by: Option<UserId>) -> Result<()> {
+        let _timer = QueryTimer::new("add_fine_payment");
+        info!("Payment of {amount} from {user}");
+        sqlx::query!(r#"
+        insert into events.fine_payments(user_id,amount,recorded_by)
+        values($1,$2,$3)
+        "#, user.get() as i64, amount as i32, recorded_by.map(|u| u.get() as i64))
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn get_fine_payments(&self, user: Option<UserId>) -> Result<Vec<FinePayment>> {
+        let _timer = QueryTimer::new("get_fine_payments");
+        info!("Fine payments of {user:?}");
+        let payments = sqlx::query!(r#"
+        select id, user_id, amount, paid_at
+        from events.fine_payments
+        where $1::bigint is null or user_id = $1
+        order by paid_at, id"#, user.map(|u| u.get() as i64))
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|p| FinePayment {
+                id: p.id,
+                user: UserId::new(p.user_id as u64),
+                amount: p.amount as u32,
+                paid: DateTime::<Utc>::from_timestamp(p.paid_at.unix_timestamp(), 0).unwrap_or_default()
+            })
+            .collect();
+        Ok(payments)
+    }
+
+    /// Saves a guild bank deposit of `user` and the part of it that pays their fines.
+    /// Returns `false` when the deposit was already imported
+    #[instrument]
+    pub async fn add_bank_deposit(&self, deposit: &BankDeposit, user: UserId, applied: u32, imported_by: Option<UserId>) -> Result<bool> {
+        let _timer = QueryTimer::new("add_bank_deposit");
+        info!("Bank deposit of {} from {user}, {applied} to fines", deposit.amount);
+        let deposited_at = OffsetDateTime::from_unix_timestamp(deposit.at.timestamp()).unwrap();
+        let mut tx = self.pool.begin().await?;
+        let inserted = sqlx::query!(r#"
+        insert into events.bank_deposits(deposit_key,user_id,account,amount,deposited_at,applied,imported_by)
+        values($1,$2,$3,$4,$5,$6,$7)
+        on conflict (deposit_key) do nothing
+        "#, deposit.key(), user.get() as i64, deposit.account, deposit.amount as i32, deposited_at, applied as i32,
+            imported_by.map(|u| u.get() as i64))
+            .execute(&mut *tx).await?
+            .rows_affected() > 0;
+        if inserted && applied > 0 {
+            sqlx::query!(r#"
+            insert into events.fine_payments(user_id,amount,recorded_by,paid_at,deposit_key)
+            values($1,$2,$3,$4,$5)
+            "#, user.get() as i64, applied as i32, imported_by.map(|u| u.get() as i64), deposited_at, deposit.key())
+                .execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    /// Keys of the deposits already imported among `keys`
+    #[instrument]
+    pub async fn imported_deposits(&self, keys: &[String]) -> Result<Vec<String>> {
+        let _timer = QueryTimer::new("imported_deposits");
+        info!("Imported deposits among {}", keys.len());
+        let imported = sqlx::query!(r#"
+        select deposit_key
+        from events.bank_deposits
+        where deposit_key = any($1)"#, keys)
+            .fetch_all(&self.pool).await?;
+        Ok(imported.into_iter().map(|d| d.deposit_key).collect())
+    }
+
+    #[instrument]
+    pub async fn get_notification_preferences(&self, user: UserId) -> Result<NotificationPreferences> {
+        let _timer = QueryTimer::new("get_notification_preferences");
+        info!("Notification preferences of {user}");
+        let frequency = sqlx::query!(r#"
+            select frequency as "frequency!: NotificationFrequency"
+            from events.notification_settings
+            where user_id = $1"#, user.get() as i64)
+            .fetch_optional(&self.pool).await?
+            .map(|s| s.frequency)
+            .unwrap_or_default();
+        let channels = sqlx::query!(r#"
+            select kind as "kind!: NotificationKind", channel as "channel!: NotificationChannel"
+            from events.notification_channels
+            where user_id = $1"#, user.get() as i64)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|c| (c.kind, c.channel))
+            .collect();
+        Ok(NotificationPreferences { frequency, channels })
+    }
+
+    #[instrument]
+    pub async fn set_notification_frequency(&self, user: UserId, frequency: NotificationFrequency) -> Result<()> {
+        let _timer = QueryTimer::new("set_notification_frequency");
+        info!("Notification frequency of {user}: {frequency:?}");
+        sqlx::query!(r#"
+            insert into events.notification_settings(user_id,frequency)
+            values($1,$2)
+            on conflict (user_id) do update
+            set frequency = excluded.frequency
+            "#, user.get() as i64, frequency as NotificationFrequency)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn set_notification_channel(&self, user: UserId, kind: NotificationKind, channel: NotificationChannel) -> Result<()> {
+        let _timer = QueryTimer::new("set_notification_channel");
+        info!("Notification channel of {user} for {kind:?}: {channel:?}");
+        sqlx::query!(r#"
+            insert into events.notification_channels(user_id,kind,channel)
+            values($1,$2,$3)
+            on conflict (user_id, kind) do update
+            set channel = excluded.channel
+            "#, user.get() as i64, kind as NotificationKind, channel as NotificationChannel)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn queue_notification(&self, user: UserId, kind: NotificationKind, thread: Option<ChannelId>, content: &str, due_at: DateTime<Utc>) -> Result<()> {
+        let _timer = QueryTimer::new("queue_notification");
+        info!("Queue {kind:?} notification for {user} at {due_at}");
+        sqlx::query!(r#"
+            insert into events.notification_queue(user_id,kind,thread_id,content,due_at)
+            values($1,$2,$3,$4,$5)
+            "#, user.get() as i64, kind as NotificationKind, thread.map(|t| t.get() as i64), content,
+            OffsetDateTime::from_unix_timestamp(due_at.timestamp()).ok())
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Notifications to send now, of a single member or everyone, oldest first
+    #[instrument]
+    pub async fn due_notifications(&self, user: Option<UserId>, max_attempts: i16) -> Result<Vec<Notification>> {
+        let _timer = QueryTimer::new("due_notifications");
+        info!("Due notifications of {user:?}");
+        let notifications = sqlx::query!(r#"
+            select id, user_id, kind as "kind!: NotificationKind", thread_id, content
+            from events.notification_queue
+            where sent_at is null and due_at <= now() and attempts < $2 and ($1::bigint is null or user_id = $1)
+            order by created_at, id"#, user.map(|u| u.get() as i64), max_attempts)
+            .fetch_all(&self.pool).await?
+            .into_iter()
+            .map(|n| Notification {
+                id: n.id,
+                user: UserId::new(n.user_id as u64),
+                kind: n.kind,
+                thread: n.thread_id.map(|t| ChannelId::new(t as u64)),
+                content: n.content
+            })
+            .collect();
+        Ok(notifications)
+    }
+
+    /// Marks the notifications as sent, or counts a failed attempt
+    #[instrument]
+    pub async fn notifications_delivered(&self, ids: &[i32], sent: bool) -> Result<()> {
+        let _timer = QueryTimer::new("notifications_delivered");
+        info!("Notifications {ids:?} sent: {sent}");
+        sqlx::query!(r#"
+            update events.notification_queue
+            set sent_at = case when $2 then now() else sent_at end,
+                attempts = attempts + 1
+            where id = any($1)
+            "#, ids, sent)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
     #[instrument]
     pub async fn remove_event(&self, message_id: MessageId) -> Result<()> {
+        let _timer = QueryTimer::new("remove_event");
         info!("Remove event {}", message_id.get());
         sqlx::query!(r#"
             delete from events.events
@@ -225,6 +1095,289 @@ impl Store {
             .execute(&self.pool).await?;
         Ok(())
     }
+
+    #[instrument]
+    pub async fn save_draft(&self, user: UserId, step: DraftStep, event: &Event) -> Result<()> {
+        let _timer = QueryTimer::new("save_draft");
+        info!("Save draft of {user} at step {step:?}");
+        sqlx::query!(r#"
+        insert into events.drafts(user_id,step,serialized_event)
+        values($1,$2,$3)
+        on conflict (user_id) do update
+        set step = excluded.step, serialized_event = excluded.serialized_event, updated_at = now()
+        "#, user.get() as i64, step as DraftStep, serde_json::to_string(event)?)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn get_draft(&self, user: UserId) -> Result<Option<(DraftStep, Event)>> {
+        let _timer = QueryTimer::new("get_draft");
+        info!("Get draft of {user}");
+        let draft = sqlx::query!(r#"
+        select
+            step as "step!: DraftStep",
+            serialized_event
+        from events.drafts
+        where user_id = $1"#, user.get() as i64)
+            .fetch_optional(&self.pool).await?;
+
+        match draft {
+            Some(draft) => Ok(Some((draft.step, serde_json::from_str(&draft.serialized_event)?))),
+            None => Ok(None)
+        }
+    }
+
+    #[instrument]
+    pub async fn remove_draft(&self, user: UserId) -> Result<()> {
+        let _timer = QueryTimer::new("remove_draft");
+        info!("Remove draft of {user}");
+        sqlx::query!(r#"
+            delete from events.drafts
+            where user_id = $1
+            "#, user.get() as i64)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Returns `false` when the name is taken by a template of someone else and `overwrite_any` isn't set
+    #[instrument]
+    pub async fn save_template(&self, template: &Template, overwrite_any: bool) -> Result<bool> {
+        let _timer = QueryTimer::new("save_template");
+        info!("Save template {}", template.name);
+        let saved = sqlx::query!(r#"
+        insert into events.templates(name,kind,serialized_roles,serialized_rules,serialized_quotas,owner_id)
+        values($1,$2,$3,$4,$5,$6)
+        on conflict (name) do update
+        set kind = excluded.kind, serialized_roles = excluded.serialized_roles
+        where events.templates.owner_id = excluded.owner_id or $7
+        "#, template.name, template.kind as EventKind,
+            serde_json::to_string(&template.roles)?,
+            serde_json::to_string(&template.rules)?,
+            serde_json::to_string(&template.class_quotas)?,
+            template.owner.map(|o| o.get() as i64),
+            overwrite_any)
+            .execute(&self.pool).await?;
+        Ok(saved.rows_affected() > 0)
+    }
+
+    #[instrument]
+    pub async fn get_template(&self, name: &str) -> Result<Option<Template>> {
+        let _timer = QueryTimer::new("get_template");
+        info!("Get template {name}");
+        let template = sqlx::query!(r#"
+        select
+            name,
+            kind as "kind!: EventKind",
+            serialized_roles,
+            serialized_rules,
+            serialized_quotas,
+            owner_id
+        from events.templates
+        where name = $1"#, name)
+            .fetch_optional(&self.pool).await?;
+
+        match template {
+            Some(t) => Ok(Some(Template {
+                name: t.name,
+                kind: t.kind,
+                roles: serde_json::from_str(&t.serialized_roles)?,
+                rules: serde_json::from_str(&t.serialized_rules)?,
+                class_quotas: serde_json::from_str(&t.serialized_quotas)?,
+                owner: t.owner_id.map(|o| UserId::new(o as u64))
+            })),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns `false` when there is no template with that name
+    #[instrument]
+    pub async fn update_template_rules(&self, name: &str, rules: &Vec<EligibilityRule>) -> Result<bool> {
+        let _timer = QueryTimer::new("update_template_rules");
+        info!("Update rules of template {name}");
+        let updated = sqlx::query!(r#"
+        update events.templates
+        set serialized_rules = $1
+        where name = $2
+        "#, serde_json::to_string(rules)?, name)
+            .execute(&self.pool).await?;
+        Ok(updated.rows_affected() > 0)
+    }
+
+    /// Returns `false` when there is no template with that name
+    #[instrument]
+    pub async fn update_template_quotas(&self, name: &str, quotas: &Vec<ClassQuota>) -> Result<bool> {
+        let _timer = QueryTimer::new("update_template_quotas");
+        info!("Update class quotas of template {name}");
+        let updated = sqlx::query!(r#"
+        update events.templates
+        set serialized_quotas = $1
+        where name = $2
+        "#, serde_json::to_string(quotas)?, name)
+            .execute(&self.pool).await?;
+        Ok(updated.rows_affected() > 0)
+    }
+
+    #[instrument]
+    pub async fn search_templates(&self, name: &str) -> Result<Vec<(String, EventKind)>> {
+        let _timer = QueryTimer::new("search_templates");
+        info!("Search templates like {name}");
+        let templates = sqlx::query!(r#"
+        select
+            name,
+            kind as "kind!: EventKind"
+        from events.templates
+        where name ilike '%' || $1 || '%'
+        order by name
+        limit 25"#, name)
+            .fetch_all(&self.pool).await?;
+
+        Ok(templates.into_iter().map(|t| (t.name, t.kind)).collect())
+    }
+
+    /// Moves both players to their new role and replaces their flex roles in a single transaction
+    #[instrument]
+    pub async fn swap_players(&self, message_id: MessageId, players: &[(EventRole, Player); 2]) -> Result<()> {
+        let _timer = QueryTimer::new("swap_players");
+        info!("Swap players {} and {} in {}", players[0].1.name, players[1].1.name, message_id.get());
+        let mut tx = self.pool.begin().await?;
+        for (role, player) in players {
+            sqlx::query!(r#"
+            update events.players
+            set role = $1
+            where message_id = $2 and user_id = $3
+            "#, *role as EventRole, message_id.get() as i64, player.id.get() as i64)
+                .execute(&mut *tx).await?;
+
+            sqlx::query!(r#"
+            delete from events.flex_roles
+            where message_id = $1 and user_id = $2
+            "#, message_id.get() as i64, player.id.get() as i64)
+                .execute(&mut *tx).await?;
+            for flex in &player.flex {
+                sqlx::query!(r#"
+                insert into events.flex_roles(message_id,role,user_id)
+                values($1,$2,$3)
+                "#, message_id.get() as i64, *flex as EventRole, player.id.get() as i64)
+                    .execute(&mut *tx).await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    #[instrument]
+    pub async fn create_swap_request(&self, message_id: MessageId, channel: ChannelId, requester: (UserId, EventRole), target: (UserId, EventRole)) -> Result<i32> {
+        let _timer = QueryTimer::new("create_swap_request");
+        info!("Swap request from {} to {} in {}", requester.0, target.0, message_id.get());
+        let request = sqlx::query!(r#"
+        insert into events.swap_requests(message_id,channel_id,requester,requester_role,target,target_role)
+        values($1,$2,$3,$4,$5,$6)
+        returning id
+        "#, message_id.get() as i64, channel.get() as i64,
+            requester.0.get() as i64, requester.1 as EventRole,
+            target.0.get() as i64, target.1 as EventRole)
+            .fetch_one(&self.pool).await?;
+        Ok(request.id)
+    }
+
+    #[instrument]
+    pub async fn get_swap_request(&self, id: i32) -> Result<Option<SwapRequest>> {
+        let _timer = QueryTimer::new("get_swap_request");
+        info!("Get swap request {id}");
+        let request = sqlx::query!(r#"
+        select
+            id, message_id, channel_id, requester, target,
+            requester_role as "requester_role!: EventRole",
+            target_role as "target_role!: EventRole"
+        from events.swap_requests
+        where id = $1"#, id)
+            .fetch_optional(&self.pool).await?;
+
+        Ok(request.map(|r| SwapRequest {
+            id: r.id,
+            message: MessageId::new(r.message_id as u64),
+            channel: ChannelId::new(r.channel_id as u64),
+            requester: UserId::new(r.requester as u64),
+            requester_role: r.requester_role,
+            target: UserId::new(r.target as u64),
+            target_role: r.target_role
+        }))
+    }
+
+    #[instrument]
+    pub async fn remove_swap_request(&self, id: i32) -> Result<()> {
+        let _timer = QueryTimer::new("remove_swap_request");
+        info!("Remove swap request {id}");
+        sqlx::query!(r#"
+            delete from events.swap_requests
+            where id = $1
+            "#, id)
+            .execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[instrument(skip(data))]
+    pub async fn add_image(&self, guild: GuildId, kind: EventKind, file_name: &str, data: &[u8], key: Option<&str>, aliases: &[String]) -> Result<i32> {
+        let _timer = QueryTimer::new("add_image");
+        info!("Add {kind} image {file_name} to guild {guild}");
+        let id = sqlx::query_scalar!(r#"
+            insert into events.images(guild_id,kind,file_name,data,key,aliases)
+            values($1,$2,$3,$4,$5,$6)
+            returning id
+            "#, guild.get() as i64, kind as EventKind, file_name, data, key, aliases)
+            .fetch_one(&self.pool).await?;
+        Ok(id)
+    }
+
+    #[instrument]
+    pub async fn get_images(&self, guild: GuildId, kind: EventKind) -> Result<Vec<EventImage>> {
+        let _timer = QueryTimer::new("get_images");
+        info!("Get {kind} images of guild {guild}");
+        let images = sqlx::query!(r#"
+            select id, kind as "kind!: EventKind", file_name, key, aliases
+            from events.images
+            where guild_id = $1 and kind = $2
+            order by id"#, guild.get() as i64, kind as EventKind)
+            .fetch_all(&self.pool).await?;
+
+        Ok(images.into_iter()
+            .map(|i| EventImage {
+                id: Some(i.id),
+                kind: i.kind,
+                file_name: i.file_name,
+                key: i.key,
+                aliases: i.aliases
+            })
+            .collect())
+    }
+
+    #[instrument]
+    pub async fn get_image_data(&self, id: i32) -> Result<(String, Vec<u8>)> {
+        let _timer = QueryTimer::new("get_image_data");
+        info!("Get image data {id}");
+        let image = sqlx::query!(r#"
+            select file_name, data
+            from events.images
+            where id = $1"#, id)
+            .fetch_one(&self.pool).await?;
+        Ok((image.file_name, image.data))
+    }
+
+    /// Images of the last events of a kind, newest first
+    #[instrument]
+    pub async fn recent_images(&self, kind: EventKind, limit: i64) -> Result<Vec<String>> {
+        let _timer = QueryTimer::new("recent_images");
+        info!("Get recent {kind} images");
+        let images = sqlx::query_scalar!(r#"
+            select image as "image!"
+            from events.events
+            where kind = $1 and image is not null
+            order by created_at desc
+            limit $2"#, kind as EventKind, limit)
+            .fetch_all(&self.pool).await?;
+        Ok(images)
+    }
 }
 
 struct DbEvent {
@@ -236,7 +1389,40 @@ struct DbEvent {
     duration: String,
     leader: i64,
     scheduled_event: Option<i64>,
-    notification_role: Option<i64>
+    notification_role: Option<i64>,
+    serialized_rules: String,
+    group_size: Option<i16>,
+    group_seed: i64,
+    image: Option<String>,
+    status: EventStatus,
+    thread_id: Option<i64>,
+    channel_id: Option<i64>,
+    checkin_hours: Option<i16>,
+    pvp_alliance: Option<PvpAlliance>,
+    pvp_campaign: Option<String>,
+    pvp_style: Option<PvpStyle>,
+    pvp_min_cp: Option<i32>
+}
+
+struct DbFine {
+    id: i32,
+    user_id: i64,
+    name: String,
+    message_id: Option<i64>,
+    event_title: String,
+    reason: FineReason,
+    amount: i32,
+    created_at: OffsetDateTime,
+    waived_at: Option<OffsetDateTime>
+}
+
+struct DbVacation {
+    id: i32,
+    user_id: i64,
+    name: String,
+    start_at: OffsetDateTime,
+    end_at: OffsetDateTime,
+    reason: String
 }
 
 struct DbPlayerRole {
@@ -248,7 +1434,8 @@ struct DbPlayer {
     role: EventRole,
     user_id: i64,
     name: String,
-    class: Option<PlayerClass>
+    class: Option<PlayerClass>,
+    absence: Option<String>
 }
 
 struct DbFlexRole {
@@ -269,6 +1456,55 @@ impl Into<Event> for DbEvent {
             duration: DurationString::from_string(self.duration).unwrap(),
             scheduled_event: self.scheduled_event.map(|s| ScheduledEventId::new(s as u64)),
             notification_role: self.notification_role.map(|s| RoleId::new(s as u64)),
+            rules: serde_json::from_str(&self.serialized_rules).unwrap_or_default(),
+            class_quotas: vec![],
+            group_settings: GroupSettings {
+                size: self.group_size.map(|s| s as usize),
+                seed: self.group_seed as u64,
+                locks: vec![]
+            },
+            image: self.image,
+            status: self.status,
+            interested: vec![],
+            thread: self.thread_id.map(|t| ChannelId::new(t as u64)),
+            channel: self.channel_id.map(|c| ChannelId::new(c as u64)),
+            checkin: self.checkin_hours.map(|h| h as u32),
+            confirmed: vec![],
+            pvp: self.pvp_alliance.map(|alliance| PvpDetails {
+                campaign: self.pvp_campaign,
+                alliance,
+                style: self.pvp_style,
+                min_cp: self.pvp_min_cp.map(|cp| cp as u32)
+            })
+        }
+    }
+}
+
+impl From<DbFine> for Fine {
+    fn from(fine: DbFine) -> Self {
+        Fine {
+            id: fine.id,
+            user: UserId::new(fine.user_id as u64),
+            name: fine.name,
+            message: fine.message_id.map(|m| MessageId::new(m as u64)),
+            event_title: fine.event_title,
+            reason: fine.reason,
+            amount: fine.amount as u32,
+            created: DateTime::<Utc>::from_timestamp(fine.created_at.unix_timestamp(), 0).unwrap_or_default(),
+            waived: fine.waived_at.is_some()
+        }
+    }
+}
+
+impl From<DbVacation> for Vacation {
+    fn from(vacation: DbVacation) -> Self {
+        Vacation {
+            id: vacation.id,
+            user: UserId::new(vacation.user_id as u64),
+            name: vacation.name,
+            start: DateTime::<Utc>::from_timestamp(vacation.start_at.unix_timestamp(), 0).unwrap_or_default(),
+            end: DateTime::<Utc>::from_timestamp(vacation.end_at.unix_timestamp(), 0).unwrap_or_default(),
+            reason: vacation.reason
         }
     }
 }
@@ -289,7 +1525,8 @@ impl Into<Player> for &DbPlayer {
             id: UserId::new(self.user_id as u64),
             name: self.name.to_string(),
             class: self.class.clone(),
-            flex: vec![]
+            flex: vec![],
+            absence: self.absence.clone()
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use chrono::{Duration, Utc};
+use lazy_static::lazy_static;
+use serenity::all::{ButtonStyle, CreateButton, CreateEmbed, CreateMessage, Mention, MessageId, VoiceState};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use crate::discord::Discord;
+use crate::events::{AttendanceProposal, EventKind, EventRole, EventStatus, Player, VOICE_EARLY_MINUTES};
+use crate::prelude::*;
+
+lazy_static! {
+    static ref PROPOSAL_TASKS: Mutex<HashMap<MessageId, JoinHandle<()>>> = Mutex::new(HashMap::new());
+}
+
+/// Records the signed players joining or leaving the voice channel of the events in progress
+pub async fn voice_state_update(state: &VoiceState, store: &Store) -> Result<()> {
+    let now = Utc::now();
+    store.voice_left(state.user_id, state.channel_id, now).await?;
+
+    let (Some(channel), Some(guild)) = (state.channel_id, state.guild_id) else { return Ok(()) };
+    if ![EventKind::Trial, EventKind::PvP].iter().any(|kind| kind.voice_channel(guild) == channel) {
+        return Ok(());
+    }
+
+    for message in store.events_in_progress(Duration::try_minutes(VOICE_EARLY_MINUTES).unwrap_or_default()).await? {
+        let event = store.get_event(message).await?;
+        if event.kind.voice_channel(guild) == channel && event.role_of(state.user_id).is_some_and(|role| role != EventRole::Absent) {
+            store.voice_joined(message, state.user_id, channel, now).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Sends the attendance proposal to the leader once the event is over, replacing the previous task
+pub fn schedule_proposal(discord: Arc<dyn Discord>, message: MessageId, store: Arc<Store>) {
+    cancel_proposal(&message);
+    let handle = tokio::spawn(async move {
+        let Some(end) = store.get_event(message).await.ok().and_then(|e| e.end()) else { return };
+        let Ok(wait) = (end - Utc::now()).to_std() else { return };
+        tokio::time::sleep(wait).await;
+        if let Err(why) = propose_attendance(discord.as_ref(), &store, message).await {
+            warn!("Attendance proposal of {message} failed: {why:?}");
+        }
+    });
+    PROPOSAL_TASKS.lock().unwrap().insert(message, handle);
+}
+
+pub(crate) fn cancel_proposal(message: &MessageId) {
+    if let Some(task) = PROPOSAL_TASKS.lock().unwrap().remove(message) {
+        task.abort();
+    }
+}
+
+/// DMs the leader who was in the voice channel, nothing is sent when nobody was recorded.
+/// Returns whether the proposal was sent
+pub async fn propose_attendance<D: Discord + ?Sized>(discord: &D, store: &Store, message: MessageId) -> Result<bool> {
+    let event = store.get_event(message).await?;
+    let sessions = store.voice_presence(message).await?;
+    if sessions.is_empty() || event.status == EventStatus::Canceled {
+        return Ok(false);
+    }
+    let proposal = AttendanceProposal::new(&event, &sessions);
+    info!("Attendance proposal of {message}: {}", proposal.summary_es());
+
+    let names = |players: &[Player]| if players.is_empty() { "-".to_string() } else {
+        players.iter().map(|p| p.name.clone()).collect::<Vec<String>>().join("\n")
+    };
+    let late = if proposal.late.is_empty() { "-".to_string() } else {
+        proposal.late.iter().map(|(p, minutes)| format!("{} ({minutes} min)", p.name)).collect::<Vec<String>>().join("\n")
+    };
+    let place = event.channel.map(|c| format!(" de {}", Mention::Channel(c))).unwrap_or_default();
+
+    discord.dm(event.leader, CreateMessage::new()
+        .content(format!("Asistencia del evento{place} segun el canal de voz, los titulares sin presencia se marcaran como ausentes"))
+        .embed(CreateEmbed::new()
+            .title(format!("📋 {}", event.title))
+            .field("✅ Presentes", names(&proposal.present), true)
+            .field("⏱️ Tarde", late, true)
+            .field("❌ Sin presencia", names(&proposal.missing), true))
+        .button(CreateButton::new(answer_id("confirm", message))
+            .label("Confirmar")
+            .style(ButtonStyle::Success))
+        .button(CreateButton::new(answer_id("dismiss", message))
+            .label("Descartar")
+            .style(ButtonStyle::Secondary))
+    ).await?;
+    Ok(true)
+}
+
+/// Id of the proposal buttons, `attendance_<confirm|dismiss>_<message>`
+fn answer_id(action: &str, message: MessageId) -> String {
+    format!("attendance_{action}_{message}")
+}
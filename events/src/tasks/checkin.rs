@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use chrono::Utc;
+use lazy_static::lazy_static;
+use serenity::all::{ButtonStyle, ChannelId, CreateButton, CreateMessage, Mention, MessageId};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use crate::discord::Discord;
+use crate::events::{check_rules, Event, EventRole, EventStatus, NotificationKind, Player};
+use crate::messages::events::{feed::post_change, render_event, signup::signup_profile};
+use crate::prelude::*;
+use super::notifications::notify;
+
+lazy_static! {
+    static ref CHECKIN_TASKS: Mutex<HashMap<MessageId, JoinHandle<()>>> = Mutex::new(HashMap::new());
+}
+
+/// Opens and closes the check-in window of the event, replacing the previous task
+pub fn schedule_checkin(discord: Arc<dyn Discord>, channel: ChannelId, message: MessageId, store: Arc<Store>) {
+    cancel_checkin(&message);
+    let handle = tokio::spawn(async move {
+        let Ok(event) = store.get_event(message).await else { return };
+        let (Some(opens), Some(closes)) = (event.checkin_opens(), event.checkin_closes()) else { return };
+
+        if let Ok(wait) = (opens - Utc::now()).to_std() {
+            tokio::time::sleep(wait).await;
+            if let Err(why) = open_checkin(discord.as_ref(), &store, channel, message).await {
+                warn!("Check-in of {message} not opened: {why:?}");
+            }
+        }
+        let Ok(wait) = (closes - Utc::now()).to_std() else { return };
+        tokio::time::sleep(wait).await;
+        if let Err(why) = close_checkin(discord.as_ref(), &store, channel, message).await {
+            warn!("Check-in of {message} not closed: {why:?}");
+        }
+    });
+    CHECKIN_TASKS.lock().unwrap().insert(message, handle);
+}
+
+pub(crate) fn cancel_checkin(message: &MessageId) {
+    if let Some(task) = CHECKIN_TASKS.lock().unwrap().remove(message) {
+        task.abort();
+    }
+}
+
+/// Asks the starters and the flexible reserves to confirm their attendance.
+/// Returns whether the window was opened
+pub async fn open_checkin<D: Discord + ?Sized>(discord: &D, store: &Store, channel: ChannelId, message: MessageId) -> Result<bool> {
+    let event = store.get_event(message).await?;
+    let Some(closes) = event.checkin_closes() else { return Ok(false) };
+    if event.status != EventStatus::Scheduled {
+        return Ok(false);
+    }
+    info!("Check-in of {message} open until {closes}");
+
+    let channel = event.channel.unwrap_or(channel);
+    let starters: Vec<String> = event.unconfirmed().iter()
+        .map(|(_, p)| Mention::User(p.id).to_string())
+        .collect();
+    let reserves: Vec<String> = event.roles.iter()
+        .filter(|pr| pr.role == EventRole::Reserve)
+        .flat_map(|pr| pr.players.iter())
+        .filter(|p| !p.flex.is_empty() && !event.confirmed.contains(&p.id))
+        .map(|p| Mention::User(p.id).to_string())
+        .collect();
+    let mut content = format!("📋 Confirmad vuestra asistencia a **{}** antes de <t:{}:t>, los titulares sin confirmar pasan a reservas",
+                              event.title, closes.timestamp());
+    if !starters.is_empty() {
+        content.push_str(&format!("\nTitulares: {}", starters.join(" ")));
+    }
+    if !reserves.is_empty() {
+        content.push_str(&format!("\nReservas, si confirmais entrais en los huecos que queden: {}", reserves.join(" ")));
+    }
+
+    let target = event.thread.unwrap_or(channel);
+    let sent = discord.send_message(target, CreateMessage::new()
+        .content(content)
+        .button(CreateButton::new(confirm_id(channel, message))
+            .label("Confirmar asistencia")
+            .style(ButtonStyle::Success))).await?;
+    store.track_message(message, target, sent.id).await?;
+    render_event(discord, Some(channel), message, &event).await;
+    Ok(true)
+}
+
+/// Moves the starters that didn't confirm to reserve and fills their slots with confirmed reserves.
+/// Returns the number of players moved to reserve
+pub async fn close_checkin<D: Discord + ?Sized>(discord: &D, store: &Store, channel: ChannelId, message: MessageId) -> Result<usize> {
+    let mut event = store.get_event(message).await?;
+    if event.checkin.is_none() || event.status != EventStatus::Scheduled {
+        return Ok(0);
+    }
+
+    let channel = event.channel.unwrap_or(channel);
+    let missing: Vec<(EventRole, Player)> = event.unconfirmed().into_iter()
+        .map(|(role, player)| (role, player.clone()))
+        .collect();
+    info!("Check-in of {message} closed, {} starters didn't confirm", missing.len());
+
+    for (role, mut player) in missing.iter().cloned() {
+        if !player.flex.contains(&role) {
+            player.flex.insert(0, role);
+        }
+        event.add_player(EventRole::Reserve, player.clone());
+        store.signup_player(message, EventRole::Reserve, &player).await?;
+        post_change(discord, store, message, &event, None, &format!("⏰ **{}** no ha confirmado y pasa a reservas", player.name)).await?;
+        notify(discord, store, player.id, NotificationKind::Reserve, event.thread,
+               &format!("No has confirmado tu asistencia al evento de {} y pasas a reservas", Mention::Channel(channel))).await?;
+
+        let promoted = promote_reserve(discord, store, &mut event, message, role).await?;
+        let replacement = match &promoted {
+            Some(promoted) => {
+                post_change(discord, store, message, &event, None, &format!("⬆️ **{}** sube a {role}", promoted.name)).await?;
+                notify(discord, store, promoted.id, NotificationKind::Promotion, event.thread,
+                       &format!("Has entrado como {role} en el evento de {} en lugar de un titular sin confirmar", Mention::Channel(channel))).await?;
+                format!(", sube {}", promoted.name)
+            }
+            None => ", no hay reservas confirmadas para el hueco".to_string()
+        };
+        notify(discord, store, event.leader, NotificationKind::Reserve, event.thread,
+               &format!("{} no ha confirmado su asistencia al evento de {} y pasa a reservas{replacement}", player.name, Mention::Channel(channel))).await?;
+    }
+
+    if !missing.is_empty() {
+        render_event(discord, Some(channel), message, &event).await;
+    }
+    Ok(missing.len())
+}
+
+/// First reserve flexible to `role` that confirmed and meets the rules and the class quotas
+async fn promote_reserve<D: Discord + ?Sized>(discord: &D, store: &Store, event: &mut Event, message: MessageId, role: EventRole) -> Result<Option<Player>> {
+    let candidates: Vec<Player> = event.flex_reserves(role).into_iter()
+        .filter(|p| event.confirmed.contains(&p.id) && event.class_quota_full(p).is_none())
+        .cloned()
+        .collect();
+    let rules = event.effective_rules();
+    let guild = super::guild();
+
+    for mut player in candidates {
+        if !rules.is_empty() {
+            let Some(guild) = guild else { continue };
+            let Ok(member) = discord.member(guild, player.id).await else { continue };
+            let profile = signup_profile(store, event, &member, &player, message, vec![]).await?;
+            if check_rules(&rules, &profile).is_some() {
+                continue;
+            }
+        }
+
+        player.flex.retain(|r| *r != role);
+        let signed_role = event.add_player(role, player.clone());
+        store.signup_player(message, signed_role, &player).await?;
+        return Ok(Some(player));
+    }
+    Ok(None)
+}
+
+/// Id of the confirmation button, `checkin_confirm_<channel>_<message>`
+fn confirm_id(channel: ChannelId, message: MessageId) -> String {
+    format!("checkin_confirm_{channel}_{message}")
+}
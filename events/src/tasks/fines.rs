@@ -0,0 +1,139 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use chrono::Utc;
+use lazy_static::lazy_static;
+use serenity::all::{ChannelId, CreateEmbed, CreateMessage, Mention, MessageId, RoleId, UserId};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use crate::discord::Discord;
+use crate::events::{format_gold, next_fine_summary, penalty_amount, BankExport, Event, Fine, FineReason, Ledger, Player, Reconciliation};
+use crate::prelude::*;
+
+/// Members listed in the treasury summary, the embed can't be longer
+const MAX_SUMMARY_LINES: usize = 50;
+
+lazy_static! {
+    static ref SUMMARIES: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+    static ref TREASURY_CHANNEL: Mutex<Option<ChannelId>> = Mutex::new(None);
+}
+
+/// Channel where the treasurers get the weekly summary of the outstanding fines
+pub fn set_treasury_channel(channel: Option<ChannelId>) {
+    *TREASURY_CHANNEL.lock().unwrap() = channel;
+}
+
+/// Fines the player following the penalty rules of their roles and lets them know by DM.
+/// Returns `None` when no rule applies or they were already fined for the event
+pub async fn fine_player<D: Discord + ?Sized>(discord: &D, store: &Store, event: &Event, message: MessageId, player: &Player, roles: &[RoleId], reason: FineReason) -> Result<Option<Fine>> {
+    let Some(amount) = penalty_amount(&store.penalty_rules().await?, roles, reason) else { return Ok(None) };
+    let Some(fine) = store.add_fine(player.id, &player.name, message, &event.title, reason, amount).await? else { return Ok(None) };
+    info!("{} fined with {amount} for {message}", player.name);
+
+    let dm = format!("💸 Multa de **{}** por {} en **{}**. Ingresala en el banco del gremio, consulta tu saldo con `/fines me`",
+                     format_gold(amount), fine.reason.label_es().to_lowercase(), event.title);
+    if let Err(why) = discord.dm(player.id, CreateMessage::new().content(dm)).await {
+        warn!("Fine of {} not sent: {why:?}", player.name);
+    }
+    Ok(Some(fine))
+}
+
+/// Roles of the member, none when they can't be looked up so the default rule applies
+pub async fn member_roles<D: Discord + ?Sized>(discord: &D, player: &Player) -> Vec<RoleId> {
+    match super::guild() {
+        Some(guild) => discord.member(guild, player.id).await.map(|m| m.roles).unwrap_or_default(),
+        None => vec![]
+    }
+}
+
+/// Records the deposits of registered accounts as payments, oldest first and up to what each member owes.
+/// Deposits already imported are skipped, so the same export can be imported again
+pub async fn import_bank_export(store: &Store, export: &BankExport, imported_by: Option<UserId>) -> Result<Reconciliation> {
+    let mut deposits = export.deposits.clone();
+    deposits.sort_by_key(|d| d.at);
+    let accounts: Vec<String> = deposits.iter().map(|d| d.account.clone()).collect();
+    let users = store.eso_account_users(&accounts).await?;
+    let mut seen: HashSet<String> = store.imported_deposits(&deposits.iter().map(|d| d.key()).collect::<Vec<String>>()).await?
+        .into_iter()
+        .collect();
+    let mut owed: HashMap<UserId, u32> = Ledger::all(&store.get_fines(None).await?, &store.get_fine_payments(None).await?).into_iter()
+        .map(|l| (l.user, l.outstanding()))
+        .collect();
+    info!("Importing {} bank deposits, {} already imported", deposits.len(), seen.len());
+
+    let mut report = Reconciliation { invalid: export.invalid.clone(), ..Default::default() };
+    for deposit in deposits {
+        if !seen.insert(deposit.key()) {
+            report.duplicated += 1;
+            continue;
+        }
+        let Some(user) = users.get(&deposit.account.to_lowercase()).copied() else {
+            report.unmatched.push(deposit);
+            continue;
+        };
+
+        let owes = owed.entry(user).or_default();
+        let applied = deposit.amount.min(*owes);
+        if !store.add_bank_deposit(&deposit, user, applied, imported_by).await? {
+            report.duplicated += 1;
+            continue;
+        }
+        *owes -= applied;
+        if applied == deposit.amount {
+            report.matched.push((deposit, user));
+        } else {
+            report.overpaid.push((deposit, user, applied));
+        }
+    }
+    Ok(report)
+}
+
+/// Sends the weekly summaries every monday, replacing the previous task
+pub fn spawn_fine_summaries(discord: Arc<dyn Discord>, store: Arc<Store>) {
+    let handle = tokio::spawn(async move {
+        loop {
+            let now = Utc::now();
+            let wait = (next_fine_summary(now) - now).to_std().unwrap_or(std::time::Duration::ZERO);
+            tokio::time::sleep(wait).await;
+            if let Err(why) = send_fine_summary(discord.as_ref(), &store).await {
+                warn!("Fine summary failed: {why:?}");
+            }
+        }
+    });
+
+    if let Some(previous) = SUMMARIES.lock().unwrap().replace(handle) {
+        previous.abort();
+    }
+}
+
+/// Reminds every member with fines to pay and posts the totals to the treasury channel.
+/// Returns the number of members that owe gold
+pub async fn send_fine_summary<D: Discord + ?Sized>(discord: &D, store: &Store) -> Result<usize> {
+    let ledgers = Ledger::all(&store.get_fines(None).await?, &store.get_fine_payments(None).await?);
+    let mut debtors: Vec<&Ledger> = ledgers.iter().filter(|l| l.outstanding() > 0).collect();
+    debtors.sort_by_key(|l| std::cmp::Reverse(l.outstanding()));
+    info!("Fine summary, {} members owe gold", debtors.len());
+
+    for ledger in &debtors {
+        let dm = format!("💸 Tienes **{}** de multas pendientes, ingresalo en el banco del gremio. Detalle con `/fines me`",
+                         format_gold(ledger.outstanding()));
+        if let Err(why) = discord.dm(ledger.user, CreateMessage::new().content(dm)).await {
+            warn!("Fine reminder to {} not sent: {why:?}", ledger.user);
+        }
+    }
+
+    let channel = *TREASURY_CHANNEL.lock().unwrap();
+    if let Some(channel) = channel {
+        let total: u32 = debtors.iter().map(|l| l.outstanding()).sum();
+        let lines = if debtors.is_empty() { "Nadie debe nada 🎉".to_string() } else {
+            debtors.iter()
+                .take(MAX_SUMMARY_LINES)
+                .map(|l| format!("- {}: {}", Mention::User(l.user), format_gold(l.outstanding())))
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+        discord.send_message(channel, CreateMessage::new().embed(CreateEmbed::new()
+            .title("💰 Multas pendientes de la semana")
+            .description(format!("{lines}\n\n**Total:** {}", format_gold(total))))).await?;
+    }
+    Ok(debtors.len())
+}
@@ -1,96 +1,174 @@
+pub mod attendance;
+pub mod checkin;
+pub mod fines;
+pub mod notifications;
+pub mod staffing;
+
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use chrono::{DateTime, Duration, Utc};
 use lazy_static::lazy_static;
-use regex::Regex;
 use serenity::all::{ChannelId, CreateMessage, GuildId, Mention, MessageId, UserId};
 use serenity::builder::CreateEmbed;
-use serenity::client::Context;
 use sqlx::PgPool;
-use tokio::task::JoinHandle;
+use tokio::task::{AbortHandle, JoinHandle};
 use tracing::{event, Instrument, instrument, Level, trace_span};
+use crate::discord::Discord;
 use crate::events::{EventRole, Player};
 use crate::prelude::*;
 
 lazy_static! {
-    static ref HASHMAP: Mutex<HashMap<ChannelId, JoinHandle<()>>> = Mutex::new(HashMap::new());
+    static ref HASHMAP: Mutex<HashMap<MessageId, AbortHandle>> = Mutex::new(HashMap::new());
+    static ref GUILD: Mutex<Option<GuildId>> = Mutex::new(None);
+}
+
+/// Guild of the members looked up by the background tasks, like their roles to check the rules or the fines
+pub fn set_guild(guild: Option<GuildId>) {
+    *GUILD.lock().unwrap() = guild;
 }
 
-pub async fn reset_all_reminders(ctx: Arc<Context>, guild: GuildId, pool: PgPool) {
+pub(crate) fn guild() -> Option<GuildId> {
+    *GUILD.lock().unwrap()
+}
+
+/// Schedules again the tasks of every event that hasn't finished, an event that can't be loaded is skipped
+pub async fn reset_all_reminders(discord: Arc<dyn Discord>, guild: GuildId, pool: PgPool) {
     let span = trace_span!("ready_reminders");
     async move {
         let store = Arc::new(Store::new(pool));
-        let events = guild.scheduled_events(&ctx.http, false).await.unwrap();
-        for event in events {
-            if event.creator_id.unwrap() == UserId::new(1148032756899643412) {
-                let (_, channel_id, message) = parse_event_link(&event.description.unwrap());
-                let channel_id = ChannelId::new(channel_id);
-
-                let message = MessageId::new(message);
-                let event = store.get_event(message).await.unwrap();
-
-                set_reminder(event.datetime.unwrap(), ctx.clone(), channel_id, message, store.clone());
+        let messages = match store.unfinished_events().await {
+            Ok(messages) => messages,
+            Err(why) => {
+                event!(Level::ERROR, "Reminders not restored: {why:?}");
+                return;
+            }
+        };
+        for message in messages {
+            let event = match store.get_event(message).await {
+                Ok(event) => event,
+                Err(why) => {
+                    event!(Level::WARN, "Event {message} skipped, can't be loaded: {why:?}");
+                    continue;
+                }
+            };
+            // Every event gets its own reminder, even when several share a channel
+            match (event.datetime, crate::messages::events::event_channel(discord.as_ref(), guild, &event).await) {
+                (Some(datetime), Some(channel)) => set_reminder(datetime, discord.clone(), channel, message, store.clone()),
+                _ => event!(Level::WARN, "Event {message} skipped, its channel is unknown")
             }
         }
     }.instrument(span).await
 }
 
-#[instrument]
-pub fn set_reminder(date: DateTime<Utc>, ctx: Arc<Context>, channel: ChannelId, message: MessageId, store: Arc<Store>) {
-    unset_reminder(&channel);
+#[instrument(skip(discord))]
+pub fn set_reminder(date: DateTime<Utc>, discord: Arc<dyn Discord>, channel: ChannelId, message: MessageId, store: Arc<Store>) {
+    unset_reminder(&message);
+    staffing::schedule_checks(date, discord.clone(), channel, message, store.clone());
+    checkin::schedule_checkin(discord.clone(), channel, message, store.clone());
+    attendance::schedule_proposal(discord.clone(), message, store.clone());
+    if let Some(delay) = reminder_delay(date) {
+        spawn_reminder(delay, discord, channel, message, store);
+    }
+}
+
+/// Time left until 30 minutes before `date`, nothing when the event is already that close
+pub fn reminder_delay(date: DateTime<Utc>) -> Option<std::time::Duration> {
+    let duration = date - Utc::now() - Duration::try_minutes(30).unwrap();
+    event!(Level::TRACE, "{} minutes left", duration.num_minutes());
+    if duration.num_minutes() > 0 { duration.to_std().ok() } else { None }
+}
+
+/// Sends the invites of the event after `delay`, the returned handle finishes once they are out
+pub fn spawn_reminder(delay: std::time::Duration, discord: Arc<dyn Discord>, channel: ChannelId, message: MessageId, store: Arc<Store>) -> JoinHandle<()> {
     let handle = tokio::spawn(async move {
-        let duration = date - Utc::now() - Duration::try_minutes(30).unwrap();
-        event!(Level::TRACE, "{} minutes left", duration.num_minutes());
-        if duration.num_minutes() > 0 {
-            tokio::time::sleep(duration.to_std().unwrap()).await;
-            if let Some(event) = store.get_event(message).await.ok() {
-                let signed_members: Vec<Player> = event.roles
-                    .into_iter()
-                    .filter_map(|pr| if pr.role != EventRole::Reserve && pr.role != EventRole::Absent {
-                        Some(pr.players)
-                    } else { None })
-                    .flatten()
-                    .collect();
-
-                channel.send_message(&ctx.http, CreateMessage::new()
-                    .content(format!("__**Invitaciones para el RL**__\n```/script {}```", signed_members.iter()
+        tokio::time::sleep(delay).await;
+        if let Ok(event) = store.get_event(message).await {
+            let signed_members: Vec<Player> = event.roles
+                .iter()
+                .filter_map(|pr| if pr.role != EventRole::Reserve && pr.role != EventRole::Absent {
+                    Some(pr.players.clone())
+                } else { None })
+                .flatten()
+                .collect();
+
+            let interested: Vec<Player> = event.pending_interested().into_iter().cloned().collect();
+            let accounts = store.get_eso_accounts(&signed_members.iter().chain(interested.iter()).map(|p| p.id).collect::<Vec<UserId>>()).await.unwrap_or_default();
+            let groups = event.groups();
+            let mut invites = if groups.is_empty() {
+                format!("__**Invitaciones para el RL**__\n{}", invite_script(&signed_members, &accounts))
+            } else {
+                groups.iter().enumerate()
+                    .map(|(i, group)| format!("__**Invitaciones para el grupo {}**__\n{}", i + 1,
+                                              invite_script(&group.iter().map(|(_, p)| p.clone()).collect::<Vec<Player>>(), &accounts)))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            };
+
+            let missing: Vec<String> = signed_members.iter()
+                .filter(|p| !accounts.contains_key(&p.id))
+                .map(|p| Mention::User(p.id).to_string())
+                .collect();
+            if !interested.is_empty() {
+                invites.push_str(&format!("\n__**Interesados sin apuntar, invitar si hay hueco**__\n{}", invite_script(&interested, &accounts)));
+            }
+
+            if !missing.is_empty() {
+                invites.push_str(&format!("\n⚠️ Sin cuenta de ESO registrada, se usa su nombre de Discord: {}\nRegistradla con `/eso-account @handle`", missing.join(" ")));
+            }
+
+            let target = event.thread.unwrap_or(channel);
+            let sent = match discord.send_message(target, CreateMessage::new()
+                .content(invites)
+                .embed(CreateEmbed::new()
+                    .title("⏰ 30 minutos para iniciar el evento!")
+                    .field("Titulares", signed_members.iter()
                         .map(|u| {
-                            format!("GroupInviteByName(\"@{}\")", u.name)
-                        }).collect::<Vec<String>>().join(" ")
-                    ))
-                    .embed(CreateEmbed::new()
-                        .title("⏰ 30 minutos para iniciar el evento!")
-                        .field("Titulares", signed_members.iter()
-                            .map(|u| {
-                                Mention::User(u.id).to_string()
-                            }).collect::<Vec<String>>()
-                            .join("\n"), true)
-                    )
-                ).await.unwrap();
+                            Mention::User(u.id).to_string()
+                        }).collect::<Vec<String>>()
+                        .join("\n"), true)
+                )
+            ).await {
+                Ok(sent) => sent,
+                Err(why) => {
+                    event!(Level::WARN, "Reminder of {message} not sent: {why:?}");
+                    return;
+                }
+            };
+
+            if let Err(why) = store.track_message(message, target, sent.id).await {
+                event!(Level::WARN, "Reminder of {message} not tracked: {why:?}");
             }
         }
     });
 
-    HASHMAP.lock().unwrap().insert(channel, handle);
+    if let Some(previous) = HASHMAP.lock().unwrap().insert(message, handle.abort_handle()) {
+        previous.abort();
+    }
+    handle
+}
+
+/// Uses the registered ESO account of each player, or their display name as a guess
+fn invite_script(players: &[Player], accounts: &HashMap<UserId, String>) -> String {
+    format!("```/script {}```", players.iter()
+        .map(|u| match accounts.get(&u.id) {
+            Some(account) => format!("GroupInviteByName(\"{account}\")"),
+            None => format!("GroupInviteByName(\"@{}\")", u.name)
+        })
+        .collect::<Vec<String>>()
+        .join(" "))
 }
 
-fn unset_reminder(channel: &ChannelId) {
-    let task = HASHMAP.lock().unwrap().remove(channel);
+pub(crate) fn unset_reminder(message: &MessageId) {
+    staffing::cancel_checks(message);
+    checkin::cancel_checkin(message);
+    attendance::cancel_proposal(message);
+    let task = HASHMAP.lock().unwrap().remove(message);
     if let Some(task) = task {
         task.abort();
     }
 }
 
-#[instrument]
-pub fn parse_event_link(text: &str) -> (u64, u64, u64) {
-    lazy_static! {
-        static ref RE: Regex = Regex::new(r"^https:\/\/discord\.com\/channels\/(?P<guild>\d+)\/(?P<channel>\d+)\/(?P<msg>\d+)$").unwrap();
-    }
-
-    RE.captures(text.lines().next().unwrap()).and_then(|cap| Option::from({
-        (cap.name("guild").map(|max| max.as_str().parse::<u64>().unwrap()).unwrap(),
-         cap.name("channel").map(|max| max.as_str().parse::<u64>().unwrap()).unwrap(),
-         cap.name("msg").map(|max| max.as_str().parse::<u64>().unwrap()).unwrap()
-        )
-    })).unwrap()
-}
\ No newline at end of file
+/// Reminders still waiting to be sent
+pub(crate) fn active_reminders() -> usize {
+    HASHMAP.lock().unwrap().values().filter(|task| !task.is_finished()).count()
+}
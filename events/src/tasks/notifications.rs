@@ -0,0 +1,90 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use serenity::all::{ChannelId, CreateMessage, Mention, UserId};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use crate::discord::Discord;
+use crate::events::{digest, Notification, NotificationChannel, NotificationKind};
+use crate::prelude::*;
+
+/// How often the queue is checked for digests and failed sends
+const TICK: Duration = Duration::from_secs(60);
+/// Pause between two messages so a burst of digests stays under the Discord rate limits
+const SEND_INTERVAL: Duration = Duration::from_millis(500);
+/// Messages sent on each tick, the rest wait for the next one
+const MAX_SENDS_PER_TICK: usize = 50;
+/// Notifications that keep failing, like DMs to members that closed them, are given up
+const MAX_ATTEMPTS: i16 = 5;
+
+lazy_static! {
+    static ref NOTIFIER: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+}
+
+/// Queues a notification following the preferences of `user`, instant ones are sent right away
+/// and `thread` is used when they prefer the thread of the event
+pub async fn notify<D: Discord + ?Sized>(discord: &D, store: &Store, user: UserId, kind: NotificationKind, thread: Option<ChannelId>, content: &str) -> Result<()> {
+    let preferences = store.get_notification_preferences(user).await?;
+    let thread = match preferences.channel(kind) {
+        NotificationChannel::None => return Ok(()),
+        NotificationChannel::Thread => thread,
+        NotificationChannel::Dm => None
+    };
+
+    store.queue_notification(user, kind, thread, content, preferences.frequency.due_at(Utc::now())).await?;
+    deliver_due(discord, store, Some(user)).await?;
+    Ok(())
+}
+
+/// Sends the due notifications, one message per member and destination, returns the messages sent
+pub async fn deliver_due<D: Discord + ?Sized>(discord: &D, store: &Store, user: Option<UserId>) -> Result<usize> {
+    // One message per member and destination, every group has at least one notification
+    let mut groups: Vec<Vec<Notification>> = vec![];
+    for notification in store.due_notifications(user, MAX_ATTEMPTS).await? {
+        match groups.iter_mut().find(|g| g[0].user == notification.user && g[0].thread == notification.thread) {
+            Some(group) => group.push(notification),
+            None => groups.push(vec![notification])
+        }
+    }
+
+    let mut sent = 0;
+    for notifications in groups.into_iter().take(MAX_SENDS_PER_TICK) {
+        if sent > 0 {
+            tokio::time::sleep(SEND_INTERVAL).await;
+        }
+
+        let (user, thread) = (notifications[0].user, notifications[0].thread);
+        let content = digest(&notifications);
+        let result = match thread {
+            Some(thread) => discord.send_message(thread, CreateMessage::new().content(format!("{} {content}", Mention::User(user)))).await,
+            None => discord.dm(user, CreateMessage::new().content(content)).await
+        };
+        let ids: Vec<i32> = notifications.iter().map(|n| n.id).collect();
+        if let Err(why) = &result {
+            warn!("Notifications {ids:?} for {user} not sent: {why:?}");
+        }
+        store.notifications_delivered(&ids, result.is_ok()).await?;
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+/// Background task sending the digests when they are due and retrying the failed sends,
+/// replaces the previous one when the bot reconnects
+pub fn spawn_notifier(discord: Arc<dyn Discord>, store: Arc<Store>) {
+    let handle = tokio::spawn(async move {
+        let mut interval = tokio::time::interval(TICK);
+        loop {
+            interval.tick().await;
+            match deliver_due(discord.as_ref(), &store, None).await {
+                Ok(0) => {}
+                Ok(sent) => info!("{sent} notification messages sent"),
+                Err(why) => warn!("Notification queue not delivered: {why:?}")
+            }
+        }
+    });
+    if let Some(previous) = NOTIFIER.lock().unwrap().replace(handle) {
+        previous.abort();
+    }
+}
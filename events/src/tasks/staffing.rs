@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+use lazy_static::lazy_static;
+use serenity::all::{ChannelId, CreateActionRow, CreateMessage, Mention, MessageId};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use crate::discord::Discord;
+use crate::events::{Event, EventRole, EventScopes, EventStatus};
+use crate::prelude::*;
+
+/// Hours before the start of each composition check and how far it calls for players
+const CHECKS: [(i64, Reach); 2] = [(24, Reach::Reserves), (4, Reach::Everyone)];
+
+lazy_static! {
+    static ref CHECK_TASKS: Mutex<HashMap<MessageId, JoinHandle<()>>> = Mutex::new(HashMap::new());
+    static ref LFG_CHANNEL: Mutex<Option<ChannelId>> = Mutex::new(None);
+}
+
+/// Who is called when a capped role is missing players
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reach {
+    /// Reserves flexible to the role, or the notification role when there are none
+    Reserves,
+    /// Flexible reserves, the notification role and the LFG channel
+    Everyone
+}
+
+/// Channel where the open slots of every event are also posted in the last check
+pub fn set_lfg_channel(channel: Option<ChannelId>) {
+    *LFG_CHANNEL.lock().unwrap() = channel;
+}
+
+/// Schedules the composition checks of the event still ahead, replacing the previous ones
+pub fn schedule_checks(date: DateTime<Utc>, discord: Arc<dyn Discord>, channel: ChannelId, message: MessageId, store: Arc<Store>) {
+    cancel_checks(&message);
+    let handle = tokio::spawn(async move {
+        for (hours, reach) in CHECKS {
+            let wait = date - Duration::try_hours(hours).unwrap_or_default() - Utc::now();
+            let Ok(wait) = wait.to_std() else { continue };
+            tokio::time::sleep(wait).await;
+            if let Err(why) = check_staffing(discord.as_ref(), &store, channel, message, reach).await {
+                warn!("Composition check of {message} failed: {why:?}");
+            }
+        }
+    });
+    CHECK_TASKS.lock().unwrap().insert(message, handle);
+}
+
+pub(crate) fn cancel_checks(message: &MessageId) {
+    if let Some(task) = CHECK_TASKS.lock().unwrap().remove(message) {
+        task.abort();
+    }
+}
+
+/// Calls for players when a capped role is below its max, nothing is sent once every slot is filled.
+/// Returns whether there were open slots
+pub async fn check_staffing<D: Discord + ?Sized>(discord: &D, store: &Store, channel: ChannelId, message: MessageId, reach: Reach) -> Result<bool> {
+    let event = store.get_event(message).await?;
+    let open = event.open_slots();
+    if open.is_empty() || event.scope == EventScopes::Private || event.status != EventStatus::Scheduled {
+        return Ok(false);
+    }
+    info!("Event {message} is missing players: {open:?}");
+
+    let channel = event.channel.unwrap_or(channel);
+    let reserves: Vec<String> = open.iter()
+        .flat_map(|(role, _)| event.flex_reserves(*role))
+        .map(|p| Mention::User(p.id).to_string())
+        .fold(vec![], |mut mentions, mention| {
+            if !mentions.contains(&mention) {
+                mentions.push(mention);
+            }
+            mentions
+        });
+    let role = event.notification_role
+        .filter(|_| reach == Reach::Everyone || reserves.is_empty())
+        .map(|r| Mention::Role(r).to_string());
+    let mentions = reserves.into_iter().chain(role).collect::<Vec<String>>().join(" ");
+
+    let target = event.thread.unwrap_or(channel);
+    let sent = discord.send_message(target, alert(&event, &open, channel, message, &mentions)).await?;
+    store.track_message(message, target, sent.id).await?;
+
+    let lfg = *LFG_CHANNEL.lock().unwrap();
+    if let (Reach::Everyone, Some(lfg)) = (reach, lfg) {
+        let place = format!("Evento en {}", Mention::Channel(channel));
+        let sent = discord.send_message(lfg, alert(&event, &open, channel, message, &place)).await?;
+        store.track_message(message, lfg, sent.id).await?;
+    }
+    Ok(true)
+}
+
+/// Id of the join button, `staffing_join_<channel>_<message>_<role>`
+fn join_id(channel: ChannelId, message: MessageId, role: EventRole) -> String {
+    format!("staffing_join_{channel}_{message}_{}", role.to_id())
+}
+
+fn alert(event: &Event, open: &[(EventRole, usize)], channel: ChannelId, message: MessageId, footer: &str) -> CreateMessage {
+    let missing = open.iter().map(|(role, n)| format!("{n} {role}")).collect::<Vec<String>>().join(", ");
+    let when = event.datetime.map(|d| format!(" (<t:{}:R>)", d.timestamp())).unwrap_or_default();
+    CreateMessage::new()
+        .content(format!("🚨 Faltan jugadores para **{}**{when}: {missing}\n{footer}", event.title))
+        .components(join_buttons(channel, message, open))
+}
+
+/// Buttons of the alerts, one per open role
+pub(crate) fn join_buttons(channel: ChannelId, message: MessageId, open: &[(EventRole, usize)]) -> Vec<CreateActionRow> {
+    if open.is_empty() {
+        return vec![];
+    }
+    vec![CreateActionRow::Buttons(open.iter()
+        .map(|(role, _)| role.to_button(join_id(channel, message, *role), format!("Apuntarse como {role}")))
+        .collect())]
+}
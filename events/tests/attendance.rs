@@ -0,0 +1,115 @@
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use events::events::{AttendanceProposal, EventKind, EventRole, Player, VoiceSession};
+use events::messages::events::attendance_answer;
+use events::tasks::attendance::{propose_attendance, voice_state_update};
+use events::Store;
+use serenity::all::{ChannelId, GuildId, UserId, VoiceState};
+use common::*;
+
+const LEADER: u64 = 1800;
+
+fn voice_state(user: UserId, channel: Option<ChannelId>) -> VoiceState {
+    serde_json::from_value(json!({
+        "guild_id": GUILD.to_string(),
+        "channel_id": channel.map(|c| c.to_string()),
+        "user_id": user.to_string(),
+        "session_id": "session",
+        "deaf": false, "mute": false, "self_deaf": false, "self_mute": false,
+        "self_video": false, "suppress": false, "request_to_speak_timestamp": null
+    })).unwrap()
+}
+
+fn session(user: UserId, joined: DateTime<Utc>, left: Option<DateTime<Utc>>) -> VoiceSession {
+    VoiceSession { user, channel: ChannelId::new(1), joined, left }
+}
+
+#[test]
+fn proposal_splits_present_late_and_missing_starters() {
+    let start = Utc::now();
+    let minutes = |m: i64| start + Duration::try_minutes(m).unwrap();
+    let mut event = trial(LEADER, start);
+    let early = Player::new(UserId::new(1), "Early");
+    let late = Player::new(UserId::new(2), "Late");
+    let gone = Player::new(UserId::new(3), "Left before");
+    let missing = Player::new(UserId::new(4), "Missing");
+    let reserve = Player::new(UserId::new(5), "Reserve");
+    for player in [&early, &late, &gone, &missing] {
+        event.add_player(EventRole::DD, player.clone());
+    }
+    event.add_player(EventRole::Reserve, reserve.clone());
+
+    let sessions = vec![
+        session(early.id, minutes(-20), None),
+        session(late.id, minutes(25), Some(minutes(110))),
+        // Left well before the window opened
+        session(gone.id, minutes(-120), Some(minutes(-60))),
+        session(reserve.id, minutes(0), None)
+    ];
+    let proposal = AttendanceProposal::new(&event, &sessions);
+    assert_eq!(proposal.present.iter().map(|p| p.id).collect::<Vec<UserId>>(), vec![early.id]);
+    assert_eq!(proposal.late.iter().map(|(p, m)| (p.id, *m)).collect::<Vec<(UserId, i64)>>(), vec![(late.id, 25)]);
+    assert_eq!(proposal.missing.iter().map(|p| p.id).collect::<Vec<UserId>>(), vec![gone.id, missing.id]);
+}
+
+#[tokio::test]
+async fn voice_updates_record_signed_players_in_the_event_channel() {
+    let pool = pool().await;
+    let store = Store::new(pool.clone());
+    let voice = EventKind::Trial.voice_channel(GuildId::new(GUILD));
+    let mut event = trial(LEADER, Utc::now() + Duration::try_minutes(10).unwrap());
+    let signed = Player::new(UserId::new(next_id()), "Signed");
+    event.add_player(EventRole::Tank, signed.clone());
+    let message_id = stored_event(&pool, &event).await;
+    let stranger = UserId::new(next_id());
+
+    voice_state_update(&voice_state(signed.id, Some(voice)), &store).await.unwrap();
+    // Muting sends the same channel again
+    voice_state_update(&voice_state(signed.id, Some(voice)), &store).await.unwrap();
+    voice_state_update(&voice_state(stranger, Some(voice)), &store).await.unwrap();
+
+    let sessions = store.voice_presence(message_id).await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!((sessions[0].user, sessions[0].channel, sessions[0].left), (signed.id, voice, None));
+
+    voice_state_update(&voice_state(signed.id, None), &store).await.unwrap();
+    assert!(store.voice_presence(message_id).await.unwrap()[0].left.is_some());
+}
+
+#[tokio::test]
+async fn confirmed_proposal_marks_missing_starters_absent() {
+    let pool = pool().await;
+    let store = Store::new(pool.clone());
+    let voice = EventKind::Trial.voice_channel(GuildId::new(GUILD));
+    let start = Utc::now() - Duration::try_hours(2).unwrap();
+    let mut event = trial(LEADER, start);
+    event.add_player(EventRole::Healer, Player::new(UserId::new(LEADER), "Leader"));
+    let missing = Player::new(UserId::new(next_id()), "Sam");
+    event.add_player(EventRole::DD, missing.clone());
+    let message_id = stored_event(&pool, &event).await;
+    store.voice_joined(message_id, UserId::new(LEADER), voice, start - Duration::try_minutes(5).unwrap()).await.unwrap();
+
+    let discord = FakeDiscord::new();
+    assert!(propose_attendance(&discord, &store, message_id).await.unwrap());
+    let dm = discord.calls().into_iter().find_map(|c| match c {
+        Call::Dm(user, dm) if user == UserId::new(LEADER) => Some(dm),
+        _ => None
+    }).unwrap();
+    assert_eq!(dm["embeds"][0]["fields"][2]["value"], "Sam");
+    assert_eq!(dm["components"][0]["components"][0]["custom_id"], format!("attendance_confirm_{message_id}"));
+
+    let proposal = message_json(next_id(), next_id(), BOT);
+    let custom_id = format!("attendance_confirm_{message_id}");
+    attendance_answer(&button(&custom_id, &member(missing.id.get(), "Sam", &[]), &proposal), &discord, pool.clone()).await.unwrap();
+    attendance_answer(&button(&custom_id, &member(LEADER, "Leader", &[]), &proposal), &discord, pool.clone()).await.unwrap();
+
+    let answers: Vec<String> = discord.responses().iter().map(|r| r["data"]["content"].as_str().unwrap().to_string()).collect();
+    assert_eq!(answers[0], "Solo el lider del evento puede confirmar la asistencia");
+    assert_eq!(answers[1], "Asistencia confirmada: 1 presentes, 0 tarde, 1 sin presencia");
+    let event = store.get_event(message_id).await.unwrap();
+    let (role, player) = event.player(missing.id).unwrap();
+    assert_eq!((role, player.absence.as_deref()), (EventRole::Absent, Some("Sin presencia en el canal de voz")));
+    assert_eq!(event.role_of(UserId::new(LEADER)), Some(EventRole::Healer));
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use chrono::{Duration, TimeZone, Utc};
+use events::events::{parse_bank_export, BankDeposit, FineReason, Ledger};
+use events::tasks::fines::import_bank_export;
+use events::Store;
+use serenity::all::UserId;
+use common::*;
+
+const LEADER: u64 = 2000;
+
+#[test]
+fn exports_are_read_in_any_column_order() {
+    let export = parse_bank_export("\u{feff}Fecha;Cuenta;Oro\n\
+        2026-10-19 20:30:00;@Payer;10.000\n\
+        \n\
+        # comment\n\
+        \"1760900000\",\"3k\",\"@other.one\"\n\
+        @Payer\tWithdraw\t5000\t1760900000\n\
+        2026-10-19T21:00:00+02:00,@Payer,500\n\
+        @Payer,deposit,yesterday\n");
+
+    let at = |h, m| Utc.with_ymd_and_hms(2026, 10, 19, h, m, 0).unwrap();
+    assert_eq!(export.deposits, vec![
+        BankDeposit { account: "@Payer".to_string(), amount: 10000, at: at(20, 30) },
+        BankDeposit { account: "@other.one".to_string(), amount: 3000, at: Utc.timestamp_opt(1760900000, 0).unwrap() },
+        BankDeposit { account: "@Payer".to_string(), amount: 500, at: at(19, 0) }
+    ]);
+    assert_eq!(export.invalid, vec![8]);
+    assert_eq!(export.deposits[0].key(), "@payer|10000|1792441800");
+}
+
+#[tokio::test]
+async fn deposits_pay_fines_and_report_overpaid_and_unknown_accounts() {
+    let pool = pool().await;
+    let store = Store::new(pool.clone());
+    let message_id = stored_event(&pool, &trial(LEADER, Utc::now() - Duration::try_days(1).unwrap())).await;
+    let debtor = UserId::new(next_id());
+    let generous = UserId::new(next_id());
+    let (debtor_account, generous_account) = (format!("@D{debtor}"), format!("@G{generous}"));
+    store.set_eso_account(debtor, &debtor_account).await.unwrap();
+    store.set_eso_account(generous, &generous_account).await.unwrap();
+    store.add_fine(debtor, "Moroso", message_id, "Trial", FineReason::NoShow, 10000).await.unwrap().unwrap();
+
+    let start = Utc::now().timestamp() - 3600;
+    let export = parse_bank_export(&format!("account,gold,time\n\
+        {},4000,{}\n\
+        {debtor_account},8000,{}\n\
+        {generous_account},5000,{}\n\
+        @NobodyKnows,1000,{}\n",
+        debtor_account.to_lowercase(), start, start + 60, start + 120, start + 180));
+    let report = import_bank_export(&store, &export, None).await.unwrap();
+
+    assert_eq!(report.matched.iter().map(|(d, u)| (d.amount, *u)).collect::<Vec<(u32, UserId)>>(), vec![(4000, debtor)]);
+    assert_eq!(report.overpaid.iter().map(|(d, u, applied)| (d.amount, *u, *applied)).collect::<Vec<(u32, UserId, u32)>>(),
+               vec![(8000, debtor, 6000), (5000, generous, 0)]);
+    assert_eq!(report.unmatched.iter().map(|d| d.account.as_str()).collect::<Vec<&str>>(), vec!["@NobodyKnows"]);
+    let ledger = Ledger::new(debtor, &store.get_fines(Some(debtor)).await.unwrap(), &store.get_fine_payments(Some(debtor)).await.unwrap());
+    assert_eq!((ledger.outstanding(), ledger.paid), (0, 10000));
+    assert!(store.get_fine_payments(Some(generous)).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn reimporting_an_export_records_nothing_twice() {
+    let pool = pool().await;
+    let store = Store::new(pool.clone());
+    let message_id = stored_event(&pool, &trial(LEADER, Utc::now() - Duration::try_days(1).unwrap())).await;
+    let debtor = UserId::new(next_id());
+    let account = format!("@R{debtor}");
+    store.set_eso_account(debtor, &account).await.unwrap();
+    store.add_fine(debtor, "Moroso", message_id, "Trial", FineReason::LateAbsence, 10000).await.unwrap().unwrap();
+
+    let time = Utc::now().timestamp() - 600;
+    let text = format!("{account},3000,{time}\n{account},3000,{time}\n{account},2000,{}\n", time + 1);
+    let first = import_bank_export(&store, &parse_bank_export(&text), None).await.unwrap();
+    let again = import_bank_export(&store, &parse_bank_export(&text), None).await.unwrap();
+
+    assert_eq!((first.matched.len(), first.duplicated), (2, 1));
+    assert_eq!((again.matched.len(), again.overpaid.len(), again.duplicated), (0, 0, 3));
+    let ledger = Ledger::new(debtor, &store.get_fines(Some(debtor)).await.unwrap(), &store.get_fine_payments(Some(debtor)).await.unwrap());
+    assert_eq!((ledger.outstanding(), ledger.paid), (5000, 5000));
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::{EventRole, Player};
+use events::messages::events::checkin_confirm;
+use events::tasks::checkin::{close_checkin, open_checkin};
+use events::tasks::set_guild;
+use events::Store;
+use serenity::all::{ChannelId, GuildId, RoleId, UserId};
+use common::*;
+
+const LEADER: u64 = 1700;
+
+#[tokio::test]
+async fn open_window_asks_starters_and_marks_their_confirmation() {
+    let pool = pool().await;
+    let channel = ChannelId::new(next_id());
+    let mut event = trial(LEADER, Utc::now() + Duration::try_hours(3).unwrap());
+    event.channel = Some(channel);
+    event.checkin = Some(6);
+    let tank = member(next_id(), "Boromir", &[]);
+    let healer = member(next_id(), "Elrond", &[]);
+    event.add_player(EventRole::Tank, Player::new(tank.user.id, "Boromir"));
+    event.add_player(EventRole::Healer, Player::new(healer.user.id, "Elrond"));
+    let message_id = stored_event(&pool, &event).await;
+
+    let discord = FakeDiscord::new();
+    let store = Store::new(pool.clone());
+    assert!(open_checkin(&discord, &store, channel, message_id).await.unwrap());
+    let sent = discord.sent_messages();
+    let content = sent[0].1["content"].as_str().unwrap();
+    assert!(content.contains(&format!("<@{}>", tank.user.id)) && content.contains(&format!("<@{}>", healer.user.id)), "{content}");
+    assert_eq!(sent[0].1["components"][0]["components"][0]["custom_id"], format!("checkin_confirm_{channel}_{message_id}"));
+
+    let checkin = message_json(next_id(), channel.get(), BOT);
+    let custom_id = format!("checkin_confirm_{channel}_{message_id}");
+    checkin_confirm(&button(&custom_id, &tank, &checkin), &discord, pool.clone()).await.unwrap();
+    checkin_confirm(&button(&custom_id, &tank, &checkin), &discord, pool.clone()).await.unwrap();
+    checkin_confirm(&button(&custom_id, &member(next_id(), "Gollum", &[]), &checkin), &discord, pool.clone()).await.unwrap();
+
+    let answers: Vec<String> = discord.responses().iter().map(|r| r["data"]["content"].as_str().unwrap().to_string()).collect();
+    assert!(answers[0].starts_with("Asistencia confirmada como Tanks"), "{}", answers[0]);
+    assert_eq!(answers[1], "Ya habias confirmado tu asistencia");
+    assert_eq!(answers[2], "No estas apuntado a este evento");
+
+    let event = store.get_event(message_id).await.unwrap();
+    assert_eq!(event.confirmed, vec![tank.user.id]);
+    let fields = serde_json::to_value(event.embed()).unwrap()["fields"].to_string();
+    assert!(fields.contains("Boromir  ✅") && fields.contains("Elrond  ⏳"), "{fields}");
+}
+
+#[tokio::test]
+async fn markers_and_confirmations_wait_for_the_window() {
+    let pool = pool().await;
+    let channel = ChannelId::new(next_id());
+    let mut event = trial(LEADER, Utc::now() + Duration::try_hours(30).unwrap());
+    event.channel = Some(channel);
+    event.checkin = Some(6);
+    let tank = member(next_id(), "Faramir", &[]);
+    event.add_player(EventRole::Tank, Player::new(tank.user.id, "Faramir"));
+    let message_id = stored_event(&pool, &event).await;
+
+    let discord = FakeDiscord::new();
+    let checkin = message_json(next_id(), channel.get(), BOT);
+    checkin_confirm(&button(&format!("checkin_confirm_{channel}_{message_id}"), &tank, &checkin), &discord, pool.clone()).await.unwrap();
+    assert_eq!(discord.responses()[0]["data"]["content"], "El check-in de este evento aun no esta abierto");
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert!(event.confirmed.is_empty());
+    let fields = serde_json::to_value(event.embed()).unwrap()["fields"].to_string();
+    assert!(!fields.contains("⏳") && !fields.contains("✅"), "{fields}");
+}
+
+#[tokio::test]
+async fn closing_moves_unconfirmed_starters_and_promotes_confirmed_reserves() {
+    let pool = pool().await;
+    let channel = ChannelId::new(next_id());
+    let role = next_id();
+    let mut event = trial(LEADER, Utc::now() + Duration::try_minutes(50).unwrap());
+    event.channel = Some(channel);
+    event.checkin = Some(6);
+    event.notification_role = Some(RoleId::new(role));
+    event.set_max(EventRole::Healer, Some(2));
+    event.add_player(EventRole::Healer, Player::new(UserId::new(LEADER), "Leader"));
+    let absent_minded = Player::new(UserId::new(next_id()), "Pippin");
+    event.add_player(EventRole::Healer, absent_minded.clone());
+    let mut reserves = vec![];
+    for name in ["Sleepy", "Outsider", "Eager"] {
+        let mut reserve = Player::new(UserId::new(next_id()), name);
+        reserve.flex = vec![EventRole::Healer];
+        event.add_player(EventRole::Reserve, reserve.clone());
+        reserves.push(reserve);
+    }
+    let [sleepy, outsider, eager] = [&reserves[0], &reserves[1], &reserves[2]];
+    let message_id = stored_event(&pool, &event).await;
+
+    let store = Store::new(pool.clone());
+    store.confirm_checkin(message_id, outsider.id).await.unwrap();
+    store.confirm_checkin(message_id, eager.id).await.unwrap();
+    let discord = FakeDiscord::new();
+    discord.add_member(member(outsider.id.get(), "Outsider", &[]));
+    discord.add_member(member(eager.id.get(), "Eager", &[role]));
+    set_guild(Some(GuildId::new(GUILD)));
+    assert_eq!(close_checkin(&discord, &store, channel, message_id).await.unwrap(), 1);
+
+    let event = store.get_event(message_id).await.unwrap();
+    assert_eq!(event.role_of(UserId::new(LEADER)), Some(EventRole::Healer), "the leader doesn't have to confirm");
+    assert_eq!(event.role_of(eager.id), Some(EventRole::Healer));
+    assert_eq!(event.role_of(sleepy.id), Some(EventRole::Reserve), "unconfirmed reserves aren't promoted");
+    assert_eq!(event.role_of(outsider.id), Some(EventRole::Reserve), "reserves that break the rules aren't promoted");
+    let (role, moved) = event.player(absent_minded.id).unwrap();
+    assert_eq!((role, moved.flex.clone()), (EventRole::Reserve, vec![EventRole::Healer]));
+
+    let dms = discord.dms();
+    assert!(dms.iter().any(|(user, dm)| *user == absent_minded.id && dm.contains("pasas a reservas")), "{dms:?}");
+    assert!(dms.iter().any(|(user, dm)| *user == eager.id && dm.contains("Has entrado como Healers")), "{dms:?}");
+}
@@ -0,0 +1,396 @@
+//! Shared helpers for the event flow tests: a recording [`Discord`] fake, gateway payload
+//! builders and a migrated Postgres pool taken from `DATABASE_URL`
+#![allow(dead_code)]
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use duration_string::DurationString;
+use events::discord::Discord;
+use events::events::{Event, EventKind};
+use events::{Error, Store};
+use serde_json::{json, Value};
+use serenity::all::{ChannelId, CommandInteraction, ComponentInteraction, CreateInteractionResponse, CreateInteractionResponseFollowup, CreateMessage, CreateScheduledEvent, EditMessage, EditScheduledEvent, GuildId, InteractionId, Member, Message, MessageId, ModalInteraction, Permissions, ScheduledEvent, ScheduledEventId, UserId};
+use serenity::async_trait;
+use sqlx::PgPool;
+
+type Result<T> = std::result::Result<T, Error>;
+
+pub const GUILD: u64 = 1134046249293717514;
+pub const BOT: u64 = 1148032756899643412;
+
+/// Every Discord side effect performed by a flow, builders are kept as their json payload
+#[derive(Debug, Clone)]
+pub enum Call {
+    SendMessage(ChannelId, Value),
+    EditMessage(ChannelId, MessageId, Value),
+    DeleteMessages(ChannelId, Vec<MessageId>),
+    CreateResponse(InteractionId, Value),
+    CreateFollowup(Value),
+    Dm(UserId, Value),
+    CreateScheduledEvent(Value),
+    EditScheduledEvent(ScheduledEventId, Value),
+    DeleteScheduledEvent(ScheduledEventId),
+    ArchiveThread(ChannelId),
+}
+
+#[derive(Default)]
+pub struct FakeDiscord {
+    calls: Mutex<Vec<Call>>,
+    components: Mutex<VecDeque<ComponentInteraction>>,
+    modals: Mutex<VecDeque<ModalInteraction>>,
+    members: Mutex<HashMap<UserId, Member>>,
+    messages: Mutex<HashMap<ChannelId, Vec<Message>>>,
+    scheduled_events: Mutex<HashMap<ScheduledEventId, ScheduledEvent>>,
+}
+
+impl FakeDiscord {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a component interaction, handed out in order to whoever awaits one
+    pub fn click(&self, interaction: ComponentInteraction) -> &Self {
+        self.components.lock().unwrap().push_back(interaction);
+        self
+    }
+
+    pub fn submit(&self, modal: ModalInteraction) -> &Self {
+        self.modals.lock().unwrap().push_back(modal);
+        self
+    }
+
+    pub fn add_member(&self, member: Member) -> &Self {
+        self.members.lock().unwrap().insert(member.user.id, member);
+        self
+    }
+
+    pub fn add_message(&self, message: Message) -> &Self {
+        self.messages.lock().unwrap().entry(message.channel_id).or_default().push(message);
+        self
+    }
+
+    pub fn add_scheduled_event(&self, event: ScheduledEvent) -> &Self {
+        self.scheduled_events.lock().unwrap().insert(event.id, event);
+        self
+    }
+
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    pub fn dms(&self) -> Vec<(UserId, String)> {
+        self.calls().into_iter()
+            .filter_map(|c| match c {
+                Call::Dm(user, msg) => Some((user, msg["content"].as_str().unwrap_or_default().to_string())),
+                _ => None
+            })
+            .collect()
+    }
+
+    pub fn sent_messages(&self) -> Vec<(ChannelId, Value)> {
+        self.calls().into_iter()
+            .filter_map(|c| match c {
+                Call::SendMessage(channel, msg) => Some((channel, msg)),
+                _ => None
+            })
+            .collect()
+    }
+
+    pub fn responses(&self) -> Vec<Value> {
+        self.calls().into_iter()
+            .filter_map(|c| match c {
+                Call::CreateResponse(_, response) => Some(response),
+                _ => None
+            })
+            .collect()
+    }
+
+    fn record(&self, call: Call) {
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+#[async_trait]
+impl Discord for FakeDiscord {
+    async fn send_message(&self, channel: ChannelId, message: CreateMessage) -> Result<Message> {
+        self.record(Call::SendMessage(channel, serde_json::to_value(&message)?));
+        Ok(message_json(next_id(), channel.get(), BOT))
+    }
+
+    async fn edit_message(&self, channel: ChannelId, message: MessageId, edit: EditMessage) -> Result<Message> {
+        self.record(Call::EditMessage(channel, message, serde_json::to_value(&edit)?));
+        Ok(message_json(message.get(), channel.get(), BOT))
+    }
+
+    async fn channel_messages(&self, channel: ChannelId) -> Result<Vec<Message>> {
+        Ok(self.messages.lock().unwrap().get(&channel).cloned().unwrap_or_default())
+    }
+
+    async fn delete_messages(&self, channel: ChannelId, messages: Vec<MessageId>) -> Result<()> {
+        if let Some(stored) = self.messages.lock().unwrap().get_mut(&channel) {
+            stored.retain(|m| !messages.contains(&m.id));
+        }
+        self.record(Call::DeleteMessages(channel, messages));
+        Ok(())
+    }
+
+    async fn create_response(&self, interaction: InteractionId, _token: &str, response: CreateInteractionResponse) -> Result<()> {
+        // Discord only takes one response per interaction
+        if self.calls().iter().any(|c| matches!(c, Call::CreateResponse(id, _) if *id == interaction)) {
+            return Err(Error::UnknownInteraction(format!("interaction {interaction} already acknowledged")));
+        }
+        self.record(Call::CreateResponse(interaction, serde_json::to_value(&response)?));
+        Ok(())
+    }
+
+    async fn create_followup(&self, _token: &str, followup: CreateInteractionResponseFollowup) -> Result<Message> {
+        self.record(Call::CreateFollowup(serde_json::to_value(&followup)?));
+        Ok(message_json(next_id(), 1, BOT))
+    }
+
+    async fn get_response(&self, _token: &str) -> Result<Message> {
+        Ok(message_json(next_id(), 1, BOT))
+    }
+
+    async fn member(&self, _guild: GuildId, user: UserId) -> Result<Member> {
+        Ok(self.members.lock().unwrap().get(&user).cloned().unwrap_or_else(|| member(user.get(), "Unknown", &[])))
+    }
+
+    async fn dm(&self, user: UserId, message: CreateMessage) -> Result<Message> {
+        self.record(Call::Dm(user, serde_json::to_value(&message)?));
+        Ok(message_json(next_id(), 1, BOT))
+    }
+
+    async fn scheduled_event(&self, _guild: GuildId, event: ScheduledEventId) -> Result<ScheduledEvent> {
+        self.scheduled_events.lock().unwrap().get(&event).cloned()
+            .ok_or(Error::UnknownInteraction(format!("scheduled event {event}")))
+    }
+
+    async fn scheduled_events(&self, _guild: GuildId) -> Result<Vec<ScheduledEvent>> {
+        Ok(self.scheduled_events.lock().unwrap().values().cloned().collect())
+    }
+
+    async fn create_scheduled_event(&self, _guild: GuildId, event: CreateScheduledEvent<'_>) -> Result<ScheduledEvent> {
+        let payload = serde_json::to_value(&event)?;
+        self.record(Call::CreateScheduledEvent(payload.clone()));
+        let created = scheduled_event(next_id(), payload["description"].as_str().unwrap_or_default(), 1);
+        self.add_scheduled_event(created.clone());
+        Ok(created)
+    }
+
+    async fn edit_scheduled_event(&self, _guild: GuildId, event: ScheduledEventId, edit: EditScheduledEvent<'_>) -> Result<ScheduledEvent> {
+        self.record(Call::EditScheduledEvent(event, serde_json::to_value(&edit)?));
+        self.scheduled_event(GuildId::new(GUILD), event).await
+    }
+
+    async fn delete_scheduled_event(&self, _guild: GuildId, event: ScheduledEventId) -> Result<()> {
+        self.scheduled_events.lock().unwrap().remove(&event);
+        self.record(Call::DeleteScheduledEvent(event));
+        Ok(())
+    }
+
+    async fn archive_thread(&self, thread: ChannelId) -> Result<()> {
+        self.record(Call::ArchiveThread(thread));
+        Ok(())
+    }
+
+    async fn await_component(&self, _message: MessageId, _timeout: Option<Duration>) -> Option<ComponentInteraction> {
+        self.components.lock().unwrap().pop_front()
+    }
+
+    async fn await_modal(&self, _message: MessageId, _timeout: Option<Duration>) -> Option<ModalInteraction> {
+        self.modals.lock().unwrap().pop_front()
+    }
+}
+
+/// Unique snowflake-like ids so tests can share one database
+pub fn next_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let _ = NEXT.compare_exchange(0, Utc::now().timestamp_micros() as u64, Ordering::SeqCst, Ordering::SeqCst);
+    NEXT.fetch_add(1, Ordering::SeqCst)
+}
+
+pub async fn pool() -> PgPool {
+    // event embeds read the images from the repository assets
+    std::env::set_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/..")).unwrap();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must point to a disposable test database");
+    let pool = PgPool::connect(&url).await.unwrap();
+    sqlx::migrate!("../migrations").run(&pool).await.unwrap();
+    pool
+}
+
+pub fn trial(leader: u64, datetime: DateTime<Utc>) -> Event {
+    let mut event = Event::new("vSS HM".to_string(), DurationString::from_string("2h".to_string()).unwrap(),
+                               "Se empezara a montar 10 minutos antes".to_string(), UserId::new(leader), EventKind::Trial);
+    event.datetime = Some(datetime);
+    event
+}
+
+/// Stores `event` under a fresh message id and returns it
+pub async fn stored_event(pool: &PgPool, event: &Event) -> MessageId {
+    let message = MessageId::new(next_id());
+    Store::new(pool.clone()).create_event(message, event).await.unwrap();
+    message
+}
+
+pub fn user_json(id: u64, name: &str) -> Value {
+    json!({ "id": id.to_string(), "username": name, "global_name": name, "discriminator": "0", "avatar": null })
+}
+
+fn member_json(id: u64, name: &str, roles: &[u64]) -> Value {
+    json!({
+        "user": user_json(id, name),
+        "nick": name,
+        "roles": roles.iter().map(|r| r.to_string()).collect::<Vec<String>>(),
+        "joined_at": "2024-01-01T00:00:00.000000+00:00",
+        "deaf": false,
+        "mute": false,
+        "flags": 0,
+        "guild_id": GUILD.to_string()
+    })
+}
+
+pub fn member(id: u64, name: &str, roles: &[u64]) -> Member {
+    serde_json::from_value(member_json(id, name, roles)).unwrap()
+}
+
+/// Member with `MANAGE_EVENTS`, like the officers that use `/roster`
+pub fn officer(id: u64, name: &str) -> Member {
+    let mut value = member_json(id, name, &[]);
+    value["permissions"] = json!(Permissions::MANAGE_EVENTS.bits().to_string());
+    serde_json::from_value(value).unwrap()
+}
+
+fn message_value(id: u64, channel: u64, author: u64) -> Value {
+    json!({
+        "id": id.to_string(),
+        "channel_id": channel.to_string(),
+        "author": user_json(author, "author"),
+        "content": "",
+        "timestamp": "2024-01-01T00:00:00.000000+00:00",
+        "edited_timestamp": null,
+        "tts": false,
+        "mention_everyone": false,
+        "mentions": [],
+        "mention_roles": [],
+        "attachments": [],
+        "embeds": [],
+        "pinned": false,
+        "type": 0
+    })
+}
+
+pub fn message_json(id: u64, channel: u64, author: u64) -> Message {
+    serde_json::from_value(message_value(id, channel, author)).unwrap()
+}
+
+fn interaction_value(kind: u8, data: Value, user: &Member, message: &Message) -> Value {
+    let mut member = member_json(user.user.id.get(), &user.user.name, &user.roles.iter().map(|r| r.get()).collect::<Vec<u64>>());
+    if let Some(permissions) = user.permissions {
+        member["permissions"] = json!(permissions.bits().to_string());
+    }
+    json!({
+        "id": next_id().to_string(),
+        "application_id": BOT.to_string(),
+        "type": kind,
+        "data": data,
+        "guild_id": GUILD.to_string(),
+        "channel_id": message.channel_id.to_string(),
+        "member": member,
+        "token": "token",
+        "version": 1,
+        "message": serde_json::to_value(message).unwrap(),
+        "app_permissions": "0",
+        "locale": "es-ES",
+        "guild_locale": "es-ES",
+        "entitlements": []
+    })
+}
+
+/// Button click on `message`
+pub fn button(custom_id: &str, user: &Member, message: &Message) -> ComponentInteraction {
+    serde_json::from_value(interaction_value(3, json!({ "custom_id": custom_id, "component_type": 2 }), user, message)).unwrap()
+}
+
+/// String select on a message whose first embed description is `embed_description`, the
+/// signup flow keeps the selected flex roles there
+pub fn string_select(custom_id: &str, values: &[&str], user: &Member, embed_description: Option<&str>) -> ComponentInteraction {
+    let mut message = message_value(next_id(), 1, BOT);
+    if let Some(description) = embed_description {
+        message["embeds"] = json!([{ "description": description }]);
+    }
+    let message: Message = serde_json::from_value(message).unwrap();
+    serde_json::from_value(interaction_value(3, json!({ "custom_id": custom_id, "component_type": 3, "values": values }), user, &message)).unwrap()
+}
+
+pub fn user_select(custom_id: &str, users: &[&Member], by: &Member) -> ComponentInteraction {
+    let message = message_json(next_id(), 1, BOT);
+    let ids: Vec<String> = users.iter().map(|m| m.user.id.to_string()).collect();
+    let mut resolved_users = serde_json::Map::new();
+    for user in users {
+        resolved_users.insert(user.user.id.to_string(), user_json(user.user.id.get(), &user.user.name));
+    }
+    serde_json::from_value(interaction_value(3, json!({
+        "custom_id": custom_id, "component_type": 5, "values": ids,
+        "resolved": { "users": resolved_users }
+    }), by, &message)).unwrap()
+}
+
+pub fn modal(custom_id: &str, inputs: &[(&str, &str)], user: &Member) -> ModalInteraction {
+    modal_on(custom_id, inputs, user, &message_json(next_id(), 1, BOT))
+}
+
+/// Modal opened from a button of `message`
+pub fn modal_on(custom_id: &str, inputs: &[(&str, &str)], user: &Member, message: &Message) -> ModalInteraction {
+    let rows: Vec<Value> = inputs.iter()
+        .map(|(id, value)| json!({ "type": 1, "components": [{ "type": 4, "custom_id": id, "value": value }] }))
+        .collect();
+    serde_json::from_value(interaction_value(5, json!({ "custom_id": custom_id, "components": rows }), user, message)).unwrap()
+}
+
+/// Message context menu command (`Edit event`, `Delete event`) targeting `target`
+pub fn message_command(name: &str, target: &Message, user: &Member) -> CommandInteraction {
+    let mut messages = serde_json::Map::new();
+    messages.insert(target.id.to_string(), serde_json::to_value(target).unwrap());
+    let mut value = interaction_value(2, json!({
+        "id": next_id().to_string(), "name": name, "type": 3, "target_id": target.id.to_string(),
+        "resolved": { "messages": messages }
+    }), user, target);
+    value["channel"] = json!({ "id": target.channel_id.to_string(), "type": 0, "name": "miercoles-a" });
+    value.as_object_mut().unwrap().remove("message");
+    serde_json::from_value(value).unwrap()
+}
+
+/// Slash command `/{name} {subcommand}` with string options
+pub fn slash_command(name: &str, subcommand: &str, options: &[(&str, &str)], user: &Member) -> CommandInteraction {
+    let message = message_json(next_id(), 1, BOT);
+    let options: Vec<Value> = options.iter()
+        .map(|(name, value)| json!({ "name": name, "type": 3, "value": value }))
+        .collect();
+    let mut value = interaction_value(2, json!({
+        "id": next_id().to_string(), "name": name, "type": 1,
+        "options": [{ "name": subcommand, "type": 1, "options": options }]
+    }), user, &message);
+    value.as_object_mut().unwrap().remove("message");
+    serde_json::from_value(value).unwrap()
+}
+
+pub fn scheduled_event(id: u64, description: &str, status: u8) -> ScheduledEvent {
+    serde_json::from_value(json!({
+        "id": id.to_string(),
+        "guild_id": GUILD.to_string(),
+        "channel_id": "1144350408769286274",
+        "creator_id": BOT.to_string(),
+        "name": "vSS HM",
+        "description": description,
+        "scheduled_start_time": "2024-01-01T20:00:00.000000+00:00",
+        "scheduled_end_time": "2024-01-01T22:00:00.000000+00:00",
+        "privacy_level": 2,
+        "status": status,
+        "entity_type": 2,
+        "entity_metadata": null
+    })).unwrap()
+}
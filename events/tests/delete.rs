@@ -0,0 +1,103 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::messages::events::delete_event;
+use events::Store;
+use serenity::all::{ChannelId, MessageId};
+use common::*;
+
+const LEADER: u64 = 300;
+
+#[tokio::test]
+async fn delete_pending_event_asks_for_confirmation() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(3).unwrap());
+    let scheduled = scheduled_event(next_id(), "", 1);
+    event.scheduled_event = Some(scheduled.id);
+    let message_id = stored_event(&pool, &event).await;
+    let channel = next_id();
+    let event_message = message_json(message_id.get(), channel, BOT);
+    let mut pinned = message_json(next_id(), channel, BOT);
+    pinned.pinned = true;
+    let leader = member(LEADER, "Leader", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.add_scheduled_event(scheduled.clone())
+        .add_message(event_message.clone())
+        .add_message(pinned.clone())
+        .click(button("delete_event_confirm", &leader, &event_message));
+
+    delete_event(&message_command("Delete event", &event_message, &leader), &discord, pool.clone()).await.unwrap();
+
+    assert!(Store::new(pool).get_event(message_id).await.is_err());
+    assert!(discord.calls().iter().any(|c| matches!(c, Call::DeleteScheduledEvent(id) if *id == scheduled.id)));
+    let deleted: Vec<MessageId> = discord.calls().into_iter()
+        .filter_map(|c| match c { Call::DeleteMessages(_, ids) => Some(ids), _ => None })
+        .flatten()
+        .collect();
+    assert_eq!(deleted, vec![message_id]);
+    assert!(discord.responses()[0]["data"]["content"].as_str().unwrap().contains("SEGURO QUE QUIERES BORRARLO"));
+}
+
+#[tokio::test]
+async fn delete_finished_event_purges_without_confirmation() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() - Duration::try_days(1).unwrap());
+    let scheduled = scheduled_event(next_id(), "", 3);
+    event.scheduled_event = Some(scheduled.id);
+    let message_id = stored_event(&pool, &event).await;
+    let event_message = message_json(message_id.get(), next_id(), BOT);
+    let leader = member(LEADER, "Leader", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.add_scheduled_event(scheduled.clone()).add_message(event_message.clone());
+
+    delete_event(&message_command("Delete event", &event_message, &leader), &discord, pool.clone()).await.unwrap();
+
+    assert!(Store::new(pool).get_event(message_id).await.is_err());
+    assert!(!discord.calls().iter().any(|c| matches!(c, Call::DeleteScheduledEvent(_))));
+    assert_eq!(discord.responses()[0]["data"]["content"], "Purgado!");
+}
+
+#[tokio::test]
+async fn delete_other_messages_is_refused() {
+    let pool = pool().await;
+    let user = member(next_id(), "Someone", &[]);
+    let random_message = message_json(next_id(), next_id(), user.user.id.get());
+
+    let discord = FakeDiscord::new();
+    discord.add_message(random_message.clone());
+
+    delete_event(&message_command("Delete event", &random_message, &user), &discord, pool).await.unwrap();
+
+    assert!(!discord.calls().iter().any(|c| matches!(c, Call::DeleteMessages(..))));
+    assert_eq!(discord.responses().len(), 1);
+}
+
+#[tokio::test]
+async fn delete_keeps_other_events_of_the_channel() {
+    let pool = pool().await;
+    let store = Store::new(pool.clone());
+    let channel = next_id();
+    let event = trial(LEADER, Utc::now() - Duration::try_days(1).unwrap());
+    let deleted_id = stored_event(&pool, &event).await;
+    let kept_id = stored_event(&pool, &event).await;
+    let reminder = MessageId::new(next_id());
+    let other_reminder = MessageId::new(next_id());
+    store.track_message(deleted_id, ChannelId::new(channel), reminder).await.unwrap();
+    store.track_message(kept_id, ChannelId::new(channel), other_reminder).await.unwrap();
+    let event_message = message_json(deleted_id.get(), channel, BOT);
+    let leader = member(LEADER, "Leader", &[]);
+
+    let discord = FakeDiscord::new();
+    delete_event(&message_command("Delete event", &event_message, &leader), &discord, pool.clone()).await.unwrap();
+
+    let deleted: Vec<MessageId> = discord.calls().into_iter()
+        .filter_map(|c| match c { Call::DeleteMessages(_, ids) => Some(ids), _ => None })
+        .flatten()
+        .collect();
+    assert_eq!(deleted, vec![deleted_id, reminder]);
+    assert!(store.get_event(deleted_id).await.is_err());
+    assert!(store.get_event(kept_id).await.is_ok());
+    assert_eq!(store.get_tracked_messages(kept_id).await.unwrap(), vec![(ChannelId::new(channel), other_reminder)]);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use events::events::{EventRole, Player};
+use events::messages::events::edit_event;
+use events::Store;
+use serenity::all::ScheduledEventId;
+use common::*;
+
+const LEADER: u64 = 200;
+
+#[tokio::test]
+async fn edit_moves_selected_players_to_reserve() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(2).unwrap());
+    let player = member(next_id(), "Lagging DD", &[]);
+    event.add_player(EventRole::DD, Player::new(player.user.id, "Lagging DD"));
+    let message_id = stored_event(&pool, &event).await;
+    let officer = member(LEADER, "Officer", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.add_member(player.clone())
+        .click(button("edit_role_reservas", &officer, &message_json(next_id(), 1, BOT)))
+        .click(user_select("edit_role_select_Reservas", &[&player], &officer));
+
+    edit_event(&message_command("Edit event", &message_json(message_id.get(), 10, BOT), &officer), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert!(event.roles.iter().find(|pr| pr.role == EventRole::DD).unwrap().players.is_empty());
+    assert_eq!(event.roles.iter().find(|pr| pr.role == EventRole::Reserve).unwrap().players[0].id, player.user.id);
+    assert!(discord.calls().iter().any(|c| matches!(c, Call::EditMessage(_, id, _) if *id == message_id)));
+}
+
+#[tokio::test]
+async fn edit_leader_steals_the_event() {
+    let pool = pool().await;
+    let message_id = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(2).unwrap())).await;
+    let thief = member(next_id(), "Thief", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.click(button("edit_leader", &thief, &message_json(next_id(), 1, BOT)));
+
+    edit_event(&message_command("Edit event", &message_json(message_id.get(), 10, BOT), &thief), &discord, pool.clone()).await.unwrap();
+
+    assert_eq!(Store::new(pool).get_event(message_id).await.unwrap().leader, thief.user.id);
+}
+
+#[tokio::test]
+async fn edit_datetime_moves_the_scheduled_event() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(2).unwrap());
+    let scheduled = scheduled_event(next_id(), "", 1);
+    event.scheduled_event = Some(scheduled.id);
+    let message_id = stored_event(&pool, &event).await;
+    let leader = member(LEADER, "Leader", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.add_scheduled_event(scheduled.clone())
+        .click(button("edit_datetime", &leader, &message_json(next_id(), 1, BOT)))
+        .submit(modal("edit_datetime_modal", &[("edit_datetime_field", "<t:1907929800:F>")], &leader));
+
+    edit_event(&message_command("Edit event", &message_json(message_id.get(), 10, BOT), &leader), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert_eq!(event.datetime, DateTime::<Utc>::from_timestamp(1907929800, 0));
+    let edits: Vec<ScheduledEventId> = discord.calls().into_iter()
+        .filter_map(|c| match c { Call::EditScheduledEvent(id, _) => Some(id), _ => None })
+        .collect();
+    assert_eq!(edits, vec![scheduled.id]);
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::{check_rules, EligibilityRule, EventRole, Player, PlayerClass, RuleOutcome, SignupProfile};
+use events::messages::events::{signup_approval, signup_event};
+use events::messages::events::rules::event_rules;
+use events::Store;
+use serenity::all::{RoleId, UserId};
+use common::*;
+
+const LEADER: u64 = 600;
+
+fn players_in(event: &events::events::Event, role: EventRole) -> Vec<UserId> {
+    event.roles.iter().find(|pr| pr.role == role).unwrap().players.iter().map(|p| p.id).collect()
+}
+
+#[test]
+fn rules_round_trip_and_strictest_outcome_wins() {
+    let rules = EligibilityRule::parse_list("rol <@&42> => reserva; clase Warden,Templar => rechazar\nasistencia 80% => aprobacion").unwrap();
+    assert_eq!(rules.iter().map(|r| r.to_string()).collect::<Vec<_>>(), vec![
+        "rol <@&42> => reserva", "clase Warden,Templar => rechazar", "asistencia 80% => aprobacion"
+    ]);
+    assert!(EligibilityRule::parse_list("rol @nadie => reserva").is_err());
+    assert!(EligibilityRule::parse_list("max-semana 2 => expulsar").is_err());
+
+    let profile = SignupProfile { roles: vec![], class: Some(PlayerClass::Warden), week_starter_signups: 0, attendance: Some(50), overlapping: vec![] };
+    let verdict = check_rules(&rules, &profile).unwrap();
+    assert_eq!(verdict.outcome, RuleOutcome::Approval);
+    assert_eq!(verdict.reasons.len(), 2);
+
+    let profile = SignupProfile { roles: vec![RoleId::new(42)], class: Some(PlayerClass::Templar), week_starter_signups: 0, attendance: None, overlapping: vec![] };
+    assert_eq!(check_rules(&rules, &profile), None);
+}
+
+#[tokio::test]
+async fn rejected_signup_is_not_stored() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    event.rules = EligibilityRule::parse_list("clase Necromancer => rechazar").unwrap();
+    let message_id = stored_event(&pool, &event).await;
+    let player = member(next_id(), "Wrong class", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.click(string_select("signup_class", &["Sorcerer"], &player, None));
+    signup_event(&button("signup_tanks", &player, &message_json(message_id.get(), 10, BOT)), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert_eq!(event.role_of(player.user.id), None);
+    assert!(discord.dms().is_empty());
+    let response = discord.responses().last().unwrap().clone();
+    assert!(response["data"]["embeds"][0]["description"].as_str().unwrap().contains("Nigromante"));
+}
+
+#[tokio::test]
+async fn weekly_limit_asks_leader_for_approval() {
+    let pool = pool().await;
+    let datetime = Utc::now() + Duration::try_days(1).unwrap();
+    let player = member(next_id(), "Eager", &[]);
+
+    let mut other = trial(LEADER, datetime);
+    other.add_player(EventRole::DD, Player::new(player.user.id, "Eager"));
+    stored_event(&pool, &other).await;
+
+    let mut event = trial(LEADER, datetime + Duration::try_hours(1).unwrap());
+    event.rules = EligibilityRule::parse_list("max-semana 1 => aprobacion").unwrap();
+    let message_id = stored_event(&pool, &event).await;
+    let event_message = message_json(message_id.get(), 10, BOT);
+
+    let discord = FakeDiscord::new();
+    discord.click(string_select("signup_class", &["Arcanist"], &player, None));
+    signup_event(&button("signup_dd", &player, &event_message), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool.clone()).get_event(message_id).await.unwrap();
+    assert_eq!(players_in(&event, EventRole::Reserve), vec![player.user.id]);
+    let approve = discord.calls().into_iter()
+        .find_map(|c| match c {
+            Call::Dm(user, msg) if user == UserId::new(LEADER) => msg["components"][0]["components"][0]["custom_id"].as_str().map(str::to_string),
+            _ => None
+        })
+        .expect("approval DM");
+
+    let leader = member(LEADER, "Leader", &[]);
+    signup_approval(&button(&approve, &leader, &message_json(next_id(), 1, BOT)), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert_eq!(players_in(&event, EventRole::DD), vec![player.user.id]);
+    assert!(players_in(&event, EventRole::Reserve).is_empty());
+    assert!(discord.dms().iter().any(|(user, msg)| *user == player.user.id && msg.contains("ha aprobado")));
+}
+
+#[tokio::test]
+async fn only_the_leader_changes_the_rules() {
+    let pool = pool().await;
+    let message_id = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(1).unwrap())).await;
+    let link = format!("https://discord.com/channels/{GUILD}/{}/{message_id}", next_id());
+    let leader = member(LEADER, "Leader", &[]);
+    let other = member(next_id(), "Otro", &[]);
+
+    let discord = FakeDiscord::new();
+    event_rules(&slash_command("event", "rules", &[("event", &link), ("rules", "max-semana 1 => rechazar")], &other), &discord, pool.clone()).await.unwrap();
+    assert_eq!(discord.responses()[0]["data"]["embeds"][0]["title"], "Sin permiso");
+    assert!(Store::new(pool.clone()).get_event(message_id).await.unwrap().rules.is_empty());
+
+    // Everyone can still look at them
+    event_rules(&slash_command("event", "rules", &[("event", &link)], &other), &discord, pool.clone()).await.unwrap();
+    assert_ne!(discord.responses()[1]["data"]["embeds"][0]["title"], "Sin permiso");
+
+    event_rules(&slash_command("event", "rules", &[("event", &link), ("rules", "max-semana 1 => rechazar")], &leader), &discord, pool.clone()).await.unwrap();
+    assert_eq!(Store::new(pool).get_event(message_id).await.unwrap().rules.len(), 1);
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use events::messages::failures::{report_failure, Failure};
+use events::discord::Discord;
+use events::Error;
+use serenity::all::{ChannelId, CreateInteractionResponse, CreateInteractionResponseMessage, Interaction};
+use common::*;
+
+#[tokio::test]
+async fn members_get_the_reason_and_a_correlation_id() {
+    let player = member(next_id(), "Impaciente", &[]);
+    let interaction = Interaction::Component(button("signup_tanks", &player, &message_json(next_id(), next_id(), BOT)));
+    let admin_log = ChannelId::new(next_id());
+
+    let discord = FakeDiscord::new();
+    let id = report_failure(&discord, Some(&interaction), "Signup event", (&Error::Timeout).into(), Some(admin_log)).await;
+
+    let response = &discord.responses()[0]["data"];
+    assert_eq!(response["flags"], 64);
+    assert_eq!(response["content"], format!("Se ha agotado el tiempo para responder, vuelve a empezar\nCodigo de error: `{id}`"));
+    // Mistakes of the member aren't sent to the admins
+    assert!(discord.sent_messages().is_empty());
+}
+
+#[tokio::test]
+async fn internal_errors_are_forwarded_to_the_admins() {
+    let user = member(next_id(), "Lider", &[]);
+    let command = slash_command("event", "announce", &[], &user);
+    let admin_log = ChannelId::new(next_id());
+
+    // The handler already answered before failing
+    let discord = FakeDiscord::new();
+    discord.create_response(command.id, &command.token, CreateInteractionResponse::Message(CreateInteractionResponseMessage::new())).await.unwrap();
+    let failure = Failure::from(&Error::Postgres(sqlx::Error::RowNotFound));
+    let id = report_failure(&discord, Some(&Interaction::Command(command)), "Event command", failure, Some(admin_log)).await;
+
+    let followup = discord.calls().into_iter().find_map(|c| match c {
+        Call::CreateFollowup(followup) => Some(followup),
+        _ => None
+    }).unwrap();
+    assert_eq!(followup["content"], format!("Ha ocurrido un error inesperado, ya se ha avisado a los administradores\nCodigo de error: `{id}`"));
+
+    let sent = discord.sent_messages();
+    assert_eq!(sent[0].0, admin_log);
+    let embed = &sent[0].1["embeds"][0];
+    assert_eq!(embed["title"], format!("⚠️ Error interno `{id}`"));
+    assert!(embed["description"].as_str().unwrap().contains("RowNotFound"));
+    assert!(embed["fields"].as_array().unwrap().iter().any(|f| f["name"] == "Tipo" && f["value"] == "Postgres"));
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use events::events::{EventRole, EventStatus, Player};
+use events::messages::events::{absence_submit, feed, scheduled_event_update, signup_event};
+use events::Store;
+use serenity::all::{ChannelId, ScheduledEventId, ScheduledEventStatus, Timestamp, UserId};
+use common::*;
+
+const LEADER: u64 = 1300;
+
+#[tokio::test]
+async fn roster_changes_are_logged_and_posted_to_the_thread() {
+    let pool = pool().await;
+    let thread = ChannelId::new(next_id());
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    event.thread = Some(thread);
+    let message_id = stored_event(&pool, &event).await;
+    let event_message = message_json(message_id.get(), next_id(), BOT);
+    let player = member(next_id(), "Galadriel", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.click(string_select("signup_class", &["Warden"], &player, None));
+    signup_event(&button("signup_tanks", &player, &event_message), &discord, pool.clone()).await.unwrap();
+    signup_event(&button(&format!("signup_{}", EventRole::Absent.to_id()), &player, &event_message), &discord, pool.clone()).await.unwrap();
+    absence_submit(&modal_on("absence_modal", &[("absence_reason", "Boda"), ("absence_from", ""), ("absence_to", "")], &player, &event_message), &discord, pool.clone()).await.unwrap();
+
+    let posts: Vec<String> = discord.sent_messages().into_iter()
+        .filter(|(channel, _)| *channel == thread)
+        .map(|(_, msg)| msg["content"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(posts, vec!["✅ **Galadriel** se apunta como Tanks", "❌ **Galadriel** no podra asistir: Boda"]);
+
+    let changes = Store::new(pool).get_changes(message_id).await.unwrap();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].actor, Some(player.user.id));
+}
+
+#[tokio::test]
+async fn completed_event_archives_its_thread() {
+    let pool = pool().await;
+    let thread = ChannelId::new(next_id());
+    let scheduled_id = next_id();
+    let mut event = trial(LEADER, Utc::now() - Duration::try_hours(1).unwrap());
+    event.thread = Some(thread);
+    event.scheduled_event = Some(ScheduledEventId::new(scheduled_id));
+    let message_id = stored_event(&pool, &event).await;
+    let channel = next_id();
+
+    let mut scheduled = scheduled_event(scheduled_id, &format!("https://discord.com/channels/{GUILD}/{channel}/{message_id}"), 3);
+    scheduled.start_time = Timestamp::from_unix_timestamp(event.datetime.unwrap().timestamp()).unwrap();
+    scheduled.status = ScheduledEventStatus::Completed;
+
+    let discord = Arc::new(FakeDiscord::new());
+    scheduled_event_update(&scheduled, discord.clone(), pool.clone()).await.unwrap();
+
+    assert_eq!(Store::new(pool).get_event(message_id).await.unwrap().status, EventStatus::Completed);
+    assert_eq!(discord.sent_messages().last().unwrap().1["content"], "🏁 Evento finalizado");
+    assert!(discord.calls().iter().any(|c| matches!(c, Call::ArchiveThread(t) if *t == thread)));
+}
+
+#[tokio::test]
+async fn only_the_leader_announces() {
+    let pool = pool().await;
+    let thread = ChannelId::new(next_id());
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    event.thread = Some(thread);
+    event.add_player(EventRole::DD, Player::new(UserId::new(next_id()), "Dps"));
+    let message_id = stored_event(&pool, &event).await;
+    let link = format!("https://discord.com/channels/{GUILD}/{}/{message_id}", next_id());
+    let leader = member(LEADER, "Leader", &[]);
+    let other = member(next_id(), "Otro", &[]);
+
+    let discord = FakeDiscord::new();
+    feed::announce(&slash_command("event", "announce", &[("event", &link), ("message", "Traed pociones")], &other), &discord, pool.clone()).await.unwrap();
+    assert!(discord.sent_messages().is_empty());
+    assert_eq!(discord.responses()[0]["data"]["content"], "Solo el lider del evento puede enviar anuncios");
+
+    feed::announce(&slash_command("event", "announce", &[("event", &link), ("message", "Traed pociones")], &leader), &discord, pool.clone()).await.unwrap();
+    let sent = discord.sent_messages();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, thread);
+    assert!(sent[0].1["embeds"][0]["description"].as_str().unwrap().starts_with("Traed pociones"));
+    assert_eq!(Store::new(pool).get_changes(message_id).await.unwrap()[0].description, "📢 Anuncio: Traed pociones");
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use chrono::{Duration, TimeZone, Utc};
+use events::events::{format_gold, next_fine_summary, parse_gold, penalty_amount, EventRole, Fine, FinePayment, FineReason, FineStatus, Ledger, PenaltyRule, Player};
+use events::messages::events::{absence_submit, signup_event};
+use events::messages::fines::{fines_command, set_treasurer_role};
+use events::tasks::fines::send_fine_summary;
+use events::Store;
+use serenity::all::{MessageId, RoleId, UserId};
+use common::*;
+
+const LEADER: u64 = 1900;
+const ESCUDERO: u64 = 592733654996746253;
+const TREASURER: u64 = 1901;
+
+fn fine(id: i32, user: UserId, amount: u32, days_ago: i64, waived: bool) -> Fine {
+    Fine {
+        id, user, name: "Moroso".to_string(), message: Some(MessageId::new(1)), event_title: "Trial".to_string(),
+        reason: FineReason::NoShow, amount, created: Utc::now() - Duration::try_days(days_ago).unwrap(), waived
+    }
+}
+
+#[test]
+fn ledger_pays_the_oldest_fines_first() {
+    let user = UserId::new(1);
+    let fines = vec![fine(3, user, 3000, 1, false), fine(1, user, 10000, 10, false), fine(2, user, 5000, 5, true), fine(4, UserId::new(2), 500, 1, false)];
+    let payments = vec![FinePayment { id: 1, user, amount: 11000, paid: Utc::now() }];
+
+    let ledger = Ledger::new(user, &fines, &payments);
+    let statuses: Vec<(i32, FineStatus)> = ledger.fines.iter().map(|(f, s)| (f.id, *s)).collect();
+    assert_eq!(statuses, vec![(1, FineStatus::Paid), (2, FineStatus::Waived), (3, FineStatus::Outstanding(2000))]);
+    assert_eq!((ledger.outstanding(), ledger.credit()), (2000, 0));
+    assert_eq!(Ledger::all(&fines, &payments).len(), 2);
+
+    let rules = vec![
+        PenaltyRule { role: None, no_show: 10000, late_absence: 5000 },
+        PenaltyRule { role: Some(RoleId::new(7)), no_show: 3000, late_absence: 3000 },
+        PenaltyRule { role: Some(RoleId::new(8)), no_show: 2000, late_absence: 4000 }
+    ];
+    assert_eq!(penalty_amount(&rules, &[], FineReason::NoShow), Some(10000));
+    assert_eq!(penalty_amount(&rules, &[RoleId::new(7), RoleId::new(8)], FineReason::LateAbsence), Some(3000));
+    assert_eq!(penalty_amount(&[], &[RoleId::new(7)], FineReason::NoShow), None);
+
+    assert_eq!(format_gold(10000), "10.000 oro");
+    assert_eq!(format_gold(500), "500 oro");
+    assert_eq!((parse_gold("3000").unwrap(), parse_gold("3.000").unwrap(), parse_gold("3k").unwrap(), parse_gold("1,5k").unwrap()), (3000, 3000, 3000, 1500));
+    assert!(parse_gold("mucho").is_err() && parse_gold("0").is_err());
+    // Monday 20:00 in Spain, 18:00 UTC
+    let monday = Utc.with_ymd_and_hms(2026, 10, 19, 18, 0, 0).unwrap();
+    assert_eq!(next_fine_summary(monday - Duration::try_hours(1).unwrap()), monday);
+    assert_eq!(next_fine_summary(monday), monday + Duration::try_weeks(1).unwrap());
+}
+
+#[tokio::test]
+async fn late_absence_of_a_starter_is_fined_once_by_role() {
+    let pool = pool().await;
+    let store = Store::new(pool.clone());
+    let mut event = trial(LEADER, Utc::now() + Duration::try_hours(3).unwrap());
+    let escudero = member(next_id(), "Escudero", &[ESCUDERO]);
+    let other = member(next_id(), "Veterano", &[]);
+    event.add_player(EventRole::DD, Player::new(escudero.user.id, "Escudero"));
+    event.add_player(EventRole::Tank, Player::new(other.user.id, "Veterano"));
+    let message_id = stored_event(&pool, &event).await;
+
+    let discord = FakeDiscord::new();
+    let event_message = message_json(message_id.get(), 10, BOT);
+    for player in [&escudero, &other, &escudero] {
+        signup_event(&button("signup_ausencias", player, &event_message), &discord, pool.clone()).await.unwrap();
+        absence_submit(&modal_on("absence_modal", &[("absence_reason", "Cena"), ("absence_from", ""), ("absence_to", "")], player, &event_message), &discord, pool.clone()).await.unwrap();
+    }
+
+    let fines = store.get_fines(None).await.unwrap();
+    let fined: Vec<(UserId, FineReason, u32)> = fines.iter()
+        .filter(|f| f.message == Some(message_id))
+        .map(|f| (f.user, f.reason, f.amount))
+        .collect();
+    assert_eq!(fined.len(), 2);
+    assert!(fined.contains(&(escudero.user.id, FineReason::LateAbsence, 3000)));
+    assert!(fined.contains(&(other.user.id, FineReason::LateAbsence, 10000)));
+    let dms: Vec<String> = discord.dms().into_iter().filter(|(user, _)| *user == escudero.user.id).map(|(_, dm)| dm).collect();
+    assert_eq!(dms.len(), 1);
+    assert!(dms[0].contains("Multa de **3.000 oro** por ausencia de ultima hora"), "{}", dms[0]);
+}
+
+#[tokio::test]
+async fn treasurers_record_payments_and_members_see_their_balance() {
+    let pool = pool().await;
+    let store = Store::new(pool.clone());
+    let message_id = stored_event(&pool, &trial(LEADER, Utc::now() - Duration::try_days(1).unwrap())).await;
+    let debtor = member(next_id(), "Moroso", &[]);
+    store.add_fine(debtor.user.id, "Moroso", message_id, "Trial", FineReason::NoShow, 10000).await.unwrap().unwrap();
+    assert!(store.add_fine(debtor.user.id, "Moroso", message_id, "Trial", FineReason::NoShow, 10000).await.unwrap().is_none());
+    set_treasurer_role(Some(RoleId::new(TREASURER)));
+    let treasurer = member(next_id(), "Tesorero", &[TREASURER]);
+    let player = debtor.user.id.to_string();
+
+    let discord = FakeDiscord::new();
+    fines_command(&slash_command("fines", "pay", &[("player", &player), ("amount", "4k")], &debtor), &discord, pool.clone()).await.unwrap();
+    fines_command(&slash_command("fines", "pay", &[("player", &player), ("amount", "4k")], &treasurer), &discord, pool.clone()).await.unwrap();
+    fines_command(&slash_command("fines", "me", &[], &debtor), &discord, pool.clone()).await.unwrap();
+
+    let responses = discord.responses();
+    assert_eq!(responses[0]["data"]["embeds"][0]["title"], "Solo para tesoreros");
+    assert_eq!(responses[1]["data"]["embeds"][0]["title"], "Pago registrado");
+    assert_eq!(responses[2]["data"]["embeds"][0]["fields"][1]["value"], "6.000 oro");
+    assert_eq!(responses[2]["data"]["embeds"][0]["fields"][2]["value"], "4.000 oro");
+
+    let summary = FakeDiscord::new();
+    assert!(send_fine_summary(&summary, &store).await.unwrap() >= 1);
+    let reminder = summary.dms().into_iter().find(|(user, _)| *user == debtor.user.id).unwrap().1;
+    assert!(reminder.contains("**6.000 oro**"), "{reminder}");
+
+    fines_command(&slash_command("fines", "pay", &[("player", &player)], &treasurer), &discord, pool.clone()).await.unwrap();
+    let ledger = Ledger::new(debtor.user.id, &store.get_fines(Some(debtor.user.id)).await.unwrap(), &store.get_fine_payments(Some(debtor.user.id)).await.unwrap());
+    assert_eq!((ledger.outstanding(), ledger.paid), (0, 10000));
+}
@@ -0,0 +1,87 @@
+mod common;
+
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use events::events::{Event, EventKind, EventRole, Player};
+use events::messages::events::edit_event;
+use events::{tasks, Store};
+use serenity::all::{ChannelId, UserId};
+use common::*;
+
+const LEADER: u64 = 800;
+
+fn pvp(datetime: chrono::DateTime<Utc>) -> Event {
+    let mut event = Event::new("Cyrodiil".to_string(), "2h".parse().unwrap(), String::new(), UserId::new(LEADER), EventKind::PvP);
+    event.datetime = Some(datetime);
+    for (role, count) in [(EventRole::Tank, 2), (EventRole::Healer, 4), (EventRole::Brawler, 10), (EventRole::Bomber, 4), (EventRole::Ganker, 2)] {
+        for i in 0..count {
+            event.add_player(role, Player::new(UserId::new(next_id()), format!("{role}{i}")));
+        }
+    }
+    event
+}
+
+fn role_count(group: &[(EventRole, Player)], role: EventRole) -> usize {
+    group.iter().filter(|(r, _)| *r == role).count()
+}
+
+#[test]
+fn groups_spread_roles_evenly_and_respect_locks() {
+    let mut event = pvp(Utc::now());
+    assert!(event.groups().is_empty());
+
+    event.group_settings.size = Some(12);
+    let groups = event.groups();
+    assert_eq!(groups.iter().map(|g| g.len()).collect::<Vec<_>>(), vec![11, 11]);
+    for role in [EventRole::Tank, EventRole::Healer, EventRole::Brawler, EventRole::Bomber, EventRole::Ganker] {
+        assert_eq!(role_count(&groups[0], role), role_count(&groups[1], role), "{role}");
+    }
+
+    let healer = groups[0].iter().find(|(r, _)| *r == EventRole::Healer).unwrap().1.id;
+    event.group_settings.locks = vec![(healer, 1)];
+    event.group_settings.seed = 42;
+    let groups = event.groups();
+    assert!(groups[1].iter().any(|(_, p)| p.id == healer));
+    assert_eq!(role_count(&groups[0], EventRole::Healer), 2);
+}
+
+#[tokio::test]
+async fn leader_splits_groups_from_the_edit_menu() {
+    let pool = pool().await;
+    let message_id = stored_event(&pool, &pvp(Utc::now() + Duration::try_days(1).unwrap())).await;
+    let leader = member(LEADER, "Leader", &[]);
+    let panel = message_json(next_id(), 1, BOT);
+
+    let discord = FakeDiscord::new();
+    discord
+        .click(button("edit_groups", &leader, &panel))
+        .click(string_select("edit_groups_size", &["6"], &leader, None))
+        .click(button("edit_groups_done", &leader, &panel));
+
+    edit_event(&message_command("Edit event", &message_json(message_id.get(), 10, BOT), &leader), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert_eq!(event.group_settings.size, Some(6));
+    assert_eq!(event.groups().len(), 4);
+    let edit = discord.calls().into_iter()
+        .rev()
+        .find_map(|c| match c { Call::EditMessage(_, id, edit) if id == message_id => Some(edit), _ => None })
+        .unwrap();
+    assert_eq!(edit["embeds"].as_array().unwrap().len(), 2);
+    assert_eq!(edit["embeds"][1]["title"], "👥 Grupos de 6");
+}
+
+#[tokio::test]
+async fn reminder_has_one_invite_script_per_group() {
+    let pool = pool().await;
+    let mut event = pvp(Utc::now() + Duration::try_minutes(45).unwrap());
+    event.group_settings.size = Some(12);
+    let message_id = stored_event(&pool, &event).await;
+
+    let discord = Arc::new(FakeDiscord::new());
+    tasks::spawn_reminder(std::time::Duration::ZERO, discord.clone(), ChannelId::new(next_id()), message_id, Arc::new(Store::new(pool))).await.unwrap();
+
+    let content = discord.sent_messages()[0].1["content"].as_str().unwrap().to_string();
+    assert!(content.contains("grupo 1") && content.contains("grupo 2"), "{content}");
+    assert_eq!(content.matches("/script").count(), 2);
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use events::events::{choose_image, image_file_name, EventImage, EventKind};
+use events::messages::events::images::upload_image;
+use common::*;
+
+fn trial_image(file_name: &str, key: &str, aliases: &[&str]) -> EventImage {
+    EventImage {
+        id: None,
+        kind: EventKind::Trial,
+        file_name: file_name.to_string(),
+        key: Some(key.to_string()),
+        aliases: aliases.iter().map(|a| a.to_string()).collect()
+    }
+}
+
+fn pvp_image(id: i32) -> EventImage {
+    EventImage {
+        id: Some(id),
+        kind: EventKind::PvP,
+        file_name: format!("{id}.jpg"),
+        key: None,
+        aliases: vec![]
+    }
+}
+
+#[test]
+fn trial_images_match_whole_words_in_any_language() {
+    let so = trial_image("so.png", "so", &["ophidia", "santuario"]);
+    let hrc = trial_image("hrc.jpg", "hrc", &["hel ra"]);
+
+    assert!(so.matches("SO veterano"));
+    assert!(so.matches("Santuario de Ofidia"));
+    assert!(so.matches("Sanctum Ophidia HM"));
+    assert!(!so.matches("Sombras del norte"));
+    assert!(!so.matches("Progresion Sol"));
+    assert!(hrc.matches("Ciudadela de Hel-Ra"));
+    assert!(!hrc.matches("Helado y ranas"));
+
+    let images = vec![so.clone(), hrc.clone(), trial_image("generic.jpg", "generic", &[])];
+    assert_eq!(choose_image(&images, "Hel Ra normal", EventKind::Trial, &[]), Some(&hrc));
+    assert_eq!(choose_image(&images, "Sombras", EventKind::Trial, &[]).map(|i| i.file_name.as_str()), Some("generic.jpg"));
+}
+
+#[test]
+fn pvp_rotation_avoids_recent_images() {
+    let images: Vec<EventImage> = (1..=3).map(pvp_image).collect();
+    let recent = vec![images[0].reference(), images[2].reference()];
+
+    for _ in 0..20 {
+        assert_eq!(choose_image(&images, "Cyrodiil", EventKind::PvP, &recent), Some(&images[1]));
+    }
+
+    // Every image used recently, any of them is fine
+    let all: Vec<String> = images.iter().map(|i| i.reference()).collect();
+    assert!(choose_image(&images, "Cyrodiil", EventKind::PvP, &all).is_some());
+}
+
+#[test]
+fn references_keep_the_attachment_file_name() {
+    let library = pvp_image(7);
+    assert_eq!(library.reference(), "library:7:7.jpg");
+    assert_eq!(image_file_name(&library.reference()), Some("7.jpg"));
+
+    let bundled = trial_image("ka.png", "ka", &[]);
+    assert_eq!(bundled.reference(), "asset:trial/ka.png");
+    assert_eq!(image_file_name(&bundled.reference()), Some("ka.png"));
+
+    assert_eq!(image_file_name("https://example.com/ka.png"), None);
+}
+
+#[tokio::test]
+async fn only_officers_upload_images() {
+    let pool = pool().await;
+    let discord = FakeDiscord::new();
+
+    upload_image(&slash_command("event", "image", &[("kind", "trial")], &member(next_id(), "Miembro", &[])), &discord, pool.clone()).await.unwrap();
+    upload_image(&slash_command("event", "image", &[("kind", "trial")], &officer(next_id(), "Oficial")), &discord, pool).await.unwrap();
+
+    let titles: Vec<String> = discord.responses().iter().map(|r| r["data"]["embeds"][0]["title"].as_str().unwrap().to_string()).collect();
+    assert_eq!(titles, vec!["Sin permiso", "Imagen no valida"]);
+}
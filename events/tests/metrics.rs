@@ -0,0 +1,51 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::{EventRole, Player};
+use events::{metrics, Error, Store};
+use serenity::all::UserId;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use common::*;
+
+const LEADER: u64 = 1500;
+
+async fn get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn metrics_are_rendered_in_the_prometheus_format() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(2).unwrap());
+    event.title = "vRG \"HM\"".to_string();
+    event.add_player(EventRole::DD, Player::new(UserId::new(next_id()), "Dps"));
+    event.add_player(EventRole::Absent, Player::new(UserId::new(next_id()), "Ausente"));
+    let message = stored_event(&pool, &event).await;
+    metrics::record_error(&Error::Timeout);
+
+    let text = metrics::render(&Store::new(pool)).await.unwrap();
+    assert!(text.contains("# TYPE leki_db_query_duration_seconds histogram"));
+    assert!(text.contains("leki_errors_total{variant=\"Timeout\"} 1"));
+    assert!(text.contains(&format!("leki_event_signups{{event=\"{message}\",title=\"vRG \\\"HM\\\"\"}} 1")));
+    assert!(text.contains("leki_db_query_duration_seconds_bucket{query=\"create_event\",le=\"+Inf\"}"));
+}
+
+#[tokio::test]
+async fn health_route_checks_postgres_and_the_gateway() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(metrics::serve(listener, Store::new(pool().await), None));
+
+    // No gateway client yet, the bot can't answer interactions
+    let health = get(addr, "/healthz").await;
+    assert!(health.starts_with("HTTP/1.1 503"));
+    assert!(health.contains("postgres: ok\ngateway: no shards"));
+
+    assert!(get(addr, "/metrics").await.contains("# TYPE leki_interactions_total counter"));
+    assert!(get(addr, "/nada").await.starts_with("HTTP/1.1 404"));
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use chrono::{DateTime, Duration, Utc};
+use events::events::{NotificationChannel, NotificationFrequency, NotificationKind};
+use events::messages::events::{absence_submit, signup_event};
+use events::messages::notifications::notifications_command;
+use events::tasks::notifications::deliver_due;
+use events::Store;
+use serde_json::json;
+use serenity::all::{ChannelId, CommandInteraction, Member};
+use common::*;
+
+/// `/notifications` takes its options directly, without subcommand
+fn notifications(options: &[(&str, &str)], user: &Member) -> CommandInteraction {
+    let mut value = serde_json::to_value(slash_command("notifications", "", &[], user)).unwrap();
+    value["data"]["options"] = options.iter()
+        .map(|(name, value)| json!({ "name": name, "type": 3, "value": value }))
+        .collect();
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn digests_are_due_at_the_next_hour_or_spanish_evening() {
+    let now = DateTime::parse_from_rfc3339("2026-10-19T10:25:00Z").unwrap().with_timezone(&Utc);
+    assert_eq!(NotificationFrequency::Instant.due_at(now), now);
+    assert_eq!(NotificationFrequency::Hourly.due_at(now).to_rfc3339(), "2026-10-19T11:00:00+00:00");
+    assert_eq!(NotificationFrequency::Daily.due_at(now).to_rfc3339(), "2026-10-19T18:00:00+00:00");
+    let evening = now + Duration::try_hours(9).unwrap();
+    assert_eq!(NotificationFrequency::Daily.due_at(evening).to_rfc3339(), "2026-10-20T18:00:00+00:00");
+}
+
+#[tokio::test]
+async fn hourly_leader_gets_one_digest_instead_of_a_dm_per_signup() {
+    let pool = pool().await;
+    let leader = next_id();
+    let store = Store::new(pool.clone());
+    store.set_notification_frequency(leader.into(), NotificationFrequency::Hourly).await.unwrap();
+    let message_id = stored_event(&pool, &trial(leader, Utc::now() + Duration::try_days(1).unwrap())).await;
+
+    let discord = FakeDiscord::new();
+    for (name, role) in [("Legolas", "signup_dd"), ("Gimli", "signup_tanks")] {
+        let player = member(next_id(), name, &[]);
+        discord.click(string_select("signup_class", &["Templar"], &player, None));
+        signup_event(&button(role, &player, &message_json(message_id.get(), 10, BOT)), &discord, pool.clone()).await.unwrap();
+    }
+    assert!(discord.dms().iter().all(|(user, _)| user.get() != leader));
+
+    sqlx::query("update events.notification_queue set due_at = now() where user_id = $1")
+        .bind(leader as i64)
+        .execute(&pool).await.unwrap();
+    assert_eq!(deliver_due(&discord, &store, Some(leader.into())).await.unwrap(), 1);
+
+    let digests: Vec<String> = discord.dms().into_iter().filter(|(user, _)| user.get() == leader).map(|(_, dm)| dm).collect();
+    assert_eq!(digests.len(), 1);
+    assert!(digests[0].starts_with("📬 **Resumen de avisos** (2)"), "{}", digests[0]);
+    assert!(digests[0].contains("Legolas se ha apuntado") && digests[0].contains("Gimli se ha apuntado"), "{}", digests[0]);
+    // Delivered notifications are not sent again
+    assert_eq!(deliver_due(&discord, &store, Some(leader.into())).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn notifications_can_go_to_the_event_thread_or_nowhere() {
+    let pool = pool().await;
+    let leader = member(next_id(), "Gandalf", &[]);
+    let thread = ChannelId::new(next_id());
+    let mut event = trial(leader.user.id.get(), Utc::now() + Duration::try_days(1).unwrap());
+    event.thread = Some(thread);
+    let message_id = stored_event(&pool, &event).await;
+
+    let discord = FakeDiscord::new();
+    notifications_command(&notifications(&[("signup", "none"), ("absence", "thread")], &leader), &discord, pool.clone()).await.unwrap();
+    let embed = &discord.responses()[0]["data"]["embeds"][0];
+    assert_eq!(embed["title"], "Tus avisos");
+    assert!(embed["description"].as_str().unwrap().contains("Ausencias: Hilo del evento"));
+    let preferences = Store::new(pool.clone()).get_notification_preferences(leader.user.id).await.unwrap();
+    assert_eq!(preferences.frequency, NotificationFrequency::Instant);
+
+    let event_message = message_json(message_id.get(), 10, BOT);
+    let player = member(next_id(), "Pippin", &[]);
+    discord.click(string_select("signup_class", &["Templar"], &player, None));
+    signup_event(&button("signup_dd", &player, &event_message), &discord, pool.clone()).await.unwrap();
+    let absent = member(next_id(), "Merry", &[]);
+    absence_submit(&modal_on("absence_modal", &[("absence_reason", "Examenes"), ("absence_from", ""), ("absence_to", "")], &absent, &event_message), &discord, pool.clone()).await.unwrap();
+
+    assert!(discord.dms().iter().all(|(user, _)| *user != leader.user.id));
+    let mention = format!("<@{}> Merry no va a poder asistir", leader.user.id);
+    assert!(discord.sent_messages().iter().any(|(channel, message)| *channel == thread
+        && message["content"].as_str().is_some_and(|c| c.starts_with(&mention) && c.ends_with(": Examenes"))));
+    assert_eq!(preferences.channel(NotificationKind::Promotion), NotificationChannel::Dm);
+}
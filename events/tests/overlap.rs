@@ -0,0 +1,91 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::{Conflict, EligibilityRule, EventKind, EventRole, EventStatus, Player};
+use events::messages::events::signup_event;
+use events::Store;
+use serenity::all::{GuildId, UserId};
+use common::*;
+
+const LEADER: u64 = 1400;
+
+#[test]
+fn conflicts_need_events_at_the_same_time() {
+    let guild = GuildId::new(GUILD);
+    let start = Utc::now() + Duration::try_days(2).unwrap();
+    let mut event = trial(LEADER, start);
+    let mut other = trial(LEADER, start + Duration::try_hours(1).unwrap());
+    for id in 1..=3 {
+        event.add_player(EventRole::DD, Player::new(UserId::new(id), format!("Dps {id}")));
+        other.add_player(EventRole::DD, Player::new(UserId::new(id), format!("Dps {id}")));
+    }
+
+    assert_eq!(event.conflicts_with(&other, guild), vec![
+        Conflict::SameLeader,
+        Conflict::SameVoiceChannel,
+        Conflict::SharedPlayers(vec!["Dps 1".to_string(), "Dps 2".to_string(), "Dps 3".to_string()])
+    ]);
+
+    // Two shared starters are fine and a reserve doesn't count
+    other.add_player(EventRole::Reserve, Player::new(UserId::new(3), "Dps 3"));
+    other.leader = UserId::new(LEADER + 1);
+    assert_eq!(event.conflicts_with(&other, guild), vec![Conflict::SameVoiceChannel]);
+
+    // Back to back events don't overlap
+    let later = trial(LEADER, start + Duration::try_hours(2).unwrap());
+    assert!(event.conflicts_with(&later, guild).is_empty());
+}
+
+#[tokio::test]
+async fn store_finds_events_still_to_be_played_at_the_same_time() {
+    let pool = pool().await;
+    let store = Store::new(pool.clone());
+    let start = Utc::now() + Duration::try_days(40).unwrap();
+    let event = trial(LEADER, start);
+    let message = stored_event(&pool, &event).await;
+
+    let mut pvp = trial(LEADER, start + Duration::try_minutes(90).unwrap());
+    pvp.kind = EventKind::PvP;
+    pvp.duration = "30m".parse().unwrap();
+    let overlapping = stored_event(&pool, &pvp).await;
+    let canceled = stored_event(&pool, &trial(LEADER, start)).await;
+    store.update_status(canceled, EventStatus::Canceled).await.unwrap();
+    let after = stored_event(&pool, &trial(LEADER, start + Duration::try_hours(2).unwrap())).await;
+
+    let found = store.overlapping_events(start, event.end().unwrap(), message).await.unwrap();
+    assert!(found.contains(&overlapping));
+    assert!(!found.contains(&message));
+    assert!(!found.contains(&canceled));
+    assert!(!found.contains(&after));
+}
+
+#[tokio::test]
+async fn starters_at_the_same_time_are_warned_or_sent_to_reserve() {
+    let pool = pool().await;
+    let start = Utc::now() + Duration::try_days(5).unwrap();
+    let player = member(next_id(), "Ubicuo", &[]);
+    let mut other = trial(LEADER, start);
+    other.title = "vDSR".to_string();
+    other.add_player(EventRole::DD, Player::new(player.user.id, "Ubicuo"));
+    stored_event(&pool, &other).await;
+
+    let warned = stored_event(&pool, &trial(LEADER, start)).await;
+    let discord = FakeDiscord::new();
+    discord.click(string_select("signup_class", &["Warden"], &player, None));
+    signup_event(&button("signup_tanks", &player, &message_json(warned.get(), next_id(), BOT)), &discord, pool.clone()).await.unwrap();
+
+    assert_eq!(Store::new(pool.clone()).get_event(warned).await.unwrap().role_of(player.user.id), Some(EventRole::Tank));
+    let response = discord.responses().last().unwrap().clone();
+    assert!(response["data"]["embeds"][0]["description"].as_str().unwrap().contains("ya estas como titular en: **vDSR**"));
+
+    let mut strict = trial(LEADER, start + Duration::try_hours(1).unwrap());
+    strict.rules = EligibilityRule::parse_list("sin-solape => reserva").unwrap();
+    assert_eq!(strict.rules[0].to_string(), "sin-solape => reserva");
+    let strict = stored_event(&pool, &strict).await;
+    let discord = FakeDiscord::new();
+    discord.click(string_select("signup_class", &["Warden"], &player, None));
+    signup_event(&button("signup_tanks", &player, &message_json(strict.get(), next_id(), BOT)), &discord, pool.clone()).await.unwrap();
+
+    assert_eq!(Store::new(pool).get_event(strict).await.unwrap().role_of(player.user.id), Some(EventRole::Reserve));
+    assert!(discord.dms()[0].1.contains("otro evento a la misma hora"));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use duration_string::DurationString;
+use events::events::{Event, EventKind, PvpAlliance, PvpDetails, PvpStyle};
+use events::messages::events::list::list_events;
+use events::Store;
+use serenity::all::{ChannelId, UserId};
+use common::*;
+
+const LEADER: u64 = 2100;
+
+fn pvp(title: &str, details: PvpDetails) -> Event {
+    let mut event = Event::new(title.to_string(), DurationString::from_string("2h".to_string()).unwrap(),
+                               "Cyrodiil".to_string(), UserId::new(LEADER), EventKind::PvP);
+    event.datetime = Some(Utc::now() + Duration::try_days(2).unwrap());
+    event.channel = Some(ChannelId::new(20));
+    event.pvp = Some(details);
+    event
+}
+
+#[test]
+fn alliance_drives_the_thumbnail_and_details_show_in_the_embed() {
+    let event = pvp("Asedio", PvpDetails {
+        campaign: Some("Grey Host".to_string()), alliance: PvpAlliance::Aldmeri, style: Some(PvpStyle::KeepSiege), min_cp: Some(1200)
+    });
+    let embed = serde_json::to_value(event.embed()).unwrap();
+    assert_eq!(embed["thumbnail"]["url"], "https://images.uesp.net/9/9d/ON-icon-alliance-Aldmeri.png");
+    assert_eq!(embed["fields"][3]["value"], "Alianza: Dominio Aldmeri (AD)\nCampaña: Grey Host\nEstilo: Asedio de fortalezas\nCP minimo: 1200");
+    assert_eq!(event.pvp.as_ref().unwrap().short_es(), "AD · Grey Host · Asedio de fortalezas · CP 1200");
+
+    // PvP events default to the guild alliance, trials have no details
+    let quick = Event::new("PvP".to_string(), DurationString::from_string("1h".to_string()).unwrap(), String::new(), UserId::new(LEADER), EventKind::PvP);
+    assert_eq!(quick.thumbnail(), "https://images.uesp.net/9/9e/ON-icon-alliance-Ebonheart.png");
+    assert!(trial(LEADER, Utc::now()).pvp.is_none());
+    assert_eq!((PvpAlliance::from_id("DC"), PvpStyle::from_id("small_scale")), (Some(PvpAlliance::Daggerfall), Some(PvpStyle::SmallScale)));
+}
+
+#[tokio::test]
+async fn pvp_details_are_stored_with_the_event() {
+    let pool = pool().await;
+    let details = PvpDetails { campaign: Some("Ravenwatch".to_string()), alliance: PvpAlliance::Daggerfall, style: Some(PvpStyle::Battlegrounds), min_cp: None };
+    let message_id = stored_event(&pool, &pvp("Campos de batalla", details.clone())).await;
+    let trial_id = stored_event(&pool, &trial(LEADER, Utc::now())).await;
+
+    let store = Store::new(pool);
+    assert_eq!(store.get_event(message_id).await.unwrap().pvp, Some(details));
+    assert_eq!(store.get_event(trial_id).await.unwrap().pvp, None);
+}
+
+#[tokio::test]
+async fn listing_filters_pvp_events_by_campaign_alliance_and_style() {
+    let pool = pool().await;
+    let campaign = format!("Campaign {}", next_id());
+    let details = |alliance, style| PvpDetails { campaign: Some(campaign.clone()), alliance, style: Some(style), min_cp: None };
+    stored_event(&pool, &pvp("Zerg EP", details(PvpAlliance::Ebonheart, PvpStyle::OpenField))).await;
+    stored_event(&pool, &pvp("Asedio EP", details(PvpAlliance::Ebonheart, PvpStyle::KeepSiege))).await;
+    stored_event(&pool, &pvp("Grupo AD", details(PvpAlliance::Aldmeri, PvpStyle::SmallScale))).await;
+
+    let discord = FakeDiscord::new();
+    let user = member(next_id(), "Player", &[]);
+    list_events(&slash_command("event", "list", &[("campaign", &campaign), ("alliance", "ep")], &user), &discord, pool.clone()).await.unwrap();
+    list_events(&slash_command("event", "list", &[("campaign", &campaign), ("style", "small_scale")], &user), &discord, pool.clone()).await.unwrap();
+    list_events(&slash_command("event", "list", &[("campaign", "no such campaign")], &user), &discord, pool.clone()).await.unwrap();
+
+    let listings: Vec<String> = discord.responses().iter()
+        .map(|r| r["data"]["embeds"][0]["description"].as_str().unwrap().to_string())
+        .collect();
+    assert!(listings[0].contains("Zerg EP") && listings[0].contains("Asedio EP") && !listings[0].contains("Grupo AD"), "{}", listings[0]);
+    assert!(listings[0].contains(&format!("EP · {campaign} · Asedio de fortalezas")), "{}", listings[0]);
+    assert!(listings[1].contains("Grupo AD") && !listings[1].contains("EP"), "{}", listings[1]);
+    assert_eq!(listings[2], "No hay eventos que coincidan");
+}
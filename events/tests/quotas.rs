@@ -0,0 +1,82 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::{ClassQuota, EventRole, Player, PlayerClass};
+use events::messages::events::signup_event;
+use events::messages::events::quotas::event_quotas;
+use events::Store;
+use serenity::all::UserId;
+use common::*;
+
+const LEADER: u64 = 700;
+
+fn necro(name: &str) -> Player {
+    let mut player = Player::new(UserId::new(next_id()), name);
+    player.class = Some(PlayerClass::Necromancer);
+    player
+}
+
+#[test]
+fn quotas_parse_and_report_missing_classes() {
+    let quotas = ClassQuota::parse_list("Necromancer max 1; Warden min 1 max 2").unwrap();
+    assert_eq!(quotas.iter().map(|q| q.to_string()).collect::<Vec<_>>(), vec!["Necromancer max 1", "Warden min 1 max 2"]);
+    assert!(ClassQuota::parse_list("Warden").is_err());
+    assert!(ClassQuota::parse_list("Warden min 3 max 1").is_err());
+
+    let mut event = trial(LEADER, Utc::now());
+    event.class_quotas = quotas;
+    assert_eq!(event.missing_classes(), vec![(PlayerClass::Warden, 1)]);
+
+    assert_eq!(event.add_player(EventRole::DD, necro("Necro 1")), EventRole::DD);
+    assert_eq!(event.add_player(EventRole::Healer, necro("Necro 2")), EventRole::Reserve);
+    // Reserves don't count towards the quota
+    assert_eq!(event.add_player(EventRole::Reserve, necro("Necro 3")), EventRole::Reserve);
+}
+
+#[tokio::test]
+async fn signup_over_class_max_falls_back_to_reserve() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    event.class_quotas = ClassQuota::parse_list("Necromancer max 1; Warden min 1").unwrap();
+    event.add_player(EventRole::DD, necro("Necro 1"));
+    let message_id = stored_event(&pool, &event).await;
+    let player = member(next_id(), "Necro 2", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.click(string_select("signup_class", &["Necromancer"], &player, None));
+    signup_event(&button("signup_dd", &player, &message_json(message_id.get(), 10, BOT)), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert_eq!(event.class_quotas.len(), 2);
+    assert_eq!(event.role_of(player.user.id), Some(EventRole::Reserve));
+    let response = discord.responses().last().unwrap().clone();
+    assert!(response["data"]["embeds"][0]["description"].as_str().unwrap().contains("Nigromante"));
+
+    let edit = discord.calls().into_iter()
+        .find_map(|c| match c { Call::EditMessage(_, _, edit) => Some(edit), _ => None })
+        .unwrap();
+    assert!(edit["embeds"][0]["fields"].as_array().unwrap().iter().any(|f| f["value"] == "1 Warden"));
+}
+
+#[tokio::test]
+async fn only_the_leader_changes_the_quotas() {
+    let pool = pool().await;
+    let message_id = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(1).unwrap())).await;
+    let link = format!("https://discord.com/channels/{GUILD}/{}/{message_id}", next_id());
+    let leader = member(LEADER, "Leader", &[]);
+    let other = member(next_id(), "Otro", &[]);
+
+    let discord = FakeDiscord::new();
+    event_quotas(&slash_command("event", "quotas", &[("event", &link), ("quotas", "Warden min 1")], &other), &discord, pool.clone()).await.unwrap();
+    assert_eq!(discord.responses()[0]["data"]["embeds"][0]["title"], "Sin permiso");
+    assert!(Store::new(pool.clone()).get_event(message_id).await.unwrap().class_quotas.is_empty());
+
+    // Everyone can still look at them
+    event_quotas(&slash_command("event", "quotas", &[("event", &link)], &other), &discord, pool.clone()).await.unwrap();
+    assert_ne!(discord.responses()[1]["data"]["embeds"][0]["title"], "Sin permiso");
+    assert!(!discord.calls().into_iter().any(|c| matches!(c, Call::EditMessage(..))));
+
+    event_quotas(&slash_command("event", "quotas", &[("event", &link), ("quotas", "Warden min 1")], &leader), &discord, pool.clone()).await.unwrap();
+    assert_eq!(Store::new(pool).get_event(message_id).await.unwrap().class_quotas.len(), 1);
+    assert!(discord.calls().into_iter().any(|c| matches!(c, Call::EditMessage(..))));
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use events::events::{parse_eso_account, EventRole, EventStatus, Player};
+use events::{tasks, Store};
+use serenity::all::{ChannelId, GuildId, UserId};
+use common::*;
+
+const LEADER: u64 = 400;
+
+#[tokio::test]
+async fn reminder_invites_starters_30_minutes_before() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_minutes(45).unwrap());
+    let tank = UserId::new(next_id());
+    let reserve = UserId::new(next_id());
+    event.add_player(EventRole::Tank, Player::new(tank, "TankyMcTank"));
+    event.add_player(EventRole::Reserve, Player::new(reserve, "Benchwarmer"));
+    let message_id = stored_event(&pool, &event).await;
+    let channel = ChannelId::new(next_id());
+
+    let discord = Arc::new(FakeDiscord::new());
+    tasks::spawn_reminder(std::time::Duration::ZERO, discord.clone(), channel, message_id, Arc::new(Store::new(pool))).await.unwrap();
+
+    let sent = discord.sent_messages();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, channel);
+    let content = sent[0].1["content"].as_str().unwrap();
+    assert!(content.contains("GroupInviteByName(\"@TankyMcTank\")"), "{content}");
+    assert!(!content.contains("Benchwarmer"));
+}
+
+#[test]
+fn reminder_is_skipped_for_imminent_events() {
+    assert_eq!(tasks::reminder_delay(Utc::now() + Duration::try_minutes(10).unwrap()), None);
+    let delay = tasks::reminder_delay(Utc::now() + Duration::try_minutes(45).unwrap()).unwrap();
+    assert!(delay <= std::time::Duration::from_secs(15 * 60) && delay > std::time::Duration::from_secs(14 * 60));
+}
+
+#[test]
+fn eso_accounts_are_validated_and_normalized() {
+    assert_eq!(parse_eso_account("Tanky.McTank").unwrap(), "@Tanky.McTank");
+    assert_eq!(parse_eso_account(" @Healer_99 ").unwrap(), "@Healer_99");
+    assert!(parse_eso_account("@ab").is_err());
+    assert!(parse_eso_account("@has spaces").is_err());
+    assert!(parse_eso_account("@waytoolongforanesoaccount").is_err());
+}
+
+#[tokio::test]
+async fn reminder_uses_registered_eso_accounts_and_flags_missing_ones() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_minutes(45).unwrap());
+    let registered = UserId::new(next_id());
+    let unregistered = UserId::new(next_id());
+    event.add_player(EventRole::Healer, Player::new(registered, "Discord Name"));
+    event.add_player(EventRole::DD, Player::new(unregistered, "Guesswork"));
+    let message_id = stored_event(&pool, &event).await;
+    let store = Store::new(pool);
+    store.set_eso_account(registered, "@RealHandle").await.unwrap();
+
+    let discord = Arc::new(FakeDiscord::new());
+    tasks::spawn_reminder(std::time::Duration::ZERO, discord.clone(), ChannelId::new(next_id()), message_id, Arc::new(store)).await.unwrap();
+
+    let content = discord.sent_messages()[0].1["content"].as_str().unwrap().to_string();
+    assert!(content.contains("GroupInviteByName(\"@RealHandle\")"), "{content}");
+    assert!(!content.contains("Discord Name"));
+    assert!(content.contains("GroupInviteByName(\"@Guesswork\")"));
+    assert!(content.contains(&format!("Sin cuenta de ESO registrada, se usa su nombre de Discord: <@{unregistered}>")));
+}
+
+#[tokio::test]
+async fn events_sharing_a_channel_keep_their_own_reminders() {
+    let pool = pool().await;
+    let store = Arc::new(Store::new(pool.clone()));
+    let channel = ChannelId::new(next_id());
+    let first = trial(LEADER, Utc::now() + Duration::try_minutes(45).unwrap());
+    let second = trial(LEADER, Utc::now() + Duration::try_minutes(50).unwrap());
+    let first_id = stored_event(&pool, &first).await;
+    let second_id = stored_event(&pool, &second).await;
+
+    let discord = Arc::new(FakeDiscord::new());
+    let first_reminder = tasks::spawn_reminder(std::time::Duration::ZERO, discord.clone(), channel, first_id, store.clone());
+    let second_reminder = tasks::spawn_reminder(std::time::Duration::ZERO, discord.clone(), channel, second_id, store.clone());
+    first_reminder.await.unwrap();
+    second_reminder.await.unwrap();
+
+    assert_eq!(discord.sent_messages().len(), 2);
+    assert_eq!(store.get_tracked_messages(first_id).await.unwrap().len(), 1);
+    assert_eq!(store.get_tracked_messages(second_id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn startup_restores_unfinished_events_from_the_store() {
+    let pool = pool().await;
+    let store = Store::new(pool.clone());
+    let mut upcoming = trial(LEADER, Utc::now() + Duration::try_days(2).unwrap());
+    upcoming.channel = Some(ChannelId::new(next_id()));
+    let upcoming = stored_event(&pool, &upcoming).await;
+    // Without channel nor scheduled event there's nowhere to remind, it's skipped
+    let lost = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(2).unwrap())).await;
+    let finished = stored_event(&pool, &trial(LEADER, Utc::now() - Duration::try_days(1).unwrap())).await;
+    let canceled = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(2).unwrap())).await;
+    store.update_status(canceled, EventStatus::Canceled).await.unwrap();
+
+    let unfinished = store.unfinished_events().await.unwrap();
+    assert!(unfinished.contains(&upcoming) && unfinished.contains(&lost));
+    assert!(!unfinished.contains(&finished) && !unfinished.contains(&canceled));
+
+    tasks::reset_all_reminders(Arc::new(FakeDiscord::new()), GuildId::new(GUILD), pool).await;
+}
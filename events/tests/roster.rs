@@ -0,0 +1,122 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::{EventRole, Player, PlayerClass};
+use events::messages::events::{roster_autocomplete, roster_command};
+use events::Store;
+use serde_json::json;
+use serenity::all::{ChannelId, CommandInteraction, UserId};
+use common::*;
+
+const LEADER: u64 = 1400;
+
+/// Autocomplete request of `command` with `option` focused
+fn autocomplete(command: CommandInteraction, option: &str) -> CommandInteraction {
+    let mut value = serde_json::to_value(command).unwrap();
+    value["type"] = json!(4);
+    for o in value["data"]["options"][0]["options"].as_array_mut().unwrap() {
+        if o["name"] == option {
+            o["focused"] = json!(true);
+        }
+    }
+    serde_json::from_value(value).unwrap()
+}
+
+/// `player` sent as a user option, like `/roster add` does
+fn with_user(command: CommandInteraction, user: u64) -> CommandInteraction {
+    let mut value = serde_json::to_value(command).unwrap();
+    for o in value["data"]["options"][0]["options"].as_array_mut().unwrap() {
+        if o["name"] == "player" {
+            o["type"] = json!(6);
+        }
+    }
+    value["data"]["resolved"] = json!({
+        "users": { user.to_string(): { "id": user.to_string(), "username": "Nuevo", "discriminator": "0000", "avatar": null } }
+    });
+    serde_json::from_value(value).unwrap()
+}
+
+#[tokio::test]
+async fn moving_to_a_full_role_keeps_class_and_flex_in_the_reserve() {
+    let pool = pool().await;
+    let thread = ChannelId::new(next_id());
+    let channel = ChannelId::new(next_id());
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    event.thread = Some(thread);
+    event.channel = Some(channel);
+    event.add_player(EventRole::Tank, Player::new(UserId::new(next_id()), "Tank 1"));
+    event.add_player(EventRole::Tank, Player::new(UserId::new(next_id()), "Tank 2"));
+    let dd = UserId::new(next_id());
+    let mut player = Player::new(dd, "Elrond");
+    player.class = Some(PlayerClass::Templar);
+    event.add_player(EventRole::DD, player);
+    let message_id = stored_event(&pool, &event).await;
+    let officer = member(next_id(), "Oficial", &[]);
+
+    let discord = FakeDiscord::new();
+    let command = slash_command("roster", "move", &[("event", &message_id.to_string()), ("player", &dd.to_string()), ("role", "tanks")], &officer);
+    roster_command(&command, &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool.clone()).get_event(message_id).await.unwrap();
+    let (role, player) = event.player(dd).unwrap();
+    assert_eq!(role, EventRole::Reserve);
+    assert_eq!(player.class, Some(PlayerClass::Templar));
+    assert_eq!(player.flex, vec![EventRole::Tank]);
+
+    let change = "🔀 **Elrond** movido de DD a Reservas (Tanks esta lleno) por un oficial";
+    assert_eq!(discord.responses()[0]["data"]["content"], change);
+    assert!(discord.sent_messages().iter().any(|(c, m)| *c == thread && m["content"] == change));
+    assert!(discord.calls().iter().any(|c| matches!(c, Call::EditMessage(c, m, _) if *c == channel && *m == message_id)));
+    let changes = Store::new(pool).get_changes(message_id).await.unwrap();
+    assert_eq!(changes[0].actor, Some(officer.user.id));
+}
+
+#[tokio::test]
+async fn officers_add_and_remove_players() {
+    let pool = pool().await;
+    let event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    let message_id = stored_event(&pool, &event).await;
+    let officer = member(next_id(), "Oficial", &[]);
+    let newcomer = member(next_id(), "Arwen", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.add_member(newcomer.clone());
+    let add = slash_command("roster", "add", &[("event", &message_id.to_string()), ("player", &newcomer.user.id.to_string()), ("role", "healers"), ("class", "Warden")], &officer);
+    roster_command(&with_user(add, newcomer.user.id.get()), &discord, pool.clone()).await.unwrap();
+
+    let store = Store::new(pool.clone());
+    let event = store.get_event(message_id).await.unwrap();
+    let (role, player) = event.player(newcomer.user.id).unwrap();
+    assert_eq!((role, player.name.as_str(), player.class), (EventRole::Healer, "Arwen", Some(PlayerClass::Warden)));
+
+    let remove = slash_command("roster", "remove", &[("event", &message_id.to_string()), ("player", &newcomer.user.id.to_string())], &officer);
+    roster_command(&remove, &discord, pool.clone()).await.unwrap();
+    assert!(store.get_event(message_id).await.unwrap().player(newcomer.user.id).is_none());
+
+    let changes: Vec<String> = store.get_changes(message_id).await.unwrap().into_iter().map(|c| c.description).collect();
+    assert_eq!(changes, vec!["➕ **Arwen** añadido a Healers por un oficial", "🗑️ **Arwen** quitado de Healers por un oficial"]);
+
+    // Removing again only explains why
+    roster_command(&slash_command("roster", "remove", &[("event", &message_id.to_string()), ("player", &newcomer.user.id.to_string())], &officer), &discord, pool.clone()).await.unwrap();
+    assert_eq!(discord.responses()[2]["data"]["content"], "Ese jugador no esta apuntado al evento");
+}
+
+#[tokio::test]
+async fn player_autocomplete_lists_the_signups_of_the_event() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    let healer = UserId::new(next_id());
+    event.add_player(EventRole::Healer, Player::new(healer, "Galadriel"));
+    event.add_player(EventRole::DD, Player::new(UserId::new(next_id()), "Boromir"));
+    let message_id = stored_event(&pool, &event).await;
+    let officer = member(next_id(), "Oficial", &[]);
+
+    let discord = FakeDiscord::new();
+    let command = slash_command("roster", "remove", &[("event", &message_id.to_string()), ("player", "gala")], &officer);
+    roster_autocomplete(&autocomplete(command, "player"), &discord, pool).await.unwrap();
+
+    let choices = &discord.responses()[0]["data"]["choices"];
+    assert_eq!(choices.as_array().unwrap().len(), 1);
+    assert_eq!(choices[0]["name"], "Galadriel (Healers)");
+    assert_eq!(choices[0]["value"], healer.to_string());
+}
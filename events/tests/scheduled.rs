@@ -0,0 +1,136 @@
+mod common;
+
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use events::events::{EventRole, EventStatus, Player};
+use events::messages::events::{scheduled_event_delete, scheduled_event_interest, scheduled_event_update};
+use events::Store;
+use serde_json::Value;
+use serenity::all::{ChannelId, GuildId, MessageId, ScheduledEventId, ScheduledEventStatus, Timestamp, UserId};
+use common::*;
+
+const LEADER: u64 = 1200;
+
+fn event_link(channel: u64, message: MessageId) -> String {
+    format!("https://discord.com/channels/{GUILD}/{channel}/{}\nSe empezara a montar 10 minutos antes", message.get())
+}
+
+fn edits(discord: &FakeDiscord, channel: u64) -> Vec<Value> {
+    discord.calls().into_iter()
+        .filter_map(|c| match c {
+            Call::EditMessage(ch, _, edit) if ch == ChannelId::new(channel) => Some(edit),
+            _ => None
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn discord_changes_are_synced_back_to_the_event() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(3).unwrap());
+    let scheduled_id = next_id();
+    event.scheduled_event = Some(ScheduledEventId::new(scheduled_id));
+    let message_id = stored_event(&pool, &event).await;
+    let channel = next_id();
+
+    let start = Utc::now() + Duration::try_days(4).unwrap();
+    let mut scheduled = scheduled_event(scheduled_id, &event_link(channel, message_id), 1);
+    scheduled.name = "vSS HM (cambio de hora)".to_string();
+    scheduled.start_time = Timestamp::from_unix_timestamp(start.timestamp()).unwrap();
+
+    let discord = Arc::new(FakeDiscord::new());
+    scheduled_event_update(&scheduled, discord.clone(), pool.clone()).await.unwrap();
+
+    let stored = Store::new(pool.clone()).get_event(message_id).await.unwrap();
+    assert_eq!(stored.title, "vSS HM (cambio de hora)");
+    assert_eq!(stored.datetime.unwrap().timestamp(), start.timestamp());
+    assert_eq!(stored.status, EventStatus::Scheduled);
+    let edit = &edits(&discord, channel)[0];
+    assert_eq!(edit["embeds"][0]["title"], "vSS HM (cambio de hora)");
+    assert!(edit.get("components").is_none());
+
+    scheduled.status = ScheduledEventStatus::Canceled;
+    scheduled_event_update(&scheduled, discord.clone(), pool.clone()).await.unwrap();
+
+    assert_eq!(Store::new(pool).get_event(message_id).await.unwrap().status, EventStatus::Canceled);
+    let edit = &edits(&discord, channel)[1];
+    assert_eq!(edit["components"], serde_json::json!([]));
+    assert!(edit["embeds"][0]["fields"].as_array().unwrap().iter().any(|f| f["value"] == "❌ Cancelado"));
+}
+
+#[tokio::test]
+async fn edited_descriptions_still_sync_to_the_stored_channel() {
+    let pool = pool().await;
+    let channel = next_id();
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(3).unwrap());
+    let scheduled_id = next_id();
+    event.scheduled_event = Some(ScheduledEventId::new(scheduled_id));
+    event.channel = Some(ChannelId::new(channel));
+    let message_id = stored_event(&pool, &event).await;
+
+    let mut scheduled = scheduled_event(scheduled_id, "Traed pociones y comida", 1);
+    scheduled.name = "vSS HM (sin enlace)".to_string();
+
+    let discord = Arc::new(FakeDiscord::new());
+    scheduled_event_update(&scheduled, discord.clone(), pool.clone()).await.unwrap();
+
+    assert_eq!(Store::new(pool).get_event(message_id).await.unwrap().title, "vSS HM (sin enlace)");
+    assert_eq!(edits(&discord, channel)[0]["embeds"][0]["title"], "vSS HM (sin enlace)");
+}
+
+#[tokio::test]
+async fn deleted_scheduled_event_cancels_the_event() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(3).unwrap());
+    let scheduled_id = next_id();
+    event.scheduled_event = Some(ScheduledEventId::new(scheduled_id));
+    let message_id = stored_event(&pool, &event).await;
+    let channel = next_id();
+    let scheduled = scheduled_event(scheduled_id, &event_link(channel, message_id), 1);
+
+    let discord = Arc::new(FakeDiscord::new());
+    scheduled_event_delete(&scheduled, discord.clone(), pool.clone()).await.unwrap();
+
+    let stored = Store::new(pool.clone()).get_event(message_id).await.unwrap();
+    assert_eq!(stored.status, EventStatus::Canceled);
+    assert_eq!(stored.scheduled_event, None);
+    assert_eq!(edits(&discord, channel).len(), 1);
+
+    // Events removed by the bot itself are left alone
+    let unknown = scheduled_event(next_id(), &event_link(channel, MessageId::new(next_id())), 1);
+    scheduled_event_delete(&unknown, discord.clone(), pool).await.unwrap();
+    assert_eq!(edits(&discord, channel).len(), 1);
+}
+
+#[tokio::test]
+async fn interested_members_are_listed_until_they_sign_up() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(3).unwrap());
+    let scheduled_id = next_id();
+    let signed = UserId::new(next_id());
+    event.scheduled_event = Some(ScheduledEventId::new(scheduled_id));
+    event.add_player(EventRole::DD, Player::new(signed, "Ya Apuntado"));
+    let message_id = stored_event(&pool, &event).await;
+    let channel = next_id();
+    let curious = next_id();
+
+    let discord = Arc::new(FakeDiscord::new());
+    discord.add_scheduled_event(scheduled_event(scheduled_id, &event_link(channel, message_id), 1))
+        .add_member(member(curious, "Curioso", &[]))
+        .add_member(member(signed.get(), "Ya Apuntado", &[]));
+
+    let guild = GuildId::new(GUILD);
+    let scheduled = ScheduledEventId::new(scheduled_id);
+    scheduled_event_interest(guild, scheduled, UserId::new(curious), true, discord.clone(), pool.clone()).await.unwrap();
+    scheduled_event_interest(guild, scheduled, signed, true, discord.clone(), pool.clone()).await.unwrap();
+
+    let stored = Store::new(pool.clone()).get_event(message_id).await.unwrap();
+    assert_eq!(stored.interested.len(), 2);
+    let pending: Vec<&str> = stored.pending_interested().iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(pending, vec!["Curioso"]);
+    let fields = edits(&discord, channel)[1]["embeds"][0]["fields"].clone();
+    assert!(fields.as_array().unwrap().iter().any(|f| f["name"] == ":star: Interesados (1)" && f["value"] == "└ Curioso"));
+
+    scheduled_event_interest(guild, scheduled, UserId::new(curious), false, discord.clone(), pool.clone()).await.unwrap();
+    assert!(Store::new(pool).get_event(message_id).await.unwrap().pending_interested().is_empty());
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::{EventRole, Player, PlayerClass};
+use events::messages::events::{absence_submit, signup_event};
+use events::Store;
+use serenity::all::{RoleId, UserId};
+use common::*;
+
+const LEADER: u64 = 100;
+
+#[tokio::test]
+async fn signup_as_starter_with_class_and_flex() {
+    let pool = pool().await;
+    let event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    let message_id = stored_event(&pool, &event).await;
+    let event_message = message_json(message_id.get(), 10, BOT);
+    let player = member(next_id(), "Galadriel", &[]);
+
+    let discord = FakeDiscord::new();
+    discord
+        .click(string_select("signup_flex", &["Healers"], &player, None))
+        .click(string_select("signup_class", &["Warden"], &player, Some("Healers")));
+
+    signup_event(&button("signup_tanks", &player, &event_message), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    let tanks = event.roles.iter().find(|pr| pr.role == EventRole::Tank).unwrap();
+    assert_eq!(tanks.players, vec![Player {
+        id: player.user.id, name: "Galadriel".to_string(), class: Some(PlayerClass::Warden), flex: vec![EventRole::Healer], absence: None
+    }]);
+
+    let dms = discord.dms();
+    assert_eq!(dms.len(), 1);
+    assert_eq!(dms[0].0, UserId::new(LEADER));
+    assert!(dms[0].1.contains("se ha apuntado"), "{}", dms[0].1);
+}
+
+#[tokio::test]
+async fn signup_to_full_role_falls_back_to_reserve() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    event.add_player(EventRole::Tank, Player::new(UserId::new(next_id()), "Tank 1"));
+    event.add_player(EventRole::Tank, Player::new(UserId::new(next_id()), "Tank 2"));
+    let message_id = stored_event(&pool, &event).await;
+    let player = member(next_id(), "Tank 3", &[]);
+
+    let discord = FakeDiscord::new();
+    discord.click(string_select("signup_class", &["Templar"], &player, None));
+
+    signup_event(&button("signup_tanks", &player, &message_json(message_id.get(), 10, BOT)), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    let tanks = event.roles.iter().find(|pr| pr.role == EventRole::Tank).unwrap();
+    let reserves = event.roles.iter().find(|pr| pr.role == EventRole::Reserve).unwrap();
+    assert_eq!(tanks.players.len(), 2);
+    assert_eq!(reserves.players.len(), 1);
+    assert_eq!(reserves.players[0].id, player.user.id);
+    assert_eq!(reserves.players[0].flex, vec![EventRole::Tank]);
+    assert!(discord.dms()[0].1.contains("como Reservas"));
+}
+
+#[tokio::test]
+async fn signup_without_roster_role_goes_to_reserve() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    event.notification_role = Some(RoleId::new(555));
+    let message_id = stored_event(&pool, &event).await;
+    let outsider = member(next_id(), "Outsider", &[]);
+    let rostered = member(next_id(), "Rostered", &[555]);
+
+    let discord = FakeDiscord::new();
+    discord.click(string_select("signup_class", &["Sorcerer"], &outsider, None));
+    signup_event(&button("signup_dd", &outsider, &message_json(message_id.get(), 10, BOT)), &discord, pool.clone()).await.unwrap();
+
+    discord.click(string_select("signup_class", &["Sorcerer"], &rostered, None));
+    signup_event(&button("signup_dd", &rostered, &message_json(message_id.get(), 10, BOT)), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    let dds = event.roles.iter().find(|pr| pr.role == EventRole::DD).unwrap();
+    let reserves = event.roles.iter().find(|pr| pr.role == EventRole::Reserve).unwrap();
+    assert_eq!(dds.players.iter().map(|p| p.id).collect::<Vec<_>>(), vec![rostered.user.id]);
+    assert_eq!(reserves.players.iter().map(|p| p.id).collect::<Vec<_>>(), vec![outsider.user.id]);
+    assert_eq!(reserves.players[0].flex, vec![EventRole::DD]);
+    assert!(discord.dms()[0].1.contains("no cumple los requisitos"));
+}
+
+#[tokio::test]
+async fn signup_absence_moves_player_out_of_starters() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    let player = member(next_id(), "Busy", &[]);
+    event.add_player(EventRole::Healer, Player::new(player.user.id, "Busy"));
+    let message_id = stored_event(&pool, &event).await;
+
+    let discord = FakeDiscord::new();
+    let event_message = message_json(message_id.get(), 10, BOT);
+    signup_event(&button("signup_ausencias", &player, &event_message), &discord, pool.clone()).await.unwrap();
+    assert_eq!(discord.responses()[0]["type"], 9);
+    absence_submit(&modal_on("absence_modal", &[("absence_reason", "Trabajo"), ("absence_from", ""), ("absence_to", "")], &player, &event_message), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert!(event.roles.iter().find(|pr| pr.role == EventRole::Healer).unwrap().players.is_empty());
+    let absent = &event.roles.iter().find(|pr| pr.role == EventRole::Absent).unwrap().players[0];
+    assert_eq!((absent.id, absent.absence.as_deref()), (player.user.id, Some("Trabajo")));
+    assert!(discord.dms()[0].1.contains("no va a poder asistir al evento en <#10>: Trabajo"), "{}", discord.dms()[0].1);
+}
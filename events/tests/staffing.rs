@@ -0,0 +1,103 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::{EventRole, Player};
+use events::messages::events::staffing_join;
+use events::tasks::staffing::{check_staffing, set_lfg_channel, Reach};
+use events::Store;
+use serenity::all::{ChannelId, RoleId, UserId};
+use common::*;
+
+const LEADER: u64 = 1600;
+
+#[tokio::test]
+async fn missing_healer_pings_flexible_reserves_in_the_thread() {
+    let pool = pool().await;
+    let (channel, thread) = (ChannelId::new(next_id()), ChannelId::new(next_id()));
+    let mut event = trial(LEADER, Utc::now() + Duration::try_hours(24).unwrap());
+    event.channel = Some(channel);
+    event.thread = Some(thread);
+    event.notification_role = Some(RoleId::new(next_id()));
+    event.add_player(EventRole::Healer, Player::new(UserId::new(next_id()), "Healer 1"));
+    let mut flexible = Player::new(UserId::new(next_id()), "Flexible");
+    flexible.flex = vec![EventRole::Healer];
+    event.add_player(EventRole::Reserve, flexible.clone());
+    event.add_player(EventRole::Reserve, Player::new(UserId::new(next_id()), "Solo DD"));
+    let message_id = stored_event(&pool, &event).await;
+
+    let discord = FakeDiscord::new();
+    let store = Store::new(pool.clone());
+    assert!(check_staffing(&discord, &store, channel, message_id, Reach::Reserves).await.unwrap());
+
+    let sent = discord.sent_messages();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0, thread);
+    let content = sent[0].1["content"].as_str().unwrap();
+    assert!(content.contains("1 Healers") && content.contains("2 Tanks") && content.contains("8 DD"), "{content}");
+    assert!(content.contains(&format!("<@{}>", flexible.id)), "{content}");
+    assert!(!content.contains("<@&"), "reserves are called before the notification role: {content}");
+    let buttons = sent[0].1["components"][0]["components"].as_array().unwrap();
+    assert!(buttons.iter().any(|b| b["custom_id"] == format!("staffing_join_{channel}_{message_id}_healers")));
+}
+
+#[tokio::test]
+async fn last_check_reaches_the_notification_role_and_lfg_channel_until_the_slot_is_filled() {
+    let pool = pool().await;
+    let (channel, lfg) = (ChannelId::new(next_id()), ChannelId::new(next_id()));
+    let role = RoleId::new(next_id());
+    let mut event = trial(LEADER, Utc::now() + Duration::try_hours(4).unwrap());
+    event.channel = Some(channel);
+    event.notification_role = Some(role);
+    event.set_max(EventRole::Tank, Some(0));
+    event.set_max(EventRole::DD, Some(0));
+    event.set_max(EventRole::Healer, Some(1));
+    let message_id = stored_event(&pool, &event).await;
+
+    set_lfg_channel(Some(lfg));
+    let discord = FakeDiscord::new();
+    let store = Store::new(pool.clone());
+    assert!(check_staffing(&discord, &store, channel, message_id, Reach::Everyone).await.unwrap());
+    let sent = discord.sent_messages();
+    assert_eq!(sent.iter().map(|(c, _)| *c).collect::<Vec<ChannelId>>(), vec![channel, lfg]);
+    assert!(sent[0].1["content"].as_str().unwrap().contains(&format!("<@&{role}>")));
+
+    // Filled slots stop the alerts
+    let mut event = store.get_event(message_id).await.unwrap();
+    let healer = Player::new(UserId::new(next_id()), "Healer");
+    event.add_player(EventRole::Healer, healer.clone());
+    store.signup_player(message_id, EventRole::Healer, &healer).await.unwrap();
+    assert!(!check_staffing(&discord, &store, channel, message_id, Reach::Everyone).await.unwrap());
+    assert_eq!(discord.sent_messages().len(), 2);
+    set_lfg_channel(None);
+}
+
+#[tokio::test]
+async fn join_button_fills_the_slot_once() {
+    let pool = pool().await;
+    let channel = ChannelId::new(next_id());
+    let mut event = trial(LEADER, Utc::now() + Duration::try_hours(4).unwrap());
+    event.channel = Some(channel);
+    event.set_max(EventRole::Healer, Some(1));
+    let message_id = stored_event(&pool, &event).await;
+    let alert = message_json(next_id(), next_id(), BOT);
+    let custom_id = format!("staffing_join_{channel}_{message_id}_healers");
+
+    let discord = FakeDiscord::new();
+    let first = member(next_id(), "Samwise", &[]);
+    staffing_join(&button(&custom_id, &first, &alert), &discord, pool.clone()).await.unwrap();
+    let second = member(next_id(), "Rosie", &[]);
+    staffing_join(&button(&custom_id, &second, &alert), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert_eq!(event.role_of(first.user.id), Some(EventRole::Healer));
+    assert_eq!(event.role_of(second.user.id), None);
+    let answers: Vec<String> = discord.responses().iter().map(|r| r["data"]["content"].as_str().unwrap().to_string()).collect();
+    assert!(answers[0].starts_with("Ya estas dentro como Healers"), "{}", answers[0]);
+    assert_eq!(answers[1], "El hueco de Healers ya esta cubierto, gracias!");
+    // The alert keeps the buttons of the roles still missing players
+    let edit = discord.calls().into_iter().find_map(|c| match c {
+        Call::EditMessage(c, m, edit) if c == alert.channel_id && m == alert.id => Some(edit["components"].to_string()),
+        _ => None
+    }).unwrap();
+    assert!(edit.contains("_tanks") && !edit.contains("_healers"), "{edit}");
+}
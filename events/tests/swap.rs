@@ -0,0 +1,136 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::{ClassQuota, EventRole, Player, PlayerClass};
+use events::messages::events::{signup_event, swap_response};
+use events::Store;
+use events::tasks::set_guild;
+use serenity::all::{GuildId, RoleId, UserId};
+use common::*;
+
+const LEADER: u64 = 500;
+
+/// Signs `requester` as tank, proposes a swap to `target` and returns the DM buttons sent to the target
+async fn propose(pool: &sqlx::PgPool, discord: &FakeDiscord, event_message: &serenity::all::Message, requester: &serenity::all::Member, target: UserId) -> String {
+    discord
+        .click(string_select("signup_class", &["Templar"], requester, None))
+        .click(button("swap_propose", requester, event_message))
+        .click(string_select("swap_target", &[&target.to_string()], requester, None));
+
+    signup_event(&button("signup_tanks", requester, event_message), discord, pool.clone()).await.unwrap();
+
+    discord.calls().into_iter()
+        .find_map(|c| match c {
+            Call::Dm(user, msg) if user == target => Some(msg["components"][0]["components"][0]["custom_id"].as_str().unwrap().to_string()),
+            _ => None
+        })
+        .expect("swap request DM")
+}
+
+#[tokio::test]
+async fn accepted_swap_trades_roles_and_flex() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    let reserve = member(next_id(), "Reservista", &[]);
+    let mut reserve_player = Player::new(reserve.user.id, "Reservista");
+    reserve_player.flex = vec![EventRole::Tank, EventRole::Healer];
+    event.add_player(EventRole::Reserve, reserve_player);
+    let message_id = stored_event(&pool, &event).await;
+    let event_message = message_json(message_id.get(), 20, BOT);
+    let tank = member(next_id(), "Titular", &[]);
+
+    let discord = FakeDiscord::new();
+    set_guild(Some(GuildId::new(GUILD)));
+    let accept = propose(&pool, &discord, &event_message, &tank, reserve.user.id).await;
+    assert!(accept.starts_with("swap_answer_accept_"));
+
+    swap_response(&button(&accept, &reserve, &message_json(next_id(), 1, BOT)), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    let tanks = &event.roles.iter().find(|pr| pr.role == EventRole::Tank).unwrap().players;
+    let reserves = &event.roles.iter().find(|pr| pr.role == EventRole::Reserve).unwrap().players;
+    assert_eq!(tanks.iter().map(|p| p.id).collect::<Vec<_>>(), vec![reserve.user.id]);
+    assert_eq!(tanks[0].flex, vec![EventRole::Healer]);
+    assert_eq!(reserves.iter().map(|p| p.id).collect::<Vec<_>>(), vec![tank.user.id]);
+    assert_eq!(reserves[0].class, Some(PlayerClass::Templar));
+    assert_eq!(reserves[0].flex, vec![EventRole::Tank]);
+    assert!(discord.dms().iter().any(|(user, msg)| *user == UserId::new(LEADER) && msg.contains("intercambiado")));
+}
+
+#[tokio::test]
+async fn swap_is_refused_when_the_reserve_breaks_the_rules() {
+    let pool = pool().await;
+    let role = next_id();
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    event.notification_role = Some(RoleId::new(role));
+    let reserve = member(next_id(), "Reservista", &[]);
+    event.add_player(EventRole::Reserve, Player::new(reserve.user.id, "Reservista"));
+    let message_id = stored_event(&pool, &event).await;
+    let event_message = message_json(message_id.get(), 20, BOT);
+    let tank = member(next_id(), "Titular", &[role]);
+
+    let discord = FakeDiscord::new();
+    discord.add_member(reserve.clone()).add_member(tank.clone());
+    set_guild(Some(GuildId::new(GUILD)));
+    let accept = propose(&pool, &discord, &event_message, &tank, reserve.user.id).await;
+
+    swap_response(&button(&accept, &reserve, &message_json(next_id(), 1, BOT)), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert_eq!(event.role_of(tank.user.id), Some(EventRole::Tank));
+    assert_eq!(event.role_of(reserve.user.id), Some(EventRole::Reserve));
+    assert!(discord.dms().iter().any(|(user, msg)| *user == tank.user.id && msg.contains("No se ha podido hacer el cambio")));
+    assert!(!discord.dms().iter().any(|(user, msg)| *user == UserId::new(LEADER) && msg.contains("intercambiado")));
+}
+
+#[tokio::test]
+async fn swap_is_refused_over_the_class_max() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    event.class_quotas = ClassQuota::parse_list("Necromancer max 1").unwrap();
+    let mut necro = Player::new(UserId::new(next_id()), "Necro 1");
+    necro.class = Some(PlayerClass::Necromancer);
+    event.add_player(EventRole::DD, necro);
+    let reserve = member(next_id(), "Necro 2", &[]);
+    let mut reserve_player = Player::new(reserve.user.id, "Necro 2");
+    reserve_player.class = Some(PlayerClass::Necromancer);
+    event.add_player(EventRole::Reserve, reserve_player);
+    let message_id = stored_event(&pool, &event).await;
+    let event_message = message_json(message_id.get(), 20, BOT);
+    let tank = member(next_id(), "Titular", &[]);
+
+    let discord = FakeDiscord::new();
+    set_guild(Some(GuildId::new(GUILD)));
+    let accept = propose(&pool, &discord, &event_message, &tank, reserve.user.id).await;
+
+    swap_response(&button(&accept, &reserve, &message_json(next_id(), 1, BOT)), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert_eq!(event.role_of(tank.user.id), Some(EventRole::Tank));
+    assert_eq!(event.role_of(reserve.user.id), Some(EventRole::Reserve));
+    let response = discord.responses().last().unwrap().clone();
+    assert!(response["data"]["embeds"][0]["description"].as_str().unwrap().contains("Nigromante"));
+}
+
+#[tokio::test]
+async fn declined_swap_keeps_roles() {
+    let pool = pool().await;
+    let mut event = trial(LEADER, Utc::now() + Duration::try_days(1).unwrap());
+    let healer = member(next_id(), "Sanadora", &[]);
+    event.add_player(EventRole::Healer, Player::new(healer.user.id, "Sanadora"));
+    let message_id = stored_event(&pool, &event).await;
+    let event_message = message_json(message_id.get(), 20, BOT);
+    let tank = member(next_id(), "Titular", &[]);
+
+    let discord = FakeDiscord::new();
+    let decline = propose(&pool, &discord, &event_message, &tank, healer.user.id).await.replace("accept", "decline");
+
+    swap_response(&button(&decline, &healer, &message_json(next_id(), 1, BOT)), &discord, pool.clone()).await.unwrap();
+    // Answering twice is rejected, the request is gone
+    swap_response(&button(&decline.replace("decline", "accept"), &healer, &message_json(next_id(), 1, BOT)), &discord, pool.clone()).await.unwrap();
+
+    let event = Store::new(pool).get_event(message_id).await.unwrap();
+    assert_eq!(event.role_of(tank.user.id), Some(EventRole::Tank));
+    assert_eq!(event.role_of(healer.user.id), Some(EventRole::Healer));
+    assert!(discord.dms().iter().any(|(user, msg)| *user == tank.user.id && msg.contains("rechazado")));
+}
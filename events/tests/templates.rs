@@ -0,0 +1,49 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use events::events::encode_roles;
+use events::messages::events::rules::event_rules;
+use events::messages::events::template::save_template;
+use events::Store;
+use serenity::all::{CommandInteraction, Member};
+use common::*;
+
+const LEADER: u64 = 1900;
+
+/// `/event template` saving a trial composition as `name`
+fn save(name: &str, user: &Member) -> CommandInteraction {
+    let code = encode_roles(&trial(LEADER, Utc::now() + Duration::try_days(1).unwrap()).roles).unwrap();
+    slash_command("event", "template", &[("name", name), ("kind", "trial"), ("code", &code)], user)
+}
+
+#[tokio::test]
+async fn templates_are_only_overwritten_by_their_owner_or_officers() {
+    let pool = pool().await;
+    let name = format!("vSS {}", next_id());
+    let owner = member(next_id(), "Owner", &[]);
+    let discord = FakeDiscord::new();
+
+    save_template(&save(&name, &owner), &discord, pool.clone()).await.unwrap();
+    save_template(&save(&name, &member(next_id(), "Other", &[])), &discord, pool.clone()).await.unwrap();
+    save_template(&save(&name, &owner), &discord, pool.clone()).await.unwrap();
+    save_template(&save(&name, &officer(next_id(), "Officer")), &discord, pool.clone()).await.unwrap();
+
+    let titles: Vec<String> = discord.responses().iter().map(|r| r["data"]["embeds"][0]["title"].as_str().unwrap().to_string()).collect();
+    assert_eq!(titles, vec!["Plantilla guardada", "Nombre ocupado", "Plantilla guardada", "Plantilla guardada"]);
+    let template = Store::new(pool).get_template(&name).await.unwrap().unwrap();
+    assert_eq!(template.owner, Some(owner.user.id), "overwriting keeps the owner");
+}
+
+#[tokio::test]
+async fn templates_without_rules_say_the_defaults_apply() {
+    let pool = pool().await;
+    let name = format!("vSS {}", next_id());
+    let owner = member(next_id(), "Owner", &[]);
+    let discord = FakeDiscord::new();
+
+    save_template(&save(&name, &owner), &discord, pool.clone()).await.unwrap();
+    event_rules(&slash_command("event", "rules", &[("template", &name), ("rules", "ninguna")], &owner), &discord, pool.clone()).await.unwrap();
+
+    let description = discord.responses()[1]["data"]["embeds"][0]["description"].as_str().unwrap().to_string();
+    assert!(description.contains("por defecto"), "{description}");
+}
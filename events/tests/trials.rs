@@ -0,0 +1,54 @@
+use duration_string::DurationString;
+use serenity::all::UserId;
+use strum::IntoEnumIterator;
+use events::events::{bundled_images, Event, EventKind, EventRole, Trial, TrialDifficulty, TRIALS};
+
+fn max(event: &Event, role: EventRole) -> Option<usize> {
+    event.roles.iter().find(|pr| pr.role == role).and_then(|pr| pr.max)
+}
+
+#[test]
+fn catalog_covers_recent_trials_in_every_difficulty() {
+    for key in ["rg", "lc", "se", "dsr", "aa"] {
+        let trial = Trial::find(key).unwrap();
+        assert!(TrialDifficulty::iter().all(|d| trial.supports(d)), "{key}");
+    }
+
+    let rockgrove = Trial::find("rg").unwrap();
+    assert_eq!(rockgrove.title(TrialDifficulty::VeteranHardMode), "vRG HM - Arboleda Rocosa");
+    assert_eq!(rockgrove.title(TrialDifficulty::Normal), "nRG - Arboleda Rocosa");
+    assert_eq!(rockgrove.duration(TrialDifficulty::Veteran).to_string(), "2h");
+    assert_eq!(rockgrove.duration(TrialDifficulty::VeteranHardMode).to_string(), "3h");
+    assert_eq!(Trial::find("lc").unwrap().name_en, "Lucent Citadel");
+
+    let mut keys: Vec<&str> = TRIALS.iter().map(|t| t.key).collect();
+    keys.sort();
+    keys.dedup();
+    assert_eq!(keys.len(), TRIALS.len());
+}
+
+#[test]
+fn picked_trial_sets_image_and_caps() {
+    let mut event = Event::new("vCR - Nubelia".to_string(), "2h".parse::<DurationString>().unwrap(), "".to_string(), UserId::new(1), EventKind::Trial);
+    Trial::find("cr").unwrap().apply(&mut event);
+    assert_eq!(event.image.as_deref(), Some("asset:trial/cr.jpg"));
+    assert_eq!((max(&event, EventRole::Tank), max(&event, EventRole::Healer), max(&event, EventRole::DD)), (Some(2), Some(3), Some(7)));
+
+    // Without a bundled image the automatic one is picked when publishing
+    let mut event = Event::new("vLC".to_string(), "2h".parse::<DurationString>().unwrap(), "".to_string(), UserId::new(1), EventKind::Trial);
+    event.image = Some("library:1:lc.png".to_string());
+    Trial::find("lc").unwrap().apply(&mut event);
+    assert_eq!(event.image, None);
+}
+
+#[test]
+fn bundled_trial_images_take_keys_from_the_catalog() {
+    std::env::set_current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/..")).unwrap();
+    let images = bundled_images(EventKind::Trial).unwrap();
+    for trial in TRIALS.iter().filter(|t| t.image.is_some()) {
+        let image = images.iter().find(|i| Some(i.file_name.as_str()) == trial.image).unwrap();
+        assert_eq!(image.key.as_deref(), Some(trial.key));
+        assert!(image.matches(&trial.title(TrialDifficulty::Veteran)) || image.matches(trial.name_en), "{}", trial.key);
+    }
+    assert!(images.iter().any(|i| i.file_name == "generic.jpg" && i.key.is_none()));
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use chrono::{Duration, NaiveDate, Utc};
+use events::events::{local_today, parse_day, vacation_window, EventRole};
+use events::messages::events::{absence_submit, vacation_command};
+use events::Store;
+use common::*;
+
+const LEADER: u64 = 1500;
+
+fn day(days: i64) -> String {
+    (local_today() + Duration::try_days(days).unwrap()).format("%d/%m/%Y").to_string()
+}
+
+#[test]
+fn days_without_year_are_the_next_ones() {
+    let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+    assert_eq!(parse_day("25/12", today).unwrap(), NaiveDate::from_ymd_opt(2026, 12, 25).unwrap());
+    assert_eq!(parse_day("01/08", today).unwrap(), NaiveDate::from_ymd_opt(2027, 8, 1).unwrap());
+    assert_eq!(parse_day("01/08/2027", today).unwrap(), NaiveDate::from_ymd_opt(2027, 8, 1).unwrap());
+    assert!(parse_day("31/02", today).is_err());
+    assert!(parse_day("mañana", today).is_err());
+
+    // Spanish days, from midnight to midnight
+    let (start, end) = vacation_window(today, today);
+    assert_eq!(start.to_rfc3339(), "2026-10-18T22:00:00+00:00");
+    assert_eq!(end.to_rfc3339(), "2026-10-19T22:00:00+00:00");
+}
+
+#[tokio::test]
+async fn vacation_signs_the_player_absent_in_every_event_inside_it() {
+    let pool = pool().await;
+    let inside = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(3).unwrap())).await;
+    let outside = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(20).unwrap())).await;
+    let player = member(next_id(), "Frodo", &[]);
+
+    let discord = FakeDiscord::new();
+    let command = slash_command("vacation", "add", &[("from", &day(1)), ("to", &day(6)), ("reason", "Viaje a Mordor")], &player);
+    vacation_command(&command, &discord, pool.clone()).await.unwrap();
+
+    let store = Store::new(pool.clone());
+    let event = store.get_event(inside).await.unwrap();
+    let (role, absent) = event.player(player.user.id).unwrap();
+    assert_eq!((role, absent.absence.as_deref()), (EventRole::Absent, Some("Viaje a Mordor")));
+    assert!(store.get_event(outside).await.unwrap().player(player.user.id).is_none());
+    assert!(discord.dms().iter().any(|(leader, dm)| leader.get() == LEADER && dm.ends_with(": Viaje a Mordor")));
+
+    let vacations = store.get_vacations(Some(player.user.id)).await.unwrap();
+    assert_eq!(vacations.len(), 1);
+    assert!(vacations[0].covers(event.datetime.unwrap()));
+}
+
+#[tokio::test]
+async fn absence_modal_with_an_end_date_saves_a_vacation() {
+    let pool = pool().await;
+    let clicked = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(1).unwrap())).await;
+    let next = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(2).unwrap())).await;
+    let player = member(next_id(), "Sam", &[]);
+
+    let discord = FakeDiscord::new();
+    let event_message = message_json(clicked.get(), next_id(), BOT);
+    absence_submit(&modal_on("absence_modal", &[("absence_reason", "Boda"), ("absence_from", ""), ("absence_to", &day(3))], &player, &event_message), &discord, pool.clone()).await.unwrap();
+
+    let store = Store::new(pool.clone());
+    for message in [clicked, next] {
+        assert_eq!(store.get_event(message).await.unwrap().role_of(player.user.id), Some(EventRole::Absent));
+    }
+    assert_eq!(store.get_vacations(Some(player.user.id)).await.unwrap()[0].reason, "Boda");
+    let answer = discord.responses()[0]["data"]["content"].as_str().unwrap().to_string();
+    assert!(answer.starts_with("🏖️ Ausencia guardada"), "{answer}");
+}
+
+#[tokio::test]
+async fn absence_modal_with_only_a_start_date_saves_that_day() {
+    let pool = pool().await;
+    let clicked = stored_event(&pool, &trial(LEADER, Utc::now() + Duration::try_days(1).unwrap())).await;
+    let player = member(next_id(), "Pippin", &[]);
+
+    let discord = FakeDiscord::new();
+    let event_message = message_json(clicked.get(), next_id(), BOT);
+    absence_submit(&modal_on("absence_modal", &[("absence_reason", "Mudanza"), ("absence_from", &day(4)), ("absence_to", "")], &player, &event_message), &discord, pool.clone()).await.unwrap();
+
+    let store = Store::new(pool.clone());
+    assert_eq!(store.get_event(clicked).await.unwrap().role_of(player.user.id), Some(EventRole::Absent));
+    let vacations = store.get_vacations(Some(player.user.id)).await.unwrap();
+    let first = local_today() + Duration::try_days(4).unwrap();
+    assert_eq!((vacations[0].start, vacations[0].end), vacation_window(first, first));
+}
@@ -0,0 +1,8 @@
+create type events.draft_step as enum ('info', 'composition', 'role', 'scope', 'date');
+
+create table events.drafts (
+    user_id bigint primary key not null,
+    step events.draft_step not null,
+    serialized_event text not null,
+    updated_at TIMESTAMPTZ not null default (now() at time zone 'utc')
+);
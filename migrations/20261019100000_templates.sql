@@ -0,0 +1,6 @@
+create table events.templates (
+    name varchar primary key not null,
+    kind events.kind not null,
+    serialized_roles text not null,
+    created_at TIMESTAMPTZ not null default (now() at time zone 'utc')
+);
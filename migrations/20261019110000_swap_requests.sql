@@ -0,0 +1,10 @@
+create table events.swap_requests (
+    id serial primary key,
+    message_id bigint not null references events.events(message_id) on delete cascade,
+    channel_id bigint not null,
+    requester bigint not null,
+    requester_role events.role not null,
+    target bigint not null,
+    target_role events.role not null,
+    created_at TIMESTAMPTZ not null default (now() at time zone 'utc')
+);
@@ -0,0 +1,5 @@
+alter table events.events
+add serialized_rules text not null default '[]';
+
+alter table events.templates
+add serialized_rules text not null default '[]';
@@ -0,0 +1,10 @@
+create table events.class_quotas (
+    message_id bigint not null references events.events(message_id) on delete cascade,
+    class events.class not null,
+    min smallint,
+    max smallint,
+    primary key (message_id, class)
+);
+
+alter table events.templates
+add serialized_quotas text not null default '[]';
@@ -0,0 +1,10 @@
+alter table events.events
+add group_size smallint,
+add group_seed bigint not null default 0;
+
+create table events.group_locks (
+    message_id bigint not null references events.events(message_id) on delete cascade,
+    user_id bigint not null,
+    group_number smallint not null,
+    primary key (message_id, user_id)
+);
@@ -0,0 +1,5 @@
+create table events.eso_accounts (
+    user_id bigint primary key not null,
+    handle varchar not null,
+    updated_at TIMESTAMPTZ not null default (now() at time zone 'utc')
+);
@@ -0,0 +1,15 @@
+create table events.images (
+    id serial primary key,
+    guild_id bigint not null,
+    kind events.kind not null,
+    file_name varchar not null,
+    data bytea not null,
+    key varchar,
+    aliases text[] not null default '{}',
+    created_at TIMESTAMPTZ not null default (now() at time zone 'utc')
+);
+
+alter table events.events
+add image varchar;
+
+alter type events.draft_step add value 'image' after 'info';
@@ -0,0 +1,12 @@
+create type events.status as enum ('scheduled', 'active', 'completed', 'canceled');
+
+alter table events.events
+add status events.status not null default 'scheduled';
+
+-- Members interested in the Discord scheduled event
+create table events.interested (
+    message_id bigint not null references events.events(message_id) on delete cascade,
+    user_id bigint not null,
+    name varchar not null,
+    primary key (message_id, user_id)
+);
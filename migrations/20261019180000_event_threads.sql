@@ -0,0 +1,11 @@
+alter table events.events
+add thread_id bigint;
+
+-- Roster and schedule changes of each event, also posted to its thread
+create table events.changes (
+    id serial primary key,
+    message_id bigint not null references events.events(message_id) on delete cascade,
+    actor bigint,
+    description varchar not null,
+    created_at TIMESTAMPTZ not null default (now() at time zone 'utc')
+);
@@ -0,0 +1,6 @@
+-- Messages the bot sends about an event besides the event message, removed along with it
+create table events.bot_messages (
+    id bigint primary key,
+    message_id bigint not null references events.events(message_id) on delete cascade,
+    channel_id bigint not null
+);
@@ -0,0 +1,3 @@
+-- Channel of the event message, so the event can be rendered without its scheduled event
+alter table events.events
+add channel_id bigint;
@@ -0,0 +1,16 @@
+-- Reason given by players signed as absent, by hand or from their vacations
+alter table events.players
+add absence varchar;
+
+-- Away periods, the players are signed as absent to every event inside them
+create table events.vacations (
+    id serial primary key,
+    user_id bigint not null,
+    name varchar not null,
+    start_at TIMESTAMPTZ not null,
+    end_at TIMESTAMPTZ not null,
+    reason varchar not null,
+    created_at TIMESTAMPTZ not null default (now() at time zone 'utc')
+);
+
+create index vacations_end_at on events.vacations(end_at);
@@ -0,0 +1,31 @@
+create type events.notification_kind as enum ('signup', 'absence', 'reserve', 'promotion');
+create type events.notification_channel as enum ('dm', 'thread', 'none');
+create type events.notification_frequency as enum ('instant', 'hourly', 'daily');
+
+-- Members without a row get every notification instantly by DM
+create table events.notification_settings (
+    user_id bigint primary key,
+    frequency events.notification_frequency not null default 'instant'
+);
+
+create table events.notification_channels (
+    user_id bigint not null,
+    kind events.notification_kind not null,
+    channel events.notification_channel not null,
+    primary key (user_id, kind)
+);
+
+-- Notifications waiting for their digest or for Discord to accept them
+create table events.notification_queue (
+    id serial primary key,
+    user_id bigint not null,
+    kind events.notification_kind not null,
+    thread_id bigint,
+    content varchar not null,
+    created_at TIMESTAMPTZ not null default (now() at time zone 'utc'),
+    due_at TIMESTAMPTZ not null,
+    sent_at TIMESTAMPTZ,
+    attempts smallint not null default 0
+);
+
+create index notification_queue_pending on events.notification_queue(due_at) where sent_at is null;
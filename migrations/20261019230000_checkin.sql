@@ -0,0 +1,11 @@
+-- Hours before the start when the starters are asked to confirm their attendance
+alter table events.events
+add checkin_hours smallint;
+
+-- Players that confirmed their attendance in the check-in window
+create table events.checkins (
+    message_id bigint not null references events.events(message_id) on delete cascade,
+    user_id bigint not null,
+    confirmed_at TIMESTAMPTZ not null default (now() at time zone 'utc'),
+    primary key (message_id, user_id)
+);
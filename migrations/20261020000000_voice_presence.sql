@@ -0,0 +1,12 @@
+-- Time the signed players spend in the voice channel of the event, evidence of their attendance
+create table events.voice_presence (
+    id serial primary key,
+    message_id bigint not null references events.events(message_id) on delete cascade,
+    user_id bigint not null,
+    channel_id bigint not null,
+    joined_at TIMESTAMPTZ not null,
+    left_at TIMESTAMPTZ
+);
+
+create index voice_presence_message_id on events.voice_presence(message_id);
+create index voice_presence_connected on events.voice_presence(user_id) where left_at is null;
@@ -0,0 +1,39 @@
+create type events.fine_reason as enum ('no_show', 'late_absence');
+
+-- Gold charged to the members with the role, role 0 is everyone without a rule of their own
+create table events.penalty_rules (
+    role_id bigint primary key,
+    no_show integer not null,
+    late_absence integer not null
+);
+
+-- Penalties announced in the signup message: 3k for Escudero and 10k for everyone else
+insert into events.penalty_rules(role_id, no_show, late_absence)
+values (0, 10000, 10000), (592733654996746253, 3000, 3000);
+
+create table events.fines (
+    id serial primary key,
+    user_id bigint not null,
+    name varchar not null,
+    message_id bigint references events.events(message_id) on delete set null,
+    event_title varchar not null,
+    reason events.fine_reason not null,
+    amount integer not null,
+    created_at TIMESTAMPTZ not null default (now() at time zone 'utc'),
+    waived_by bigint,
+    waived_at TIMESTAMPTZ,
+    unique (user_id, message_id)
+);
+
+create index fines_user_id on events.fines(user_id);
+
+-- Gold deposited to the guild bank for the fines
+create table events.fine_payments (
+    id serial primary key,
+    user_id bigint not null,
+    amount integer not null,
+    recorded_by bigint,
+    paid_at TIMESTAMPTZ not null default (now() at time zone 'utc')
+);
+
+create index fine_payments_user_id on events.fine_payments(user_id);
@@ -0,0 +1,14 @@
+-- Guild bank deposits imported from the in-game history, only the ones of registered accounts
+create table events.bank_deposits (
+    deposit_key varchar primary key,
+    user_id bigint not null,
+    account varchar not null,
+    amount integer not null,
+    deposited_at TIMESTAMPTZ not null,
+    -- Gold that went to the fines, the rest was over what the member owed
+    applied integer not null,
+    imported_by bigint,
+    imported_at TIMESTAMPTZ not null default (now() at time zone 'utc')
+);
+
+alter table events.fine_payments add column deposit_key varchar references events.bank_deposits(deposit_key) on delete cascade;
@@ -0,0 +1,10 @@
+create type events.alliance as enum ('aldmeri', 'daggerfall', 'ebonheart');
+create type events.pvp_style as enum ('open_field', 'keep_siege', 'battlegrounds', 'small_scale');
+
+-- PvP events were always shown as Ebonheart Pact, the alliance of the guild
+alter table events.events add column pvp_alliance events.alliance;
+alter table events.events add column pvp_campaign varchar;
+alter table events.events add column pvp_style events.pvp_style;
+alter table events.events add column pvp_min_cp integer;
+
+update events.events set pvp_alliance = 'ebonheart' where kind = 'pvp';
@@ -0,0 +1,3 @@
+-- Templates saved before owners were recorded can only be overwritten by the officers
+alter table events.templates
+add owner_id bigint;
@@ -1,4 +1,4 @@
-use serenity::all::{CommandType, Context, CreateCommand, GuildId};
+use serenity::all::{CommandOptionType, CommandType, Context, CreateCommand, CreateCommandOption, GuildId, Permissions};
 use tracing::{error, info, info_span, Instrument};
 
 pub async fn register_commands(ctx: &Context, guild: GuildId) {
@@ -8,6 +8,238 @@ pub async fn register_commands(ctx: &Context, guild: GuildId) {
             .description("Event management")
             .description_localized("es-ES", "Gestión de eventos")
         ).await;
+        register_command(ctx, guild, CreateCommand::new("event")
+            .description("Event management shortcuts")
+            .description_localized("es-ES", "Atajos de gestión de eventos")
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "create", "Create an event without the wizard")
+                .description_localized("es-ES", "Crea un evento sin pasar por el asistente")
+                .add_sub_option(event_kind_option())
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "title", "Event title")
+                    .description_localized("es-ES", "Titulo del evento")
+                    .required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "date", "Weekday and time, e.g. miércoles 21:30")
+                    .description_localized("es-ES", "Dia y hora, ejemplo: miércoles 21:30")
+                    .required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "channel", "Day channel of the event")
+                    .description_localized("es-ES", "Canal del dia del evento")
+                    .set_autocomplete(true)
+                    .required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "duration", "Duration, e.g. 2h (default 2h)")
+                    .description_localized("es-ES", "Duracion, ejemplo: 2h (por defecto 2h)"))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "template", "Saved composition template")
+                    .description_localized("es-ES", "Plantilla de composicion guardada")
+                    .set_autocomplete(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "description", "Event description")
+                    .description_localized("es-ES", "Descripcion del evento"))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Role, "role", "Roster role allowed to sign up as starter")
+                    .description_localized("es-ES", "Rol de roster que puede apuntarse como titular"))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "scope", "Who can sign up (default open)")
+                    .description_localized("es-ES", "Quien puede apuntarse (por defecto abierto)")
+                    .add_string_choice_localized("Open", "public", [("es-ES", "Abierto")])
+                    .add_string_choice_localized("Semi-open", "semi_public", [("es-ES", "Semi-abierto")])
+                    .add_string_choice_localized("Closed", "private", [("es-ES", "Cerrado")]))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "template", "Save a composition template from its code")
+                .description_localized("es-ES", "Guarda una plantilla de composicion a partir de su codigo")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "name", "Template name, e.g. Roster A")
+                    .description_localized("es-ES", "Nombre de la plantilla, ejemplo: Roster A")
+                    .required(true))
+                .add_sub_option(event_kind_option())
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "code", "Template code shown by the wizard")
+                    .description_localized("es-ES", "Codigo de plantilla que muestra el asistente")
+                    .required(true))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "rules", "Show or change the starter signup rules of an event or template")
+                .description_localized("es-ES", "Muestra o cambia las reglas para apuntarse como titular a un evento o plantilla")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "event", "Event message link or id")
+                    .description_localized("es-ES", "Enlace o id del mensaje del evento"))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "template", "Saved composition template")
+                    .description_localized("es-ES", "Plantilla de composicion guardada")
+                    .set_autocomplete(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "rules", "e.g. rol @Roster => reserva; max-semana 2 => aprobacion ('ninguna' to reset)")
+                    .description_localized("es-ES", "Ejemplo: rol @Roster => reserva; max-semana 2 => aprobacion ('ninguna' vuelve a las de por defecto)"))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "quotas", "Show or change the class quotas of an event or template")
+                .description_localized("es-ES", "Muestra o cambia los cupos de clase de un evento o plantilla")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "event", "Event message link or id")
+                    .description_localized("es-ES", "Enlace o id del mensaje del evento"))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "template", "Saved composition template")
+                    .description_localized("es-ES", "Plantilla de composicion guardada")
+                    .set_autocomplete(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "quotas", "e.g. Necromancer max 2; Warden min 1 ('ninguna' to reset)")
+                    .description_localized("es-ES", "Ejemplo: Necromancer max 2; Warden min 1 ('ninguna' para quitarlos)"))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "announce", "Post an announcement to the players of an event")
+                .description_localized("es-ES", "Envia un anuncio a los jugadores de un evento")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "event", "Event message link")
+                    .description_localized("es-ES", "Enlace del mensaje del evento")
+                    .required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "message", "Announcement")
+                    .description_localized("es-ES", "Anuncio")
+                    .required(true))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "image", "Upload an image to the event image library")
+                .description_localized("es-ES", "Sube una imagen a la biblioteca de imagenes de eventos")
+                .add_sub_option(event_kind_option().required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Attachment, "file", "Image file")
+                    .description_localized("es-ES", "Archivo de imagen")
+                    .required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "key", "Trial short name, e.g. so")
+                    .description_localized("es-ES", "Nombre corto de la trial, ejemplo: so"))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "aliases", "Words in titles that pick it, comma separated, e.g. sanctum ophidia, santuario")
+                    .description_localized("es-ES", "Palabras del titulo que la eligen, separadas por comas, ejemplo: sanctum ophidia, santuario"))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "checkin", "Ask the starters to confirm their attendance before the event")
+                .description_localized("es-ES", "Pide a los titulares que confirmen su asistencia antes del evento")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "event", "Event message link")
+                    .description_localized("es-ES", "Enlace del mensaje del evento")
+                    .required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "hours", "Hours before the start when the check-in opens")
+                    .description_localized("es-ES", "Horas antes del inicio en las que se abre el check-in")
+                    .required(true)
+                    .add_string_choice_localized("Off", "off", [("es-ES", "Desactivado")])
+                    .add_string_choice("2h", "2")
+                    .add_string_choice("4h", "4")
+                    .add_string_choice("6h", "6")
+                    .add_string_choice("12h", "12")
+                    .add_string_choice("24h", "24"))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "list", "Upcoming events, PvP ones can be filtered by campaign, alliance and style")
+                .description_localized("es-ES", "Proximos eventos, los de PvP se pueden filtrar por campaña, alianza y estilo")
+                .add_sub_option(event_kind_option().required(false))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "campaign", "PvP campaign, e.g. Grey Host")
+                    .description_localized("es-ES", "Campaña de PvP, ejemplo: Grey Host"))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "alliance", "PvP alliance")
+                    .description_localized("es-ES", "Alianza de PvP")
+                    .add_string_choice_localized("Aldmeri Dominion", "ad", [("es-ES", "Dominio Aldmeri")])
+                    .add_string_choice_localized("Daggerfall Covenant", "dc", [("es-ES", "Pacto de Daggerfall")])
+                    .add_string_choice_localized("Ebonheart Pact", "ep", [("es-ES", "Pacto de Ebonheart")]))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "style", "PvP style")
+                    .description_localized("es-ES", "Estilo de PvP")
+                    .add_string_choice_localized("Open field", "open_field", [("es-ES", "Campo abierto")])
+                    .add_string_choice_localized("Keep siege", "keep_siege", [("es-ES", "Asedio de fortalezas")])
+                    .add_string_choice_localized("Battlegrounds", "battlegrounds", [("es-ES", "Campos de batalla")])
+                    .add_string_choice_localized("Small-scale", "small_scale", [("es-ES", "Grupo reducido")]))
+            )
+        ).await;
+        register_command(ctx, guild, CreateCommand::new("roster")
+            .description("Officer roster management")
+            .description_localized("es-ES", "Gestión de la lista de jugadores para oficiales")
+            .default_member_permissions(Permissions::MANAGE_EVENTS)
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "move", "Move a signed player to another role")
+                .description_localized("es-ES", "Mueve a un jugador apuntado a otro rol")
+                .add_sub_option(roster_event_option())
+                .add_sub_option(roster_player_option())
+                .add_sub_option(roster_role_option().required(true))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a player from the event")
+                .description_localized("es-ES", "Quita a un jugador del evento")
+                .add_sub_option(roster_event_option())
+                .add_sub_option(roster_player_option())
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Sign up a member to the event")
+                .description_localized("es-ES", "Apunta a un miembro al evento")
+                .add_sub_option(roster_event_option())
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::User, "player", "Member")
+                    .description_localized("es-ES", "Miembro")
+                    .required(true))
+                .add_sub_option(roster_role_option().required(true))
+                .add_sub_option(roster_class_option())
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "set-class", "Change the class of a signed player")
+                .description_localized("es-ES", "Cambia la clase de un jugador apuntado")
+                .add_sub_option(roster_event_option())
+                .add_sub_option(roster_player_option())
+                .add_sub_option(roster_class_option().required(true))
+            )
+        ).await;
+        register_command(ctx, guild, CreateCommand::new("vacation")
+            .description("Away periods, you are signed as absent to every event inside them")
+            .description_localized("es-ES", "Periodos de ausencia, se te apunta como ausente a todos los eventos de esas fechas")
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Save an away period")
+                .description_localized("es-ES", "Guarda un periodo de ausencia")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "from", "First day, e.g. 01/08")
+                    .description_localized("es-ES", "Primer dia, ejemplo: 01/08")
+                    .required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "to", "Last day, e.g. 15/08")
+                    .description_localized("es-ES", "Ultimo dia, ejemplo: 15/08")
+                    .required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "reason", "Reason shown to the leaders")
+                    .description_localized("es-ES", "Motivo que veran los lideres")
+                    .required(true))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "list", "Show your away periods")
+                .description_localized("es-ES", "Muestra tus periodos de ausencia")
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "cancel", "Cancel an away period")
+                .description_localized("es-ES", "Cancela un periodo de ausencia")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "period", "Away period")
+                    .description_localized("es-ES", "Periodo de ausencia")
+                    .set_autocomplete(true)
+                    .required(true))
+            )
+        ).await;
+        register_command(ctx, guild, CreateCommand::new("notifications")
+            .description("How often and where you get the event notices")
+            .description_localized("es-ES", "Cada cuanto y donde te llegan los avisos de los eventos")
+            .add_option(CreateCommandOption::new(CommandOptionType::String, "frequency", "Send them at once or in a digest")
+                .description_localized("es-ES", "Enviarlos al momento o en un resumen")
+                .add_string_choice_localized("Instant", "instant", [("es-ES", "Al momento")])
+                .add_string_choice_localized("Hourly digest", "hourly", [("es-ES", "Resumen cada hora")])
+                .add_string_choice_localized("Daily digest", "daily", [("es-ES", "Resumen diario")]))
+            .add_option(notification_channel_option("signup", "Signups to your events", "Inscripciones a tus eventos"))
+            .add_option(notification_channel_option("absence", "Absences in your events", "Ausencias en tus eventos"))
+            .add_option(notification_channel_option("reserve", "Players moved to reserve", "Jugadores movidos a reserva"))
+            .add_option(notification_channel_option("promotion", "You are promoted to starter", "Te suben a titular"))
+        ).await;
+        register_command(ctx, guild, CreateCommand::new("eso-account")
+            .description("Register your ESO account for event invites")
+            .description_localized("es-ES", "Registra tu cuenta de ESO para las invitaciones de eventos")
+            .add_option(CreateCommandOption::new(CommandOptionType::String, "handle", "ESO account, e.g. @MyAccount")
+                .description_localized("es-ES", "Cuenta de ESO, ejemplo: @MiCuenta"))
+        ).await;
+        register_command(ctx, guild, CreateCommand::new("fines")
+            .description("Fines for no-shows and late absences")
+            .description_localized("es-ES", "Multas por no presentarse o ausentarse a ultima hora")
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "me", "Show your fines")
+                .description_localized("es-ES", "Muestra tus multas")
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "list", "Treasurers: outstanding fines")
+                .description_localized("es-ES", "Tesoreros: multas pendientes")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::User, "player", "Member, everyone by default")
+                    .description_localized("es-ES", "Miembro, todos por defecto"))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "pay", "Treasurers: record a payment")
+                .description_localized("es-ES", "Tesoreros: registra un pago")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::User, "player", "Member")
+                    .description_localized("es-ES", "Miembro")
+                    .required(true))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "amount", "Gold, e.g. 3000 or 3k, everything owed by default")
+                    .description_localized("es-ES", "Oro, ejemplo: 3000 o 3k, todo lo pendiente por defecto"))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "import", "Treasurers: reconcile the guild bank history")
+                .description_localized("es-ES", "Tesoreros: cuadra las multas con el historial del banco del gremio")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Attachment, "file", "CSV with @account, gold and time per deposit")
+                    .description_localized("es-ES", "CSV con @cuenta, oro y fecha de cada ingreso")
+                    .required(true))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "waive", "Treasurers: waive a fine")
+                .description_localized("es-ES", "Tesoreros: perdona una multa")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Integer, "fine", "Fine number, e.g. 12")
+                    .description_localized("es-ES", "Numero de la multa, ejemplo: 12")
+                    .min_int_value(1)
+                    .required(true))
+            )
+            .add_option(CreateCommandOption::new(CommandOptionType::SubCommand, "rule", "Treasurers: fine amounts of a role")
+                .description_localized("es-ES", "Tesoreros: importe de las multas de un rol")
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::Role, "role", "Role, everyone else by default")
+                    .description_localized("es-ES", "Rol, el resto de miembros por defecto"))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "no_show", "Gold for not showing up")
+                    .description_localized("es-ES", "Oro por no presentarse"))
+                .add_sub_option(CreateCommandOption::new(CommandOptionType::String, "late_absence", "Gold for an absence in the last 24h")
+                    .description_localized("es-ES", "Oro por ausentarse en las ultimas 24h"))
+            )
+        ).await;
         register_command(ctx, guild, CreateCommand::new("Edit event")
             .name_localized("es-ES","Editar evento")
             .kind(CommandType::Message)
@@ -19,6 +251,61 @@ pub async fn register_commands(ctx: &Context, guild: GuildId) {
     }.instrument(span).await;
 }
 
+fn event_kind_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "kind", "Event kind")
+        .description_localized("es-ES", "Tipo de evento")
+        .add_string_choice("Trial", "trial")
+        .add_string_choice("PvP", "pvp")
+        .required(true)
+}
+
+fn notification_channel_option(name: &str, description: &str, description_es: &str) -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, name, description)
+        .description_localized("es-ES", description_es)
+        .add_string_choice_localized("Direct message", "dm", [("es-ES", "Mensaje privado")])
+        .add_string_choice_localized("Event thread", "thread", [("es-ES", "Hilo del evento")])
+        .add_string_choice_localized("None", "none", [("es-ES", "Desactivado")])
+}
+
+fn roster_event_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "event", "Upcoming event")
+        .description_localized("es-ES", "Proximo evento")
+        .set_autocomplete(true)
+        .required(true)
+}
+
+fn roster_player_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "player", "Signed player")
+        .description_localized("es-ES", "Jugador apuntado")
+        .set_autocomplete(true)
+        .required(true)
+}
+
+fn roster_role_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "role", "Role")
+        .description_localized("es-ES", "Rol")
+        .add_string_choice("Tanks", "tanks")
+        .add_string_choice("Healers", "healers")
+        .add_string_choice("DD", "dd")
+        .add_string_choice("Brawlers", "brawlers")
+        .add_string_choice("Bombers", "bombers")
+        .add_string_choice("Gankers", "gankers")
+        .add_string_choice("Reservas", "reservas")
+        .add_string_choice("Ausencias", "ausencias")
+}
+
+fn roster_class_option() -> CreateCommandOption {
+    CreateCommandOption::new(CommandOptionType::String, "class", "Class")
+        .description_localized("es-ES", "Clase")
+        .add_string_choice("Arcanist", "Arcanist")
+        .add_string_choice("Necromancer", "Necromancer")
+        .add_string_choice("Warden", "Warden")
+        .add_string_choice("Dragon Knight", "DragonKnight")
+        .add_string_choice("Templar", "Templar")
+        .add_string_choice("Sorcerer", "Sorcerer")
+        .add_string_choice("Nightblade", "NightBlade")
+}
+
 async fn register_command(ctx: &Context, guild: GuildId, builder: CreateCommand) {
     let command = guild.create_command(&ctx.http, builder).await;
     match command {
@@ -1,27 +1,56 @@
 mod commands;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use serenity::async_trait;
 use serenity::model::gateway::Ready;
-use serenity::model::id::{GuildId};
-use serenity::model::prelude::{Interaction};
+use serenity::model::id::{ChannelId, GuildId, RoleId};
+use serenity::model::prelude::{GuildScheduledEventUserAddEvent, GuildScheduledEventUserRemoveEvent, Interaction, ScheduledEvent, VoiceState};
 use serenity::prelude::*;
 use shuttle_runtime::SecretStore;
-use tracing::{error, info};
+use tracing::info;
 use sqlx::PgPool;
+use events::messages::failures::{report_failure, Failure};
 use crate::commands::register_commands;
 
+/// The bot plus the `/metrics` and `/healthz` routes on the address given by Shuttle
+struct Leki {
+    client: Client,
+    pool: PgPool
+}
+
+#[async_trait]
+impl shuttle_runtime::Service for Leki {
+    async fn bind(mut self, addr: SocketAddr) -> Result<(), shuttle_runtime::Error> {
+        events::metrics::spawn_server(addr, self.pool, self.client.shard_manager.clone());
+        self.client.start_autosharded().await.map_err(shuttle_runtime::CustomError::new)?;
+        Ok(())
+    }
+}
+
 struct Bot {
     guild: GuildId,
     pool: PgPool,
-    announcement_hook: String
+    announcement_hook: String,
+    /// Channel where internal errors are forwarded
+    admin_log: Option<ChannelId>
+}
+
+impl Bot {
+    async fn report(&self, ctx: &Context, interaction: Option<&Interaction>, handler: &str, failure: Failure) {
+        report_failure(ctx, interaction, handler, failure, self.admin_log).await;
+    }
+}
+
+fn crafting_failure(why: &crafting::Error) -> Failure {
+    Failure::new(why.into(), why.user_message_es(), format!("{why:#?}"))
 }
 
 #[shuttle_runtime::main]
 async fn serenity(
     #[shuttle_runtime::Secrets] secret_store: SecretStore,
     #[shuttle_shared_db::Postgres] pool: PgPool
-) -> shuttle_serenity::ShuttleSerenity {
+) -> Result<Leki, shuttle_runtime::Error> {
     sqlx::migrate!().run(&pool).await.expect("Migrations failed :(");
 
     let token = secret_store.get("DISCORD_TOKEN")
@@ -32,15 +61,28 @@ async fn serenity(
         .expect("'DISCORD_GUILD' was not found");
     let announcement_hook = secret_store.get("DISCORD_ANNOUNCEMENTS_HOOK")
         .expect("'DISCORD_TOKEN' was not found");
+    let admin_log = secret_store.get("DISCORD_ADMIN_LOG_CHANNEL")
+        .map(|channel| channel.parse::<u64>().expect("DISCORD_ADMIN_LOG_CHANNEL invalid u64"))
+        .map(ChannelId::new);
+    events::tasks::staffing::set_lfg_channel(secret_store.get("DISCORD_LFG_CHANNEL")
+        .map(|channel| channel.parse::<u64>().expect("DISCORD_LFG_CHANNEL invalid u64"))
+        .map(ChannelId::new));
+    events::tasks::fines::set_treasury_channel(secret_store.get("DISCORD_TREASURY_CHANNEL")
+        .map(|channel| channel.parse::<u64>().expect("DISCORD_TREASURY_CHANNEL invalid u64"))
+        .map(ChannelId::new));
+    events::messages::fines::set_treasurer_role(secret_store.get("DISCORD_TREASURER_ROLE")
+        .map(|role| role.parse::<u64>().expect("DISCORD_TREASURER_ROLE invalid u64"))
+        .map(RoleId::new));
+    events::tasks::set_guild(Some(guild));
 
-    let intents = GatewayIntents::DIRECT_MESSAGES | GatewayIntents::GUILD_SCHEDULED_EVENTS;
+    let intents = GatewayIntents::DIRECT_MESSAGES | GatewayIntents::GUILD_SCHEDULED_EVENTS | GatewayIntents::GUILD_VOICE_STATES;
 
     let client = Client::builder(&token, intents)
-        .event_handler(Bot { guild, pool, announcement_hook })
+        .event_handler(Bot { guild, pool: pool.clone(), announcement_hook, admin_log })
         .await
         .expect("Err creating client");
 
-    Ok(client.into())
+    Ok(Leki { client, pool })
 }
 
 fn get_interaction_guild(interaction: &Interaction) -> Option<GuildId> {
@@ -61,38 +103,127 @@ impl EventHandler for Bot {
         register_commands(&ctx, self.guild).await;
         crafting::register_commands(self.guild, &ctx).await;
 
+        events::tasks::notifications::spawn_notifier(Arc::new(ctx.clone()), Arc::new(events::Store::new(self.pool.clone())));
+        events::tasks::fines::spawn_fine_summaries(Arc::new(ctx.clone()), Arc::new(events::Store::new(self.pool.clone())));
         events::tasks::reset_all_reminders(Arc::new(ctx), self.guild, self.pool.clone()).await;
     }
 
+    async fn guild_scheduled_event_update(&self, ctx: Context, event: ScheduledEvent) {
+        if event.guild_id != self.guild {
+            return;
+        }
+
+        if let Err(why) = events::messages::events::scheduled_event_update(&event, Arc::new(ctx.clone()), self.pool.clone()).await {
+            self.report(&ctx, None, "Scheduled event update", (&why).into()).await;
+        }
+    }
+
+    async fn guild_scheduled_event_delete(&self, ctx: Context, event: ScheduledEvent) {
+        if event.guild_id != self.guild {
+            return;
+        }
+
+        if let Err(why) = events::messages::events::scheduled_event_delete(&event, Arc::new(ctx.clone()), self.pool.clone()).await {
+            self.report(&ctx, None, "Scheduled event delete", (&why).into()).await;
+        }
+    }
+
+    async fn guild_scheduled_event_user_add(&self, ctx: Context, subscribed: GuildScheduledEventUserAddEvent) {
+        if subscribed.guild_id != self.guild {
+            return;
+        }
+
+        if let Err(why) = events::messages::events::scheduled_event_interest(subscribed.guild_id, subscribed.scheduled_event_id, subscribed.user_id, true, Arc::new(ctx.clone()), self.pool.clone()).await {
+            self.report(&ctx, None, "Scheduled event interest", (&why).into()).await;
+        }
+    }
+
+    async fn guild_scheduled_event_user_remove(&self, ctx: Context, unsubscribed: GuildScheduledEventUserRemoveEvent) {
+        if unsubscribed.guild_id != self.guild {
+            return;
+        }
+
+        if let Err(why) = events::messages::events::scheduled_event_interest(unsubscribed.guild_id, unsubscribed.scheduled_event_id, unsubscribed.user_id, false, Arc::new(ctx.clone()), self.pool.clone()).await {
+            self.report(&ctx, None, "Scheduled event interest", (&why).into()).await;
+        }
+    }
+
+    async fn voice_state_update(&self, ctx: Context, _old: Option<VoiceState>, new: VoiceState) {
+        if new.guild_id != Some(self.guild) {
+            return;
+        }
+
+        if let Err(why) = events::tasks::attendance::voice_state_update(&new, &events::Store::new(self.pool.clone())).await {
+            self.report(&ctx, None, "Voice state update", (&why).into()).await;
+        }
+    }
+
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         if get_interaction_guild(&interaction).is_some_and(|g| g != self.guild) {
             return;
         }
+        let _timer = events::metrics::InteractionTimer::start(&interaction);
 
-        match interaction {
+        match &interaction {
             Interaction::Command(command) => {
                 info!("Command interaction: {}", command.data.name);
                 if command.data.name == "events" {
-                    if let Err(why) = events::messages::events::create_event(&command, &ctx, self.pool.clone(), &self.announcement_hook).await {
-                        error!("Create event: {why:#?}");
+                    if let Err(why) = events::messages::events::create_event(command, &ctx, self.pool.clone(), &self.announcement_hook).await {
+                        self.report(&ctx, Some(&interaction), "Create event", (&why).into()).await;
+                    }
+                }
+
+                if command.data.name == "event" {
+                    if let Err(why) = events::messages::events::event_command(command, &ctx, self.pool.clone(), &self.announcement_hook).await {
+                        self.report(&ctx, Some(&interaction), "Event command", (&why).into()).await;
+                    }
+                }
+
+                if command.data.name == "roster" {
+                    if let Err(why) = events::messages::events::roster_command(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Roster command", (&why).into()).await;
+                    }
+                }
+
+                if command.data.name == "vacation" {
+                    if let Err(why) = events::messages::events::vacation_command(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Vacation command", (&why).into()).await;
+                    }
+                }
+
+                if command.data.name == "notifications" {
+                    if let Err(why) = events::messages::notifications::notifications_command(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Notifications command", (&why).into()).await;
+                    }
+                }
+
+                if command.data.name == "eso-account" {
+                    if let Err(why) = events::messages::accounts::eso_account_command(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "ESO account", (&why).into()).await;
+                    }
+                }
+
+                if command.data.name == "fines" {
+                    if let Err(why) = events::messages::fines::fines_command(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Fines command", (&why).into()).await;
                     }
                 }
 
                 if command.data.name == "Edit event" {
-                    if let Err(why) = events::messages::events::edit_event(&command, &ctx, self.pool.clone()).await {
-                        error!("Edit event: {why:#?}");
+                    if let Err(why) = events::messages::events::edit_event(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Edit event", (&why).into()).await;
                     }
                 }
 
                 if command.data.name == "Delete event" {
-                    if let Err(why) = events::messages::events::delete_event(&command, &ctx, self.pool.clone()).await {
-                        error!("Edit event: {why:#?}");
+                    if let Err(why) = events::messages::events::delete_event(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Delete event", (&why).into()).await;
                     }
                 }
 
                 if command.data.name == "gear" {
-                    if let Err(why) = crafting::gear_set_request(&command, &ctx).await {
-                        error!("Edit event: {why:#?}");
+                    if let Err(why) = crafting::gear_set_request(command, &ctx).await {
+                        self.report(&ctx, Some(&interaction), "Gear request", crafting_failure(&why)).await;
                     }
                 }
             }
@@ -100,14 +231,66 @@ impl EventHandler for Bot {
                 info!("Component interaction: {}", component.data.custom_id);
 
                 if component.data.custom_id.starts_with("signup") {
-                    if let Err(why) = events::messages::events::signup_event(&component, &ctx, self.pool.clone()).await {
-                        error!("Signup event: {why:#?}");
+                    if let Err(why) = events::messages::events::signup_event(component, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Signup event", (&why).into()).await;
+                    }
+                }
+
+                if component.data.custom_id.starts_with("eligibility") {
+                    if let Err(why) = events::messages::events::signup_approval(component, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Signup approval", (&why).into()).await;
+                    }
+                }
+
+                if component.data.custom_id.starts_with("staffing_join") {
+                    if let Err(why) = events::messages::events::staffing_join(component, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Staffing join", (&why).into()).await;
+                    }
+                }
+
+                if component.data.custom_id.starts_with("checkin_confirm") {
+                    if let Err(why) = events::messages::events::checkin_confirm(component, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Check-in confirm", (&why).into()).await;
+                    }
+                }
+
+                if component.data.custom_id.starts_with("attendance_") {
+                    if let Err(why) = events::messages::events::attendance_answer(component, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Attendance answer", (&why).into()).await;
+                    }
+                }
+
+                if component.data.custom_id.starts_with("swap_answer") {
+                    if let Err(why) = events::messages::events::swap_response(component, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Swap response", (&why).into()).await;
+                    }
+                }
+            }
+            Interaction::Modal(modal) => {
+                info!("Modal interaction: {}", modal.data.custom_id);
+
+                if modal.data.custom_id == "absence_modal" {
+                    if let Err(why) = events::messages::events::absence_submit(modal, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Absence", (&why).into()).await;
                     }
                 }
             }
-            Interaction::Modal(m) => {info!("Modal interaction: {}", m.data.custom_id)},
             Interaction::Autocomplete(command) => {
-                crafting::gear_set_autocomplete(command, &ctx).await.unwrap();
+                if command.data.name == "event" {
+                    if let Err(why) = events::messages::events::event_autocomplete(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Event autocomplete", (&why).into()).await;
+                    }
+                } else if command.data.name == "vacation" {
+                    if let Err(why) = events::messages::events::vacation_autocomplete(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Vacation autocomplete", (&why).into()).await;
+                    }
+                } else if command.data.name == "roster" {
+                    if let Err(why) = events::messages::events::roster_autocomplete(command, &ctx, self.pool.clone()).await {
+                        self.report(&ctx, Some(&interaction), "Roster autocomplete", (&why).into()).await;
+                    }
+                } else if let Err(why) = crafting::gear_set_autocomplete(command.clone(), &ctx).await {
+                    self.report(&ctx, Some(&interaction), "Gear autocomplete", crafting_failure(&why)).await;
+                }
             }
             _ => {}
         }